@@ -0,0 +1,155 @@
+//! Analog-from-button emulation: maps discrete WASD / arrow key-down and
+//! key-up events onto the same synthesized joystick vectors a physical
+//! gamepad or on-screen touch joystick would produce (see `gamepad` /
+//! `dom_controls`), so keyboard players drive the engine's analog-only
+//! movement/look code path with the same ramped acceleration feel as a
+//! physical stick instead of snapping straight to full deflection.
+//!
+//! WASD drives `TranslationJoystickMoved`, the arrow keys drive
+//! `PitchYawJoystickMoved`.
+
+use crate::dom_controls::{send_dom_controls_user_event, DomControlsUserEvent};
+use winit::event::VirtualKeyCode;
+
+/// Time to ramp an axis from centered to full deflection.
+const RAMP_TIME_SECONDS: f64 = 0.12;
+
+#[derive(Clone, Copy)]
+struct StickState {
+    target: (f32, f32),
+    current: (f32, f32),
+    active: bool,
+}
+
+impl StickState {
+    fn new() -> Self {
+        Self { target: (0.0, 0.0), current: (0.0, 0.0), active: false }
+    }
+}
+
+pub struct AnalogFromButton {
+    translation: StickState,
+    pitch_yaw: StickState,
+
+    w_down: bool,
+    a_down: bool,
+    s_down: bool,
+    d_down: bool,
+    up_down: bool,
+    left_down: bool,
+    down_down: bool,
+    right_down: bool,
+
+    last_tick_seconds: Option<f64>,
+}
+
+impl AnalogFromButton {
+    pub fn new() -> Self {
+        Self {
+            translation: StickState::new(),
+            pitch_yaw: StickState::new(),
+            w_down: false,
+            a_down: false,
+            s_down: false,
+            d_down: false,
+            up_down: false,
+            left_down: false,
+            down_down: false,
+            right_down: false,
+            last_tick_seconds: None,
+        }
+    }
+
+    /// Updates key state and recomputes the target (not yet ramped)
+    /// deflection for whichever stick that key belongs to. Keys outside
+    /// WASD/arrows are ignored.
+    pub fn handle_key(&mut self, key: VirtualKeyCode, pressed: bool) {
+        match key {
+            VirtualKeyCode::W => self.w_down = pressed,
+            VirtualKeyCode::A => self.a_down = pressed,
+            VirtualKeyCode::S => self.s_down = pressed,
+            VirtualKeyCode::D => self.d_down = pressed,
+            VirtualKeyCode::Up => self.up_down = pressed,
+            VirtualKeyCode::Left => self.left_down = pressed,
+            VirtualKeyCode::Down => self.down_down = pressed,
+            VirtualKeyCode::Right => self.right_down = pressed,
+            _ => return,
+        }
+
+        self.translation.target = (
+            axis_target(self.d_down, self.a_down),
+            axis_target(self.w_down, self.s_down),
+        );
+        self.pitch_yaw.target = (
+            axis_target(self.right_down, self.left_down),
+            axis_target(self.up_down, self.down_down),
+        );
+    }
+
+    /// Ramps both sticks' emitted vectors toward their targets by however
+    /// much time has passed since the last call, and dispatches the
+    /// corresponding `Moved`/`Released` `DomControlsUserEvent`. Call once per
+    /// frame.
+    pub fn tick(&mut self) {
+        let now = now_seconds();
+        let dt = match self.last_tick_seconds {
+            Some(last) => now - last,
+            None => 0.0,
+        };
+        self.last_tick_seconds = Some(now);
+
+        let step = (dt / RAMP_TIME_SECONDS) as f32;
+        ramp_and_emit(&mut self.translation, step, |vector| {
+            DomControlsUserEvent::TranslationJoystickMoved { vector }
+        }, DomControlsUserEvent::TranslationJoystickReleased);
+        ramp_and_emit(&mut self.pitch_yaw, step, |vector| {
+            DomControlsUserEvent::PitchYawJoystickMoved { vector }
+        }, DomControlsUserEvent::PitchYawJoystickReleased);
+    }
+}
+
+fn ramp_and_emit(
+    state: &mut StickState,
+    step: f32,
+    moved: impl Fn((f64, f64)) -> DomControlsUserEvent,
+    released: DomControlsUserEvent,
+) {
+    state.current.0 = ramp_toward(state.current.0, state.target.0, step);
+    state.current.1 = ramp_toward(state.current.1, state.target.1, step);
+
+    let is_active = state.current.0 != 0.0 || state.current.1 != 0.0;
+    if is_active {
+        send_dom_controls_user_event(moved((state.current.0 as f64, state.current.1 as f64)));
+    } else if state.active {
+        send_dom_controls_user_event(released);
+    }
+    state.active = is_active;
+}
+
+fn axis_target(positive: bool, negative: bool) -> f32 {
+    match (positive, negative) {
+        (true, false) => 1.0,
+        (false, true) => -1.0,
+        _ => 0.0,
+    }
+}
+
+fn ramp_toward(current: f32, target: f32, step: f32) -> f32 {
+    let delta = target - current;
+    if delta.abs() <= step {
+        target
+    } else {
+        current + step * delta.signum()
+    }
+}
+
+fn now_seconds() -> f64 {
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            web_sys::window().unwrap().performance().unwrap().now() / 1000.
+        } else {
+            use std::time::{SystemTime, UNIX_EPOCH};
+            SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64()
+        }
+    }
+}
@@ -18,6 +18,21 @@ pub struct Camera {
     pub zfar: f32,
 
     pub frustum: collision::Frustum<f32>,
+
+    /// `(proj * view).invert()`, recomputed alongside `frustum` in
+    /// `update_frustum`. Unlike `build_view_projection_matrix`'s `view`, this
+    /// isn't shifted to put `eye` at the origin -- `light::update_cascades`
+    /// uses it to unproject NDC frustum-slice corners back to world space,
+    /// and caching it here saves re-deriving and re-inverting the same
+    /// matrix once per cascade.
+    pub inverse_view_proj: Matrix4<f32>,
+
+    /// `proj * view`, i.e. `inverse_view_proj` before inversion -- cached
+    /// alongside it in `update_frustum` since both come from the same `proj`
+    /// and `view`. `world::WorldState::iter_visible_chunks` converts this to
+    /// a `glam::Mat4` to build a `frustum::Frustum` and cull chunk AABBs
+    /// before they're meshed.
+    pub view_proj: Matrix4<f32>,
 }
 
 #[rustfmt::skip]
@@ -91,6 +106,8 @@ impl Camera {
             znear,
             zfar,
             frustum: dummy_frustum,
+            inverse_view_proj: Matrix4::identity(),
+            view_proj: Matrix4::identity(),
         };
         partial_self.update_frustum();
 
@@ -126,6 +143,11 @@ impl Camera {
         let forward_norm = (self.target - self.eye).normalize();
         let forward_zfar = forward_norm * self.zfar;
 
+        let view = look_at_rh(self.eye, self.target, self.up);
+        let proj = cgmath::perspective(cgmath::Deg(self.fovy), self.aspect, self.znear, self.zfar);
+        self.view_proj = proj * view;
+        self.inverse_view_proj = self.view_proj.invert().expect("camera view-proj is invertible");
+
         let right_norm = forward_norm.cross(self.world_up).normalize();
         let up_norm = right_norm.cross(forward_norm).normalize();
 
@@ -158,7 +180,68 @@ impl Camera {
         self.frustum.far = Plane::from_point_normal(self.eye + forward_zfar, -forward_norm);
     }
 
-    pub fn filter_visible_chunks(&self, mut chunk_geoms: &Vec<Aabb3<f32>>) {}
+    /// Returns the indices of `chunk_geoms` that are at least partially
+    /// inside this camera's frustum, so the renderer can skip the rest.
+    ///
+    /// Uses the standard plane/AABB test: for each frustum plane, take the
+    /// box's "positive vertex" (per axis, the `max` coordinate if that
+    /// axis's plane-normal component is positive, else `min`) -- this is the
+    /// box's vertex furthest along the plane's normal. If that vertex is
+    /// still behind the plane, the whole box is behind it and the box is
+    /// outside the frustum.
+    pub fn filter_visible_chunks(&self, chunk_geoms: &[Aabb3<f32>]) -> Vec<usize> {
+        let planes = [
+            &self.frustum.left,
+            &self.frustum.right,
+            &self.frustum.bottom,
+            &self.frustum.top,
+            &self.frustum.near,
+            &self.frustum.far,
+        ];
+
+        chunk_geoms
+            .iter()
+            .enumerate()
+            .filter_map(|(i, aabb)| {
+                let is_visible = planes.iter().all(|plane| {
+                    let positive_vertex = Vector3::new(
+                        if plane.n.x >= 0.0 { aabb.max.x } else { aabb.min.x },
+                        if plane.n.y >= 0.0 { aabb.max.y } else { aabb.min.y },
+                        if plane.n.z >= 0.0 { aabb.max.z } else { aabb.min.z },
+                    );
+                    plane.n.dot(positive_vertex) + plane.d >= 0.0
+                });
+                if is_visible {
+                    Some(i)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// View/projection contract that `CameraUniform` consumes. Lets the renderer
+/// swap in whichever camera implementation is active (flycam, orbit, ...)
+/// without caring which one it is.
+pub trait RenderCamera {
+    fn view_proj(&self) -> cgmath::Matrix4<f32>;
+    fn eye_pos(&self) -> cgmath::Point3<f32>;
+    fn frustum(&self) -> &collision::Frustum<f32>;
+}
+
+impl RenderCamera for Camera {
+    fn view_proj(&self) -> cgmath::Matrix4<f32> {
+        self.build_view_projection_matrix()
+    }
+
+    fn eye_pos(&self) -> cgmath::Point3<f32> {
+        self.eye
+    }
+
+    fn frustum(&self) -> &collision::Frustum<f32> {
+        &self.frustum
+    }
 }
 
 // We need this for Rust to store our data correctly for the shaders
@@ -180,14 +263,15 @@ impl CameraUniform {
         }
     }
 
-    pub fn update_view_proj(&mut self, camera: &Camera) {
-        self.view_proj = camera.build_view_projection_matrix().into();
-        self.eye_pos = [camera.eye.x, camera.eye.y, camera.eye.z, 1.0];
+    pub fn update_view_proj(&mut self, camera: &dyn RenderCamera) {
+        self.view_proj = camera.view_proj().into();
+        let eye = camera.eye_pos();
+        self.eye_pos = [eye.x, eye.y, eye.z, 1.0];
     }
 }
 
 pub struct CameraController {
-    _speed: f32,
+    thrust_speed: f32,
     mouse_sensitivity: f64,
     is_forward_pressed: bool,
     is_backward_pressed: bool,
@@ -198,9 +282,42 @@ pub struct CameraController {
     is_sprint_pressed: bool,
     last_mouse_delta: (f64, f64),
     last_joystick_vector: (f64, f64),
+    last_device_orientation_yaw_pitch: Option<(f64, f64)>,
+    last_update_seconds: f64,
+    velocity: Vector3<f32>,
+    /// Yaw (rotation around world up) and pitch (rotation around local
+    /// right), in radians. Explicit orientation state instead of
+    /// incrementally rotating `forward` in place, so roll can't accumulate
+    /// and the view can't flip over at the poles.
+    euler_yaw: f32,
+    euler_pitch: f32,
     num_updates: u64,
 }
 
+/// Clamp applied to `euler_pitch` so the reconstructed forward vector never
+/// reaches straight up/down, which would make yaw ill-defined (gimbal flip).
+const PITCH_CLAMP_EPSILON: f32 = 0.01;
+
+/// Half-life (seconds) of the exponential damper that blends the camera's
+/// velocity toward its target each frame: how long it takes the gap between
+/// current and target velocity to halve. Gives frame-rate-independent,
+/// critically-smooth acceleration and deceleration.
+const DAMPER_HALF_LIFE: f32 = 0.08;
+
+/// Wall-clock seconds, used to compute `update_camera`'s frame `dt`. Backed
+/// by `SystemTime` natively and `performance.now()` on web, since the `std`
+/// clocks aren't available on `wasm32-unknown-unknown`.
+fn now_seconds() -> f64 {
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            web_sys::window().unwrap().performance().unwrap().now() / 1000.
+        } else {
+            use std::time::{SystemTime, UNIX_EPOCH};
+            SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64()
+        }
+    }
+}
+
 pub struct CameraUpdateResult {
     pub did_move: bool,
     pub did_move_blocks: bool,
@@ -210,10 +327,20 @@ pub struct CameraUpdateResult {
     pub new_chunk_location: [usize; 2],
 }
 
+/// Decomposes a `[x, y, z, w]` orientation quaternion (as emitted by
+/// `dom_controls::device_motion`) into yaw (rotation around world up) and
+/// pitch (rotation around the local right axis), in radians.
+fn quat_to_yaw_pitch(quat: [f64; 4]) -> (f64, f64) {
+    let [x, y, z, w] = quat;
+    let yaw = (2.0 * (w * y + x * z)).atan2(1.0 - 2.0 * (y * y + z * z));
+    let pitch = (2.0 * (w * x - z * y)).clamp(-1.0, 1.0).asin();
+    (yaw, pitch)
+}
+
 impl CameraController {
     pub fn new(speed: f32, mouse_sensitivity: f64) -> Self {
         Self {
-            _speed: speed,
+            thrust_speed: speed,
             mouse_sensitivity,
             is_forward_pressed: false,
             is_backward_pressed: false,
@@ -224,16 +351,21 @@ impl CameraController {
             is_sprint_pressed: false,
             last_mouse_delta: (0.0, 0.0),
             last_joystick_vector: (0.0, 0.0),
+            last_device_orientation_yaw_pitch: None,
+            last_update_seconds: now_seconds(),
+            velocity: Vector3::zero(),
+            euler_yaw: 0.0,
+            euler_pitch: 0.0,
             num_updates: 0,
         }
     }
 
     fn speed(&self) -> f32 {
         if self.is_sprint_pressed {
-            //self._speed / 8.0
-            self._speed * 4.0
+            //self.thrust_speed / 8.0
+            self.thrust_speed * 4.0
         } else {
-            self._speed
+            self.thrust_speed
         }
     }
 
@@ -279,11 +411,11 @@ impl CameraController {
                         true
                     }
                     VirtualKeyCode::Minus => {
-                        self._speed *= 0.5;
+                        self.thrust_speed *= 0.5;
                         true
                     }
                     VirtualKeyCode::Equals => {
-                        self._speed *= 2.0;
+                        self.thrust_speed *= 2.0;
                         true
                     }
                     _ => false,
@@ -349,6 +481,15 @@ impl CameraController {
                 self.last_joystick_vector = (0.0, 0.0);
                 true
             }
+            DomControlsUserEvent::DeviceOrientationChanged { quat } => {
+                let (yaw, pitch) = quat_to_yaw_pitch(*quat);
+                self.last_joystick_vector = match self.last_device_orientation_yaw_pitch {
+                    Some((last_yaw, last_pitch)) => (yaw - last_yaw, pitch - last_pitch),
+                    None => (0.0, 0.0),
+                };
+                self.last_device_orientation_yaw_pitch = Some((yaw, pitch));
+                true
+            }
             DomControlsUserEvent::TranslationJoystickDirectionChanged { direction } => {
                 self.clear_translational_inputs();
                 match direction {
@@ -386,6 +527,18 @@ impl CameraController {
         self.is_left_pressed = false;
     }
 
+    /// Forces every tracked button/joystick state back to released, for
+    /// when the window loses focus mid-press and the matching release
+    /// event will never arrive -- otherwise the camera keeps thrusting (or
+    /// sprinting) in whatever direction was held when focus was lost.
+    pub fn clear_stuck_input(&mut self) {
+        self.clear_translational_inputs();
+        self.is_space_pressed = false;
+        self.is_shift_pressed = false;
+        self.is_sprint_pressed = false;
+        self.last_joystick_vector = (0.0, 0.0);
+    }
+
     pub fn reset_mouse_delta(&mut self) {
         self.last_mouse_delta = (0.0, 0.0);
     }
@@ -407,83 +560,88 @@ impl CameraController {
         let mut did_move = false;
         let mut did_translate = false;
 
+        let now = now_seconds();
+        let dt = (now - self.last_update_seconds).max(0.0) as f32;
+        self.last_update_seconds = now;
+
         // Vector pointing out of the camera's eye towards the target
         let forward = camera.target - camera.eye;
         let forward_norm = forward.normalize();
         let forward_mag = forward.magnitude();
 
+        if self.num_updates == 0 {
+            self.euler_yaw = forward_norm.z.atan2(forward_norm.x);
+            self.euler_pitch = forward_norm.y.clamp(-1.0, 1.0).asin();
+        }
+
         let mut next_eye = camera.eye;
         let mut next_target = camera.target;
 
-        // Prevents glitching when camera gets too close to the
-        // center of the scene.
-        //if self.is_forward_pressed && forward_mag > self.speed {
+        // Strafing vector, derived from forward and world_up rather than
+        // camera.up so strafing stays level regardless of look direction.
+        let right_norm = forward_norm.cross(camera.world_up).normalize();
+
+        // Build the target velocity from currently-held direction keys,
+        // scaled to top speed, then blend the current velocity toward it
+        // with an exponential damper so the camera accelerates/decelerates
+        // smoothly instead of snapping to full speed on key-down.
+        let mut thrust_dir = Vector3::zero();
         if self.is_forward_pressed {
-            did_move = true;
-            did_translate = true;
-            next_eye += forward_norm * self.speed();
-            next_target += forward_norm * self.speed();
+            thrust_dir += forward_norm;
         }
         if self.is_backward_pressed {
-            did_move = true;
-            did_translate = true;
-            next_eye -= forward_norm * self.speed();
-            next_target -= forward_norm * self.speed();
+            thrust_dir -= forward_norm;
         }
-
-        // Strafing vector
-        let right_norm = forward_norm.cross(camera.up);
-
         if self.is_right_pressed {
-            did_move = true;
-            did_translate = true;
-            next_eye += right_norm * self.speed();
-            next_target += right_norm * self.speed();
+            thrust_dir += right_norm;
         }
         if self.is_left_pressed {
-            did_move = true;
-            did_translate = true;
-            next_eye -= right_norm * self.speed();
-            next_target -= right_norm * self.speed();
+            thrust_dir -= right_norm;
         }
-
         if self.is_space_pressed {
-            did_move = true;
-            did_translate = true;
-            next_eye += camera.world_up * self.speed();
-            next_target += camera.world_up * self.speed();
+            thrust_dir += camera.world_up;
         }
         if self.is_shift_pressed {
+            thrust_dir -= camera.world_up;
+        }
+        let target_velocity = if thrust_dir.magnitude2() > 0.0 {
+            thrust_dir.normalize() * self.speed()
+        } else {
+            Vector3::zero()
+        };
+
+        let damper_factor = (-dt * std::f32::consts::LN_2 / DAMPER_HALF_LIFE).exp();
+        self.velocity = target_velocity + (self.velocity - target_velocity) * damper_factor;
+
+        if self.velocity.magnitude2() > 0.0 {
             did_move = true;
             did_translate = true;
-            next_eye -= camera.world_up * self.speed();
-            next_target -= camera.world_up * self.speed();
+            let displacement = self.velocity * dt;
+            next_eye += displacement;
+            next_target += displacement;
         }
 
-        // "Vertical" strafing vector
-        let up_norm = right_norm.cross(forward).normalize();
-
         let (x_delta, y_delta) =
             if self.last_joystick_vector.0 != 0.0 || self.last_joystick_vector.1 != 0.0 {
                 self.last_joystick_vector
             } else {
                 self.last_mouse_delta
             };
-        if y_delta != 0.0 {
-            let theta = cgmath::Rad((-y_delta * self.mouse_sensitivity) as f32);
-            let rot: cgmath::Basis3<f32> = cgmath::Rotation3::from_axis_angle(right_norm, theta);
-            let new_forward = rot.rotate_vector(forward_norm) * forward_mag;
-            let forward_diff = new_forward - forward;
-            did_translate = true;
-            next_target += forward_diff;
-        }
-        if x_delta != 0.0 {
-            let theta = cgmath::Rad((-x_delta * self.mouse_sensitivity) as f32);
-            let rot: cgmath::Basis3<f32> = cgmath::Rotation3::from_axis_angle(up_norm, theta);
-            let new_forward = rot.rotate_vector(forward_norm) * forward_mag;
-            let forward_diff = new_forward - forward;
+        if x_delta != 0.0 || y_delta != 0.0 {
+            self.euler_yaw += (-x_delta * self.mouse_sensitivity) as f32;
+            self.euler_pitch += (-y_delta * self.mouse_sensitivity) as f32;
+            self.euler_pitch = self.euler_pitch.clamp(
+                -(std::f32::consts::FRAC_PI_2 - PITCH_CLAMP_EPSILON),
+                std::f32::consts::FRAC_PI_2 - PITCH_CLAMP_EPSILON,
+            );
+
+            let new_forward = Vector3::new(
+                self.euler_pitch.cos() * self.euler_yaw.cos(),
+                self.euler_pitch.sin(),
+                self.euler_pitch.cos() * self.euler_yaw.sin(),
+            );
             did_translate = true;
-            next_target += forward_diff;
+            next_target = next_eye + new_forward * forward_mag;
         }
 
         if did_move {
@@ -550,3 +708,209 @@ impl CameraController {
         // );
     }
 }
+
+/// Which camera currently feeds `CameraUniform`. The flycam (`Camera` /
+/// `CameraController`) always keeps running regardless of mode, since world
+/// logic (movement, collision, block breaking) is always relative to it --
+/// only the render output switches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraMode {
+    FlyCam,
+    Orbit,
+}
+
+/// Third-person/orbit camera: looks at a `focus` point from `distance` away,
+/// along a direction set by `yaw`/`pitch`. Implements `RenderCamera` so it
+/// can feed the same `CameraUniform` as the flycam.
+pub struct OrbitCamera {
+    pub focus: cgmath::Point3<f32>,
+    pub eye: cgmath::Point3<f32>,
+    pub up: cgmath::Vector3<f32>,
+
+    pub aspect: f32,
+    pub fovy: f32,
+    pub znear: f32,
+    pub zfar: f32,
+
+    pub frustum: collision::Frustum<f32>,
+}
+
+impl OrbitCamera {
+    pub fn new(focus: cgmath::Point3<f32>, aspect: f32, fovy: f32, znear: f32, zfar: f32) -> Self {
+        let dummy_plane = Plane::<f32>::new(cgmath::Vector3::new(0.0, 0.0, 0.0), 0.0);
+        let dummy_frustum = Frustum::new(
+            dummy_plane,
+            dummy_plane,
+            dummy_plane,
+            dummy_plane,
+            dummy_plane,
+            dummy_plane,
+        );
+        let mut partial_self = Self {
+            focus,
+            eye: focus,
+            up: cgmath::Vector3::unit_y(),
+            aspect,
+            fovy,
+            znear,
+            zfar,
+            frustum: dummy_frustum,
+        };
+        partial_self.update_frustum();
+
+        partial_self
+    }
+
+    pub fn build_view_projection_matrix(&self) -> cgmath::Matrix4<f32> {
+        let eye_vec = self.eye.to_vec();
+        let focus_shifted_by_origin = self.focus - eye_vec;
+
+        let view = look_at_rh(cgmath::Point3::origin(), focus_shifted_by_origin, self.up);
+        let proj = cgmath::perspective(cgmath::Deg(self.fovy), self.aspect, self.znear, self.zfar);
+
+        OPENGL_TO_WGPU_MATRIX * proj * view
+    }
+
+    pub fn update_frustum(&mut self) {
+        let half_v_side = self.zfar * (self.fovy * 0.5).tan();
+        let half_h_side = half_v_side * self.aspect;
+
+        let forward_norm = (self.focus - self.eye).normalize();
+        let forward_zfar = forward_norm * self.zfar;
+
+        let right_norm = forward_norm.cross(self.up).normalize();
+        let up_norm = right_norm.cross(forward_norm).normalize();
+
+        self.frustum.left = Plane::from_point_normal(
+            self.eye,
+            (forward_zfar - right_norm * half_h_side)
+                .cross(up_norm)
+                .normalize(),
+        );
+        self.frustum.right = Plane::from_point_normal(
+            self.eye,
+            up_norm
+                .cross(forward_zfar + right_norm * half_h_side)
+                .normalize(),
+        );
+        self.frustum.bottom = Plane::from_point_normal(
+            self.eye,
+            (forward_zfar + up_norm * half_v_side)
+                .cross(right_norm)
+                .normalize(),
+        );
+        self.frustum.top = Plane::from_point_normal(
+            self.eye,
+            right_norm
+                .cross(forward_zfar - up_norm * half_v_side)
+                .normalize(),
+        );
+        self.frustum.near =
+            Plane::from_point_normal(self.eye + self.znear * forward_norm, forward_norm);
+        self.frustum.far = Plane::from_point_normal(self.eye + forward_zfar, -forward_norm);
+    }
+}
+
+impl RenderCamera for OrbitCamera {
+    fn view_proj(&self) -> cgmath::Matrix4<f32> {
+        self.build_view_projection_matrix()
+    }
+
+    fn eye_pos(&self) -> cgmath::Point3<f32> {
+        self.eye
+    }
+
+    fn frustum(&self) -> &collision::Frustum<f32> {
+        &self.frustum
+    }
+}
+
+/// Minimum orbit distance, so scrolling in can't collapse the camera onto its
+/// focus point and make `forward_norm` ill-defined.
+const MIN_ORBIT_DISTANCE: f32 = 1.0;
+
+/// Holds the `yaw`/`pitch`/`distance` that place an `OrbitCamera`: mouse drag (while the
+/// left button is held) rotates around the focus point, and the scroll wheel
+/// moves the camera closer to or further from it.
+pub struct OrbitCameraController {
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+    drag_sensitivity: f64,
+    scroll_sensitivity: f32,
+    is_dragging: bool,
+    last_mouse_delta: (f64, f64),
+}
+
+impl OrbitCameraController {
+    pub fn new(drag_sensitivity: f64, scroll_sensitivity: f32, initial_distance: f32) -> Self {
+        Self {
+            yaw: 0.0,
+            pitch: 0.3,
+            distance: initial_distance,
+            drag_sensitivity,
+            scroll_sensitivity,
+            is_dragging: false,
+            last_mouse_delta: (0.0, 0.0),
+        }
+    }
+
+    pub fn process_window_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::MouseInput {
+                state,
+                button: winit::event::MouseButton::Left,
+                ..
+            } => {
+                self.is_dragging = *state == ElementState::Pressed;
+                true
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let scroll_y = match delta {
+                    winit::event::MouseScrollDelta::LineDelta(_, y) => *y,
+                    winit::event::MouseScrollDelta::PixelDelta(pos) => pos.y as f32,
+                };
+                self.distance =
+                    (self.distance - scroll_y * self.scroll_sensitivity).max(MIN_ORBIT_DISTANCE);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn process_device_event(&mut self, event: &DeviceEvent) -> bool {
+        match event {
+            DeviceEvent::MouseMotion { delta } => {
+                self.last_mouse_delta = *delta;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn reset_mouse_delta(&mut self) {
+        self.last_mouse_delta = (0.0, 0.0);
+    }
+
+    /// Re-derives `camera`'s `eye`/`frustum` from this controller's
+    /// `yaw`/`pitch`/`distance`, following `camera.focus` as it moves (e.g.
+    /// to track the player in third-person).
+    pub fn update_orbit_camera(&mut self, camera: &mut OrbitCamera) {
+        if self.is_dragging {
+            self.yaw += (self.last_mouse_delta.0 * self.drag_sensitivity) as f32;
+            self.pitch += (-self.last_mouse_delta.1 * self.drag_sensitivity) as f32;
+            self.pitch = self.pitch.clamp(
+                -(std::f32::consts::FRAC_PI_2 - PITCH_CLAMP_EPSILON),
+                std::f32::consts::FRAC_PI_2 - PITCH_CLAMP_EPSILON,
+            );
+        }
+
+        let direction_from_focus = Vector3::new(
+            self.pitch.cos() * self.yaw.cos(),
+            self.pitch.sin(),
+            self.pitch.cos() * self.yaw.sin(),
+        );
+        camera.eye = camera.focus + direction_from_focus * self.distance;
+        camera.update_frustum();
+    }
+}
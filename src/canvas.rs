@@ -0,0 +1,60 @@
+//! Reusable windowing + wgpu harness, split out of `lib.rs`'s `run()` so the
+//! event loop, input translation, and the `pop_error_scope` panic future
+//! aren't welded to `Game` specifically. `Canvas` (the `ApplicationHandler`
+//! in `lib.rs`) owns the `Window`/wgpu `State` and drives whatever
+//! implements [`Loop`]; the `EventLoop` itself is not held by `Canvas` but
+//! by `run()` (or a host calling `pump_events`), which hands `Canvas` to it.
+//! `Game` is just the implementor this crate
+//! ships. Modeled on dunge's `Canvas`/`Loop` split.
+//!
+//! `Loop::update`/`Loop::render` differ from dunge's in two ways this crate
+//! needs: `fixed_dt`/`alpha` thread through the fixed-timestep accumulator
+//! already described in `game_loop::GameLoop`'s docs, and `action_handler`
+//! carries the resolved, rebindable action state from `input` (see that
+//! module's docs) rather than folding action mapping into `Input` itself.
+
+use std::collections::HashSet;
+
+use winit::dpi::PhysicalSize;
+use winit::event::{MouseButton, VirtualKeyCode};
+
+use crate::input;
+
+/// Raw input state `Canvas` assembles from the `winit` events it observes
+/// between two `Loop::update` calls -- keys currently held, mouse motion
+/// accumulated since the last snapshot, buttons that went down this step,
+/// and any pending resize. Deliberately doesn't know about `ActionHandler`
+/// or gameplay concepts, so a `Loop` implementor that isn't `Game` isn't
+/// forced to depend on them.
+#[derive(Debug, Clone, Default)]
+pub struct Input {
+    pub keys_held: HashSet<VirtualKeyCode>,
+    pub mouse_delta: (f64, f64),
+    pub clicks: Vec<MouseButton>,
+    pub resized_to: Option<PhysicalSize<u32>>,
+}
+
+/// Whether `Canvas` should keep pumping events after a `Loop::render` call.
+/// Lets a `Loop` implementor end the program (e.g. on an in-game quit
+/// action) without reaching back into winit's `ActiveEventLoop` itself.
+pub enum RenderResult {
+    Continue,
+    Exit,
+}
+
+/// Implemented by whatever simulation/rendering code a `Canvas` should
+/// drive. `Game` is this crate's own implementor; a host embedding
+/// `minecrust` as a library -- or a test that wants to call `update`/`render`
+/// directly without spinning up a real window -- could supply a different
+/// one to reuse the same harness.
+pub trait Loop {
+    /// Called once per fixed update tick at `Canvas`'s configured rate,
+    /// decoupling simulation speed from the display's refresh rate.
+    fn update(&mut self, input: &Input, action_handler: &input::ActionHandler, fixed_dt: f64);
+
+    /// Called once per redraw with an already-acquired swapchain frame.
+    /// `alpha` blends between the last two `update` calls for smooth
+    /// interpolated motion (see `game_loop::GameLoop::blending_factor`).
+    /// Implementors own presenting `frame` themselves.
+    fn render(&mut self, frame: wgpu::SurfaceTexture, alpha: f64) -> RenderResult;
+}
@@ -3,6 +3,9 @@ use winit::event_loop::{EventLoop, EventLoopProxy};
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
+use crate::orientation_filter::OrientationFilter;
+use crate::touch_gesture::{GestureMode, TouchGestureRecognizer};
+
 #[derive(Debug)]
 pub enum DomControlsUserEvent {
     AButtonPressed,
@@ -13,6 +16,17 @@ pub enum DomControlsUserEvent {
     PitchYawJoystickReleased,
     TranslationJoystickMoved { vector: (f64, f64) },
     TranslationJoystickReleased,
+    /// Filtered device orientation as a quaternion `[x, y, z, w]`, emitted by
+    /// `device_motion` for mobile web's gyro-based camera look.
+    DeviceOrientationChanged { quat: [f64; 4] },
+    /// A two-finger pan/zoom/twist delta recognized by `touch_gesture_updated`
+    /// from `TouchGestureRecognizer`. Which components are nonzero depends on
+    /// the active `GestureMode`.
+    Pan {
+        translation: (f64, f64),
+        scale: f64,
+        rotation: f64,
+    },
     WindowResized { size: winit::dpi::LogicalSize<u32> },
 }
 
@@ -27,7 +41,17 @@ pub unsafe fn set_global_event_loop_proxy(event_loop: &EventLoop<DomControlsUser
     EVENT_LOOP_GLOBAL_STATE.event_loop_proxy = Some(event_loop.create_proxy());
 }
 
-fn send_dom_controls_user_event(event: DomControlsUserEvent) {
+struct OrientationFilterState {
+    filter: Option<OrientationFilter>,
+}
+static mut ORIENTATION_FILTER_STATE: OrientationFilterState = OrientationFilterState { filter: None };
+
+struct TouchGestureState {
+    recognizer: Option<TouchGestureRecognizer>,
+}
+static mut TOUCH_GESTURE_STATE: TouchGestureState = TouchGestureState { recognizer: None };
+
+pub(crate) fn send_dom_controls_user_event(event: DomControlsUserEvent) {
     let event_loop_proxy = unsafe {
         match EVENT_LOOP_GLOBAL_STATE.event_loop_proxy {
             None => return,
@@ -72,6 +96,79 @@ pub fn translation_joystick_moved(x: f64, y: f64) {
 pub fn translation_joystick_released() {
     send_dom_controls_user_event(DomControlsUserEvent::TranslationJoystickReleased);
 }
+/// Feeds this frame's active touch points (0, 1, or 2 -- additional touches
+/// beyond the first two are ignored) into the two-finger gesture recognizer,
+/// and emits a `Pan` event if two fingers are down. `(x1, y1)` is unused
+/// when `touch_count < 2`.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn touch_gesture_updated(touch_count: u32, x0: f64, y0: f64, x1: f64, y1: f64) {
+    let touches: &[(f64, f64)] = match touch_count {
+        0 => &[],
+        1 => &[(x0, y0)],
+        _ => &[(x0, y0), (x1, y1)],
+    };
+    let pan = unsafe {
+        if TOUCH_GESTURE_STATE.recognizer.is_none() {
+            TOUCH_GESTURE_STATE.recognizer =
+                Some(TouchGestureRecognizer::new(GestureMode::PanFull));
+        }
+        TOUCH_GESTURE_STATE
+            .recognizer
+            .as_mut()
+            .unwrap()
+            .update(touches)
+    };
+    if let Some(pan) = pan {
+        send_dom_controls_user_event(DomControlsUserEvent::Pan {
+            translation: pan.translation,
+            scale: pan.scale,
+            rotation: pan.rotation,
+        });
+    }
+}
+/// Selects which components of future `touch_gesture_updated` calls are
+/// reported -- `0` = `PanOnly`, `1` = `PanScale`, `2` = `PanRotate`, `3` =
+/// `PanFull`.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn touch_gesture_mode_changed(mode: u8) {
+    let mode = match mode {
+        0 => GestureMode::PanOnly,
+        1 => GestureMode::PanScale,
+        2 => GestureMode::PanRotate,
+        _ => GestureMode::PanFull,
+    };
+    unsafe {
+        match TOUCH_GESTURE_STATE.recognizer.as_mut() {
+            Some(recognizer) => recognizer.set_mode(mode),
+            None => TOUCH_GESTURE_STATE.recognizer = Some(TouchGestureRecognizer::new(mode)),
+        }
+    }
+}
+/// Fuses a DeviceMotion sample (accelerometer in g, gyroscope in rad/s, and
+/// the elapsed time in seconds since the last sample) into a filtered
+/// orientation quaternion via `OrientationFilter`, and emits it as a
+/// `DeviceOrientationChanged` event so the camera subsystem can map it to
+/// pitch/yaw.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn device_motion(ax: f64, ay: f64, az: f64, gx: f64, gy: f64, gz: f64, dt: f64) {
+    let quat = unsafe {
+        if ORIENTATION_FILTER_STATE.filter.is_none() {
+            ORIENTATION_FILTER_STATE.filter = Some(OrientationFilter::new());
+        }
+        let orientation = ORIENTATION_FILTER_STATE
+            .filter
+            .as_mut()
+            .unwrap()
+            .update(
+                cgmath::Vector3::new(ax, ay, az),
+                cgmath::Vector3::new(gx, gy, gz),
+                dt,
+            );
+        [orientation.v.x, orientation.v.y, orientation.v.z, orientation.s]
+    };
+    send_dom_controls_user_event(DomControlsUserEvent::DeviceOrientationChanged { quat });
+}
+
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
 pub fn web_window_resized(width: u32, height: u32) {
     send_dom_controls_user_event(DomControlsUserEvent::WindowResized {
@@ -0,0 +1,48 @@
+//! A minimal double-buffered event queue, the `Events<T>` resource described
+//! in `WorldState`'s input handling: producers (`process_window_event`,
+//! `process_web_dom_button_event`) push translated intents via `push`, and a
+//! single consumer drains them in arrival order once per tick via `drain`.
+//!
+//! The double buffer means `push` during a `drain` (e.g. an event handler
+//! that synthesizes a follow-up event) doesn't get dropped or observed out
+//! of order -- it lands in the next buffer and is seen on the following
+//! `swap_buffers` instead of mutating the batch currently being drained.
+
+use std::collections::VecDeque;
+
+pub struct Events<T> {
+    current: VecDeque<T>,
+    next: VecDeque<T>,
+}
+
+impl<T> Events<T> {
+    pub fn new() -> Self {
+        Self {
+            current: VecDeque::new(),
+            next: VecDeque::new(),
+        }
+    }
+
+    /// Queues an event, to become visible to `drain` after the next
+    /// `swap_buffers`.
+    pub fn push(&mut self, event: T) {
+        self.next.push_back(event);
+    }
+
+    /// Brings events queued since the last swap into `drain`'s view, and
+    /// starts a fresh buffer for anything pushed from here on.
+    pub fn swap_buffers(&mut self) {
+        std::mem::swap(&mut self.current, &mut self.next);
+    }
+
+    /// Drains this tick's events in the order they were pushed.
+    pub fn drain(&mut self) -> std::collections::vec_deque::Drain<'_, T> {
+        self.current.drain(..)
+    }
+}
+
+impl<T> Default for Events<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,92 @@
+use glam::{Mat4, Vec3, Vec4};
+
+/// A clipping plane in `ax + by + cz + d = 0` form, with the normal `(a, b,
+/// c)` kept unit length so `Frustum::intersects_aabb`'s distance check
+/// doesn't need a separate normalization step per test.
+#[derive(Debug, Clone, Copy)]
+pub struct Plane {
+    pub normal: Vec3,
+    pub d: f32,
+}
+
+impl Plane {
+    fn from_row(row: Vec4) -> Self {
+        let normal = Vec3::new(row.x, row.y, row.z);
+        let len = normal.length();
+        Plane {
+            normal: normal / len,
+            d: row.w / len,
+        }
+    }
+}
+
+/// The six half-spaces of a `Mat4` view volume, extracted directly from the
+/// matrix's rows rather than rebuilt from camera/light vectors the way
+/// `Camera::update_frustum` does. Useful for volumes (like the shadow
+/// ortho/cascade matrices) that only exist as a `Mat4` and don't carry
+/// separate eye/target state to rebuild a frustum from.
+pub struct Frustum {
+    pub left: Plane,
+    pub right: Plane,
+    pub bottom: Plane,
+    pub top: Plane,
+    pub near: Plane,
+    pub far: Plane,
+}
+
+impl Frustum {
+    /// Extracts the six clipping planes of `view_proj`, per Gribb &
+    /// Hartmann's "Fast Extraction of Viewing Frustum Planes from the
+    /// World-View-Projection Matrix": a clip-space point `p = view_proj *
+    /// v` is inside the view volume exactly when `-p.w <= p.x <= p.w` (and
+    /// likewise for `y`), and, for wgpu's `[0, 1]` depth range, `0 <= p.z <=
+    /// p.w`. Each of those six inequalities is itself a plane equation in
+    /// `v`, given by the sum or difference of `view_proj`'s rows.
+    pub fn from_matrix(view_proj: Mat4) -> Self {
+        let row = |i: usize| {
+            Vec4::new(
+                view_proj.x_axis[i],
+                view_proj.y_axis[i],
+                view_proj.z_axis[i],
+                view_proj.w_axis[i],
+            )
+        };
+        let row0 = row(0);
+        let row1 = row(1);
+        let row2 = row(2);
+        let row3 = row(3);
+
+        Frustum {
+            left: Plane::from_row(row3 + row0),
+            right: Plane::from_row(row3 - row0),
+            bottom: Plane::from_row(row3 + row1),
+            top: Plane::from_row(row3 - row1),
+            near: Plane::from_row(row2),
+            far: Plane::from_row(row3 - row2),
+        }
+    }
+
+    /// Standard plane/AABB "positive vertex" test: for each plane, take the
+    /// box's vertex furthest along the plane's normal (per axis, `max` if
+    /// that axis's normal component is non-negative, else `min`). If that
+    /// vertex is still behind the plane, the whole box is behind it and
+    /// outside the frustum. Mirrors `Camera::filter_visible_chunks`'s test.
+    pub fn intersects_aabb(&self, min: Vec3, max: Vec3) -> bool {
+        let planes = [
+            &self.left,
+            &self.right,
+            &self.bottom,
+            &self.top,
+            &self.near,
+            &self.far,
+        ];
+        planes.iter().all(|plane| {
+            let positive_vertex = Vec3::new(
+                if plane.normal.x >= 0.0 { max.x } else { min.x },
+                if plane.normal.y >= 0.0 { max.y } else { min.y },
+                if plane.normal.z >= 0.0 { max.z } else { min.z },
+            );
+            plane.normal.dot(positive_vertex) + plane.d >= 0.0
+        })
+    }
+}
@@ -4,6 +4,12 @@ pub trait TimeTrait: Copy {
     fn sub(&self, other: &Self) -> f64;
 }
 
+/// Implemented by simulation state that can be blended between two fixed
+/// updates for rendering. `alpha` is always in `[0, 1)`.
+pub trait Interpolate {
+    fn lerp(&self, next: &Self, alpha: f64) -> Self;
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 mod time {
     use super::*;
@@ -42,10 +48,23 @@ mod time {
     }
 }
 
-pub struct GameLoop<T: TimeTrait = time::Time> {
+// Number of trailing frame-time samples averaged by `fps()`/`average_delta()`.
+const FRAME_TIME_HISTORY_LEN: usize = 100;
+
+/// Owns the simulation state `G` alongside the fixed-timestep bookkeeping, so
+/// `update`/`render` closures reach game state through `&mut self.game`
+/// instead of their own captured variables. Mirrors the upstream
+/// tuzz/game-loop design.
+pub struct GameLoop<G, T: TimeTrait = time::Time> {
+    pub game: G,
     pub updates_per_second: u32,
     pub max_frame_time: f64,
 
+    /// Set by the game to request a clean shutdown. Checked at the start of
+    /// `next_frame`, which returns `false` (stop running) once it's set,
+    /// after finishing the frame already in progress.
+    pub exit_next_iteration: bool,
+
     fixed_time_step: f64,
     number_of_updates: u32,
     number_of_renders: u32,
@@ -55,13 +74,19 @@ pub struct GameLoop<T: TimeTrait = time::Time> {
     blending_factor: f64,
     previous_instant: T,
     current_instant: T,
+
+    frame_time_history: [f64; FRAME_TIME_HISTORY_LEN],
+    frame_time_cursor: usize,
+    frame_time_count: usize,
 }
 
-impl<T: TimeTrait> GameLoop<T> {
-    pub fn new(updates_per_second: u32, max_frame_time: f64) -> Self {
+impl<G, T: TimeTrait> GameLoop<G, T> {
+    pub fn new(game: G, updates_per_second: u32, max_frame_time: f64) -> Self {
         Self {
+            game,
             updates_per_second,
             max_frame_time,
+            exit_next_iteration: false,
 
             fixed_time_step: 1.0 / updates_per_second as f64,
             number_of_updates: 0,
@@ -72,14 +97,34 @@ impl<T: TimeTrait> GameLoop<T> {
             previous_instant: T::now(),
             current_instant: T::now(),
             last_frame_time: 0.0,
+
+            frame_time_history: [0.0; FRAME_TIME_HISTORY_LEN],
+            frame_time_cursor: 0,
+            frame_time_count: 0,
         }
     }
 
-    pub fn next_frame<U, R>(&mut self, mut update: U, mut render: R)
+    fn push_frame_time(&mut self, elapsed: f64) {
+        self.frame_time_history[self.frame_time_cursor] = elapsed;
+        self.frame_time_cursor = (self.frame_time_cursor + 1) % FRAME_TIME_HISTORY_LEN;
+        if self.frame_time_count < FRAME_TIME_HISTORY_LEN {
+            self.frame_time_count += 1;
+        }
+    }
+
+    /// Advances the loop by one frame: runs zero or more fixed `update`s to
+    /// catch up, then exactly one `render`. Returns `false` once
+    /// `exit_next_iteration` has been set, so callers (e.g. the winit event
+    /// loop) can break out deterministically after this frame completes.
+    pub fn next_frame<U, R>(&mut self, mut update: U, mut render: R) -> bool
     where
-        U: FnMut(&mut GameLoop<T>),
-        R: FnMut(&mut GameLoop<T>),
+        U: FnMut(&mut GameLoop<G, T>),
+        R: FnMut(&mut GameLoop<G, T>),
     {
+        if self.exit_next_iteration {
+            return false;
+        }
+
         let mut g = self;
 
         g.current_instant = T::now();
@@ -91,6 +136,7 @@ impl<T: TimeTrait> GameLoop<T> {
         }
 
         g.last_frame_time = elapsed;
+        g.push_frame_time(elapsed);
         g.running_time += elapsed;
         g.accumulated_time += elapsed;
 
@@ -107,6 +153,8 @@ impl<T: TimeTrait> GameLoop<T> {
 
         g.number_of_renders += 1;
         g.previous_instant = g.current_instant;
+
+        !g.exit_next_iteration
     }
 
     pub fn re_accumulate(&mut self) {
@@ -161,6 +209,31 @@ impl<T: TimeTrait> GameLoop<T> {
         self.blending_factor
     }
 
+    /// Average frame delta over the last `FRAME_TIME_HISTORY_LEN` samples
+    /// (or fewer, during warm-up), in seconds.
+    pub fn average_delta(&self) -> f64 {
+        if self.frame_time_count == 0 {
+            return self.last_frame_time;
+        }
+        let sum: f64 = self.frame_time_history[..self.frame_time_count].iter().sum();
+        sum / self.frame_time_count as f64
+    }
+
+    /// Smoothed frames-per-second estimate, stable across single-frame jitter.
+    pub fn fps(&self) -> f64 {
+        let average_delta = self.average_delta();
+        if average_delta <= 0.0 {
+            return 0.0;
+        }
+        1.0 / average_delta
+    }
+
+    /// Time until `accumulated_time` would next cross `fixed_time_step`, i.e.
+    /// how long a caller can sleep/yield before the next fixed update is due.
+    pub fn remaining_update_time(&self) -> f64 {
+        (self.fixed_time_step - self.accumulated_time).max(0.0)
+    }
+
     pub fn previous_instant(&self) -> T {
         self.previous_instant
     }
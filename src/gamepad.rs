@@ -0,0 +1,139 @@
+//! Native gamepad backend (desktop builds only) that feeds the same
+//! `DomControlsUserEvent` stream the web build drives from JS via
+//! `dom_controls`'s `wasm_bindgen` shims, so the rest of the engine stays
+//! backend-agnostic between the two input sources.
+
+use crate::dom_controls::{send_dom_controls_user_event, DomControlsUserEvent};
+use gilrs::{Axis, Button, EventType, Gilrs};
+
+/// Radial deadzone below which a stick axis is treated as centered, so
+/// analog drift on an idle controller doesn't produce spurious
+/// joystick-moved events.
+const STICK_DEADZONE: f32 = 0.15;
+
+#[derive(Clone, Copy)]
+enum StickAxis {
+    Translation,
+    PitchYaw,
+}
+
+struct StickState {
+    x: f32,
+    y: f32,
+    active: bool,
+}
+
+impl StickState {
+    fn new() -> Self {
+        Self { x: 0.0, y: 0.0, active: false }
+    }
+}
+
+/// Polls connected controllers and translates left-stick/right-stick/face
+/// button input into `DomControlsUserEvent`s, mirroring the web build's
+/// twin-stick control scheme.
+pub struct GamepadBackend {
+    gilrs: Gilrs,
+    translation_stick: StickState,
+    pitch_yaw_stick: StickState,
+}
+
+impl GamepadBackend {
+    /// Returns `None` if no gamepad backend could be initialized (e.g. no
+    /// supported input backend on this platform); the caller should just
+    /// skip polling in that case rather than treating it as fatal.
+    pub fn new() -> Option<Self> {
+        match Gilrs::new() {
+            Ok(gilrs) => Some(Self {
+                gilrs,
+                translation_stick: StickState::new(),
+                pitch_yaw_stick: StickState::new(),
+            }),
+            Err(err) => {
+                log::warn!("Gamepad backend unavailable: {}", err);
+                None
+            }
+        }
+    }
+
+    /// Drains pending gilrs events for this frame and re-dispatches them
+    /// through the same event loop proxy the web DOM controls use.
+    pub fn poll(&mut self) {
+        while let Some(gilrs::Event { event, .. }) = self.gilrs.next_event() {
+            match event {
+                EventType::ButtonPressed(Button::South, _) => {
+                    send_dom_controls_user_event(DomControlsUserEvent::AButtonPressed);
+                }
+                EventType::ButtonReleased(Button::South, _) => {
+                    send_dom_controls_user_event(DomControlsUserEvent::AButtonReleased);
+                }
+                EventType::ButtonPressed(Button::East, _) => {
+                    send_dom_controls_user_event(DomControlsUserEvent::BButtonPressed);
+                }
+                EventType::ButtonReleased(Button::East, _) => {
+                    send_dom_controls_user_event(DomControlsUserEvent::BButtonReleased);
+                }
+                EventType::AxisChanged(Axis::LeftStickX, value, _) => {
+                    self.update_stick(StickAxis::Translation, Some(value), None);
+                }
+                EventType::AxisChanged(Axis::LeftStickY, value, _) => {
+                    self.update_stick(StickAxis::Translation, None, Some(value));
+                }
+                EventType::AxisChanged(Axis::RightStickX, value, _) => {
+                    self.update_stick(StickAxis::PitchYaw, Some(value), None);
+                }
+                EventType::AxisChanged(Axis::RightStickY, value, _) => {
+                    self.update_stick(StickAxis::PitchYaw, None, Some(value));
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn update_stick(&mut self, which: StickAxis, x: Option<f32>, y: Option<f32>) {
+        let state = match which {
+            StickAxis::Translation => &mut self.translation_stick,
+            StickAxis::PitchYaw => &mut self.pitch_yaw_stick,
+        };
+        if let Some(x) = x {
+            state.x = x;
+        }
+        if let Some(y) = y {
+            state.y = y;
+        }
+
+        let (dx, dy) = apply_deadzone(state.x, state.y);
+        let is_active = dx != 0.0 || dy != 0.0;
+        let vector = (dx as f64, dy as f64);
+
+        match (which, state.active, is_active) {
+            (StickAxis::Translation, _, true) => send_dom_controls_user_event(
+                DomControlsUserEvent::TranslationJoystickMoved { vector },
+            ),
+            (StickAxis::Translation, true, false) => {
+                send_dom_controls_user_event(DomControlsUserEvent::TranslationJoystickReleased);
+            }
+            (StickAxis::PitchYaw, _, true) => send_dom_controls_user_event(
+                DomControlsUserEvent::PitchYawJoystickMoved { vector },
+            ),
+            (StickAxis::PitchYaw, true, false) => {
+                send_dom_controls_user_event(DomControlsUserEvent::PitchYawJoystickReleased);
+            }
+            _ => (),
+        }
+        state.active = is_active;
+    }
+}
+
+/// Rescales the stick vector `(x, y)` so the deadzone is subtracted from its
+/// magnitude rather than just clamped, giving full unit deflection range just
+/// outside it while keeping the stick's direction.
+fn apply_deadzone(x: f32, y: f32) -> (f32, f32) {
+    let magnitude = (x * x + y * y).sqrt();
+    if magnitude < STICK_DEADZONE {
+        (0.0, 0.0)
+    } else {
+        let scale = ((magnitude - STICK_DEADZONE) / (1.0 - STICK_DEADZONE)).min(1.0) / magnitude;
+        (x * scale, y * scale)
+    }
+}
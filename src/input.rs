@@ -0,0 +1,390 @@
+//! Named, rebindable input actions, sitting between raw `winit`/DOM events
+//! and gameplay code. Replaces hardcoded matches like `VirtualKeyCode::W` or
+//! a `left_mouse_clicked` bool scattered through `run()`/`update_tick` with a
+//! small data-driven layer: an `ActionHandler` is built from named
+//! [`ActionLayout`]s (see `ActionHandlerBuilder`), fed raw `WindowEvent`s and
+//! DOM joystick/button events each frame, and then queried by action id
+//! instead of by key.
+//!
+//! Two kinds of action:
+//! - [`ActionKind::Button`] -- down/up, queried with [`ActionHandler::button_just_pressed`].
+//! - [`ActionKind::DigitalAxis`]/[`ActionKind::AnalogAxis`] -- aggregated into a
+//!   `[-1.0, 1.0]` float, queried with [`ActionHandler::axis`].
+//!
+//! Layouts let a whole binding table be swapped at runtime instead of
+//! threading an `is_flying`-style bool through every match arm: `"walking"`
+//! and `"flying"` (see [`default_walking_layout`]/[`default_flying_layout`])
+//! are alternate *base* layouts, switched with [`ActionHandler::set_base_layout`]
+//! when the player starts/stops flying. A menu or other overlay can instead
+//! be [`ActionHandler::push_overlay_layout`]ed on top -- it's consulted first,
+//! and falls through to the base layout for any action it doesn't rebind, or
+//! suppresses one outright by binding it to [`ActionKind::Blocked`] (see
+//! [`text_input_overlay_layout`], pushed while `text_input::TextInputBuffer`
+//! is composing a chat line).
+//!
+//! Bindings are `#[cfg_attr(feature = "serde", derive(...))]` (matching the
+//! optional-serde convention already used for save data in `zarray`) so a
+//! config file could deserialize a custom set of layouts without any code
+//! changes here -- this snapshot doesn't have a config loader wired up, so
+//! [`default_walking_layout`]/[`default_flying_layout`] are the only layouts
+//! actually constructed today.
+
+use std::collections::{HashMap, HashSet};
+use winit::event::{ElementState, MouseButton, VirtualKeyCode, WindowEvent};
+
+pub type ActionId = &'static str;
+
+pub const MOVE_FORWARD_BACKWARD: ActionId = "move_forward_backward";
+pub const STRAFE_LEFT_RIGHT: ActionId = "strafe_left_right";
+pub const JUMP: ActionId = "jump";
+pub const BREAK_BLOCK: ActionId = "break_block";
+pub const PLACE_BLOCK: ActionId = "place_block";
+
+/// A single raw digital input this crate can bind an action to. The web
+/// build's on-screen A/B buttons are aliased onto `MouseButton::Left`/`Right`
+/// (see `ActionHandler::set_source_held`) rather than getting their own
+/// variant, since they've always stood in for break/place -- the DOM
+/// joystick vectors are analog, not digital, and get their own source; see
+/// [`AnalogSource`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum InputSource {
+    Key(VirtualKeyCode),
+    MouseButton(MouseButton),
+}
+
+/// A continuous `[-1.0, 1.0]`-ish input -- one axis of a web build's
+/// on-screen joystick, fed in through [`ActionHandler::set_analog_value`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AnalogSource {
+    TranslationJoystickX,
+    TranslationJoystickY,
+    PitchYawJoystickX,
+    PitchYawJoystickY,
+}
+
+/// What an action id maps to: a single digital source that's down or up, two
+/// opposed digital sources aggregated into an axis, or one analog source
+/// passed straight through.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ActionKind {
+    Button(InputSource),
+    DigitalAxis {
+        positive: InputSource,
+        negative: InputSource,
+    },
+    AnalogAxis(AnalogSource),
+    /// Deliberately suppressed -- an overlay binds an action id to this
+    /// instead of just leaving it unbound, since an unbound id falls
+    /// through to the layout underneath rather than blocking it. See
+    /// [`text_input_overlay_layout`].
+    Blocked,
+}
+
+/// A named, swappable binding table -- see the module docs for how layouts
+/// compose. Built with [`ActionHandlerBuilder`].
+#[derive(Default)]
+pub struct ActionLayout {
+    bindings: HashMap<ActionId, ActionKind>,
+}
+
+/// Registers `(action_id, binding)` pairs, then builds an [`ActionLayout`].
+/// This is the extension point a config file would populate instead of
+/// [`default_walking_layout`]/[`default_flying_layout`], without touching
+/// `run()` or `update_tick`.
+#[derive(Default)]
+pub struct ActionHandlerBuilder {
+    bindings: HashMap<ActionId, ActionKind>,
+}
+
+impl ActionHandlerBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn bind(mut self, id: ActionId, kind: ActionKind) -> Self {
+        self.bindings.insert(id, kind);
+        self
+    }
+
+    pub fn build(self) -> ActionLayout {
+        ActionLayout {
+            bindings: self.bindings,
+        }
+    }
+}
+
+/// The desktop keyboard/mouse bindings `run()` hardcoded before this
+/// subsystem existed: WS for forward/backward, AD for strafing, space to
+/// jump, left/right mouse (and the web build's A/B buttons, aliased onto the
+/// same sources) for break/place. A rebind UI or config file would call
+/// `ActionHandlerBuilder` directly instead of this.
+///
+/// `MOVE_FORWARD_BACKWARD` and `STRAFE_LEFT_RIGHT` are registered here as the
+/// motivating examples of axis actions, but `camera::CameraController` still
+/// owns WASD movement directly -- migrating it onto these axes is follow-up
+/// work, not done here.
+pub fn default_walking_layout() -> ActionLayout {
+    ActionHandlerBuilder::new()
+        .bind(
+            MOVE_FORWARD_BACKWARD,
+            ActionKind::DigitalAxis {
+                positive: InputSource::Key(VirtualKeyCode::W),
+                negative: InputSource::Key(VirtualKeyCode::S),
+            },
+        )
+        .bind(
+            STRAFE_LEFT_RIGHT,
+            ActionKind::DigitalAxis {
+                positive: InputSource::Key(VirtualKeyCode::D),
+                negative: InputSource::Key(VirtualKeyCode::A),
+            },
+        )
+        .bind(
+            JUMP,
+            ActionKind::Button(InputSource::Key(VirtualKeyCode::Space)),
+        )
+        .bind(
+            BREAK_BLOCK,
+            ActionKind::Button(InputSource::MouseButton(MouseButton::Left)),
+        )
+        .bind(
+            PLACE_BLOCK,
+            ActionKind::Button(InputSource::MouseButton(MouseButton::Right)),
+        )
+        .build()
+}
+
+/// The "flying"/noclip base layout `world::WorldState::is_flying` switches to
+/// -- same break/place bindings as [`default_walking_layout`], but `JUMP` is
+/// unbound since ascend/descend is driven by `VirtualKeyCode::Space`/
+/// `LShift` directly in `camera::CameraController` today rather than through
+/// this action (follow-up work, same as the WASD axes above).
+pub fn default_flying_layout() -> ActionLayout {
+    ActionHandlerBuilder::new()
+        .bind(
+            MOVE_FORWARD_BACKWARD,
+            ActionKind::DigitalAxis {
+                positive: InputSource::Key(VirtualKeyCode::W),
+                negative: InputSource::Key(VirtualKeyCode::S),
+            },
+        )
+        .bind(
+            STRAFE_LEFT_RIGHT,
+            ActionKind::DigitalAxis {
+                positive: InputSource::Key(VirtualKeyCode::D),
+                negative: InputSource::Key(VirtualKeyCode::A),
+            },
+        )
+        .bind(
+            BREAK_BLOCK,
+            ActionKind::Button(InputSource::MouseButton(MouseButton::Left)),
+        )
+        .bind(
+            PLACE_BLOCK,
+            ActionKind::Button(InputSource::MouseButton(MouseButton::Right)),
+        )
+        .build()
+}
+
+/// Shadows every gameplay action while a `text_input::TextInputBuffer` is
+/// active, so typing `W`/`A`/`S`/`D` into a chat line (or clicking while
+/// composing one) doesn't also walk the character or break/place a block.
+pub fn text_input_overlay_layout() -> ActionLayout {
+    ActionHandlerBuilder::new()
+        .bind(MOVE_FORWARD_BACKWARD, ActionKind::Blocked)
+        .bind(STRAFE_LEFT_RIGHT, ActionKind::Blocked)
+        .bind(JUMP, ActionKind::Blocked)
+        .bind(BREAK_BLOCK, ActionKind::Blocked)
+        .bind(PLACE_BLOCK, ActionKind::Blocked)
+        .build()
+}
+
+pub const WALKING_LAYOUT: &str = "walking";
+pub const FLYING_LAYOUT: &str = "flying";
+pub const TEXT_INPUT_OVERLAY: &str = "text_input";
+
+/// Tracks which raw sources are currently held/deflected and resolves that
+/// state through whichever layout(s) are active. Queried once per
+/// `update_tick` via [`ActionHandler::axis`]/[`ActionHandler::button_just_pressed`],
+/// in place of the bespoke booleans `update_tick` used to take as parameters.
+///
+/// `active_layouts[0]` is the *base* layout (swapped wholesale by
+/// [`ActionHandler::set_base_layout`], e.g. walking vs. flying); anything
+/// above it is an *overlay* ([`ActionHandler::push_overlay_layout`], e.g. a
+/// pause menu) consulted first and falling through to lower layers for any
+/// action id it leaves unbound.
+pub struct ActionHandler {
+    layouts: HashMap<&'static str, ActionLayout>,
+    active_layouts: Vec<&'static str>,
+    held: HashSet<InputSource>,
+    /// Sources that transitioned from up to held since the last
+    /// `clear_frame_state` call -- consumed by `button_just_pressed` so a
+    /// held mouse button doesn't fire every frame.
+    just_pressed: HashSet<InputSource>,
+    analog_values: HashMap<AnalogSource, f32>,
+}
+
+impl ActionHandler {
+    /// Registers `layouts` (id, layout) and activates `base_layout` (which
+    /// must be one of the registered ids) as `active_layouts[0]`.
+    pub fn new(layouts: Vec<(&'static str, ActionLayout)>, base_layout: &'static str) -> Self {
+        let layouts: HashMap<_, _> = layouts.into_iter().collect();
+        assert!(
+            layouts.contains_key(base_layout),
+            "base_layout {base_layout:?} is not one of the registered layouts"
+        );
+        Self {
+            layouts,
+            active_layouts: vec![base_layout],
+            held: HashSet::new(),
+            just_pressed: HashSet::new(),
+            analog_values: HashMap::new(),
+        }
+    }
+
+    /// Swaps the base layout (index 0 of the active stack) wholesale -- e.g.
+    /// `world::WorldState::is_flying` toggling between [`WALKING_LAYOUT`] and
+    /// [`FLYING_LAYOUT`]. Overlay layouts pushed on top are left in place.
+    pub fn set_base_layout(&mut self, layout: &'static str) {
+        assert!(
+            self.layouts.contains_key(layout),
+            "layout {layout:?} is not registered"
+        );
+        self.active_layouts[0] = layout;
+    }
+
+    /// Pushes an overlay layout (e.g. a pause menu's bindings) on top of the
+    /// active stack -- it shadows the base layout for any action id it
+    /// binds, and falls through to the base layout for any it doesn't.
+    pub fn push_overlay_layout(&mut self, layout: &'static str) {
+        assert!(
+            self.layouts.contains_key(layout),
+            "layout {layout:?} is not registered"
+        );
+        self.active_layouts.push(layout);
+    }
+
+    /// Pops the most recently pushed overlay layout, if any (never pops the
+    /// base layout at index 0).
+    pub fn pop_overlay_layout(&mut self) {
+        if self.active_layouts.len() > 1 {
+            self.active_layouts.pop();
+        }
+    }
+
+    /// Looks up `id` in the active stack, starting from the topmost overlay
+    /// and falling through to the base layout.
+    fn resolve(&self, id: ActionId) -> Option<&ActionKind> {
+        self.active_layouts
+            .iter()
+            .rev()
+            .find_map(|layout| self.layouts[layout].bindings.get(id))
+    }
+
+    /// Feeds a window event's key/mouse state into the handler. Call for
+    /// every `WindowEvent` the event loop receives, desktop or web.
+    pub fn process_window_event(&mut self, event: &WindowEvent) {
+        match *event {
+            WindowEvent::KeyboardInput { input, .. } => {
+                if let Some(keycode) = input.virtual_keycode {
+                    self.set_source_held(
+                        InputSource::Key(keycode),
+                        input.state == ElementState::Pressed,
+                    );
+                }
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                self.set_source_held(
+                    InputSource::MouseButton(button),
+                    state == ElementState::Pressed,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    /// Same effect as `process_window_event`'s button handling, for any
+    /// digital source that doesn't arrive as a `winit` `WindowEvent` -- the
+    /// web build's on-screen A/B buttons (aliased onto `MouseButton::Left`/
+    /// `Right`, see `InputSource`'s docs).
+    pub fn set_source_held(&mut self, source: InputSource, held: bool) {
+        if held {
+            if self.held.insert(source) {
+                self.just_pressed.insert(source);
+            }
+        } else {
+            self.held.remove(&source);
+        }
+    }
+
+    /// Feeds one axis of the web build's on-screen joystick -- e.g.
+    /// `DomControlsUserEvent::TranslationJoystickMoved`'s `vector.0`/`.1` --
+    /// in directly as the current deflection of `source`, consumed by any
+    /// action bound with `ActionKind::AnalogAxis(source)`.
+    pub fn set_analog_value(&mut self, source: AnalogSource, value: f32) {
+        self.analog_values.insert(source, value);
+    }
+
+    /// `[-1.0, 1.0]` deflection for a `DigitalAxis`/`AnalogAxis` action, or
+    /// `0.0` if `id` isn't currently bound to an axis by the active layout
+    /// stack (including if it's unbound everywhere, or bound to a `Button`).
+    pub fn axis(&self, id: ActionId) -> f32 {
+        match self.resolve(id) {
+            Some(ActionKind::DigitalAxis { positive, negative }) => {
+                let positive = self.held.contains(positive) as i32 as f32;
+                let negative = self.held.contains(negative) as i32 as f32;
+                positive - negative
+            }
+            Some(ActionKind::AnalogAxis(source)) => {
+                self.analog_values.get(source).copied().unwrap_or(0.0)
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Whether a `Button` action transitioned from up to held since the last
+    /// `clear_frame_state` call. `false` if `id` isn't currently bound to a
+    /// button by the active layout stack.
+    pub fn button_just_pressed(&self, id: ActionId) -> bool {
+        match self.resolve(id) {
+            Some(ActionKind::Button(source)) => self.just_pressed.contains(source),
+            _ => false,
+        }
+    }
+
+    /// Drains the just-pressed set. Call once per tick, after gameplay code
+    /// has had a chance to query `button_just_pressed`.
+    pub fn clear_frame_state(&mut self) {
+        self.just_pressed.clear();
+    }
+
+    /// Forces every tracked source back to released and every analog axis
+    /// back to neutral. Call on focus loss, when the window (or a DOM
+    /// button release) won't necessarily get a chance to clear `held`
+    /// itself -- otherwise a source stays latched down until it happens to
+    /// repeat after focus returns.
+    pub fn release_all(&mut self) {
+        self.held.clear();
+        self.just_pressed.clear();
+        self.analog_values.clear();
+    }
+}
+
+impl Default for ActionHandler {
+    /// The desktop-key defaults, registered under [`WALKING_LAYOUT`]/
+    /// [`FLYING_LAYOUT`] with `"walking"` active -- what `run()` constructs
+    /// before any rebind UI or config file exists.
+    fn default() -> Self {
+        Self::new(
+            vec![
+                (WALKING_LAYOUT, default_walking_layout()),
+                (FLYING_LAYOUT, default_flying_layout()),
+                (TEXT_INPUT_OVERLAY, text_input_overlay_layout()),
+            ],
+            WALKING_LAYOUT,
+        )
+    }
+}
@@ -0,0 +1,90 @@
+//! A reusable press/release state tracker for arbitrary keys, generalizing
+//! what used to be one bespoke bool (or, for the jump button, a one-off
+//! `Pressed`/`Held`/`Released`/`Idle` machine) per tracked key. Events feed
+//! transitions through the single [`InputHelper::set_pressed`] regardless of
+//! which key or input source they came from, and game logic polls the
+//! result at tick time via [`InputHelper::key_pressed`]/[`key_held`]/
+//! [`key_released`] instead of reacting inline in the event match.
+//!
+//! [`key_held`]: InputHelper::key_held
+//! [`key_released`]: InputHelper::key_released
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum ButtonState {
+    Pressed,
+    Held,
+    Released,
+    Idle,
+}
+
+pub struct InputHelper<K> {
+    states: HashMap<K, ButtonState>,
+}
+
+impl<K: Eq + Hash + Copy> InputHelper<K> {
+    pub fn new() -> Self {
+        Self {
+            states: HashMap::new(),
+        }
+    }
+
+    /// Feeds a raw press/release event for `key`, advancing its state.
+    pub fn set_pressed(&mut self, key: K, pressed: bool) {
+        let state = self.states.entry(key).or_insert(ButtonState::Idle);
+        *state = if pressed {
+            match state {
+                ButtonState::Pressed | ButtonState::Held => ButtonState::Held,
+                ButtonState::Released | ButtonState::Idle => ButtonState::Pressed,
+            }
+        } else {
+            match state {
+                ButtonState::Pressed | ButtonState::Held => ButtonState::Released,
+                ButtonState::Released | ButtonState::Idle => ButtonState::Idle,
+            }
+        };
+    }
+
+    /// True only on the one tick `key` transitions down -- observing it
+    /// consumes the edge, advancing the state to `Held` the way the old
+    /// jump state machine could only be "pressed" for a single tick.
+    pub fn key_pressed(&mut self, key: K) -> bool {
+        let pressed = matches!(self.states.get(&key), Some(ButtonState::Pressed));
+        if pressed {
+            self.states.insert(key, ButtonState::Held);
+        }
+        pressed
+    }
+
+    /// True for as long as `key` is down, including the tick it was pressed.
+    pub fn key_held(&self, key: K) -> bool {
+        matches!(
+            self.states.get(&key),
+            Some(ButtonState::Pressed) | Some(ButtonState::Held)
+        )
+    }
+
+    /// True only on the one tick `key` transitions up; consumes the edge
+    /// the same way `key_pressed` does.
+    pub fn key_released(&mut self, key: K) -> bool {
+        let released = matches!(self.states.get(&key), Some(ButtonState::Released));
+        if released {
+            self.states.insert(key, ButtonState::Idle);
+        }
+        released
+    }
+
+    /// Forces every tracked key back to `Idle` -- for when a release event
+    /// will never arrive (e.g. window focus lost mid-press).
+    pub fn clear(&mut self) {
+        self.states.clear();
+    }
+}
+
+impl<K: Eq + Hash + Copy> Default for InputHelper<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
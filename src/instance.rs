@@ -1,8 +1,34 @@
+use crate::game_loop::Interpolate;
+
+/// Position + rotation of an instance between two fixed updates. Kept as a
+/// plain snapshot (rather than baked straight into `InstanceRaw`) so it can
+/// be linearly/slerp-interpolated before being handed to the GPU.
+#[derive(Copy, Clone)]
+pub struct Transform {
+    pub position: cgmath::Vector3<f32>,
+    pub rotation: cgmath::Quaternion<f32>,
+}
+
+impl Interpolate for Transform {
+    fn lerp(&self, next: &Self, alpha: f64) -> Self {
+        let alpha = alpha as f32;
+        Transform {
+            position: self.position + (next.position - self.position) * alpha,
+            rotation: self.rotation.slerp(next.rotation, alpha),
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct InstanceRaw {
     position: [f32; 4],
     rotation: [f32; 4],
+    // Columns of the 3x3 normal matrix, for lighting the instance correctly
+    // under rotation in the shadow/main passes. Every instance is uniformly
+    // scaled (unit cubes), so the normal matrix is just the rotation matrix
+    // itself -- no inverse-transpose needed.
+    normal_matrix: [[f32; 3]; 3],
     texture_atlas_offset: [f32; 2],
     color_adjust: [f32; 4],
 }
@@ -14,9 +40,12 @@ impl InstanceRaw {
         texture_atlas_offset: [f32; 2],
         color_adjust: [f32; 4],
     ) -> Self {
+        let normal_matrix: cgmath::Matrix3<f32> = rotation.into();
+
         InstanceRaw {
             position: [position.x, position.y, position.z, 1.0],
             rotation: [rotation.v.x, rotation.v.y, rotation.v.z, rotation.s],
+            normal_matrix: normal_matrix.into(),
             texture_atlas_offset: texture_atlas_offset,
             color_adjust: color_adjust,
         }
@@ -27,6 +56,10 @@ impl InstanceRaw {
         mem::size_of::<InstanceRaw>()
     }
 
+    // NOTE: shader_location indices here (4..=10) must stay in lockstep with
+    // both the main shader and the shadow-map shader's vertex inputs -- both
+    // pipelines bind this same layout (see `Scene::new`'s
+    // `vertex_buffer_layouts`).
     pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
         use std::mem;
         wgpu::VertexBufferLayout {
@@ -48,11 +81,26 @@ impl InstanceRaw {
                 },
                 wgpu::VertexAttribute {
                     offset: mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    shader_location: 6,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 11]>() as wgpu::BufferAddress,
+                    shader_location: 7,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 14]>() as wgpu::BufferAddress,
+                    shader_location: 8,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 17]>() as wgpu::BufferAddress,
                     shader_location: 9,
                     format: wgpu::VertexFormat::Float32x2,
                 },
                 wgpu::VertexAttribute {
-                    offset: mem::size_of::<[f32; 10]>() as wgpu::BufferAddress,
+                    offset: mem::size_of::<[f32; 19]>() as wgpu::BufferAddress,
                     shader_location: 10,
                     format: wgpu::VertexFormat::Float32x4,
                 },
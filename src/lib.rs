@@ -3,19 +3,36 @@ extern crate itertools;
 #[macro_use]
 extern crate bmp;
 
+pub mod analog_from_button;
 pub mod camera;
+pub mod canvas;
 pub mod color;
 pub mod dom_controls;
+pub mod events;
 pub mod face;
+pub mod frustum;
+pub mod game_loop;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod gamepad;
+pub mod input;
+pub mod input_helper;
 pub mod instance;
 pub mod light;
 pub mod map_generation;
+pub mod mesh;
+pub mod orientation_filter;
+pub mod render_world;
 pub mod spawner;
+pub mod text_input;
 pub mod texture;
+pub mod timings;
+pub mod touch_gesture;
 pub mod vec_extra;
 pub mod vertex;
 pub mod world;
+pub mod zarray;
 
+use canvas::Loop as _;
 use cgmath::Point3;
 use dom_controls::DomControlsUserEvent;
 use futures::executor::block_on;
@@ -23,29 +40,62 @@ use spawner::Spawner;
 use std::{borrow::Cow, collections::HashSet, future::Future, mem, pin::Pin, task};
 use wgpu::{util::DeviceExt, SurfaceTexture};
 use winit::{
-    event::{DeviceEvent, ElementState, Event, MouseButton, VirtualKeyCode, WindowEvent},
+    event::{DeviceEvent, ElementState, MouseButton, VirtualKeyCode, WindowEvent},
     event_loop::{ControlFlow, EventLoopBuilder},
 };
-use world::CHUNK_XZ_SIZE;
+use world::{CHUNK_XZ_SIZE, CHUNK_Y_SIZE};
 
 use crate::world::ChunkDataType;
 
 static RENDER_WIREFRAME: bool = false;
+// Only the initial `visible` value of the matching `render_world::RenderEntity`
+// spawned in `Scene::new` -- toggle the entity at runtime (via
+// `RenderWorld::set_visible`) instead of flipping this and recompiling.
 static RENDER_LIGHT_DEBUG_DATA: bool = false;
 static RENDER_CHARACTER_ENTITY: bool = true;
+// Toggle so frustum culling can be disabled to debug chunks popping in/out
+// at the frustum boundary vs. a real meshing/streaming bug.
+static ENABLE_FRUSTUM_CULLING: bool = true;
+// Toggle the HDR-target + tonemap path off to draw straight into the sRGB
+// swapchain `view` instead, e.g. to A/B the ACES curve against the raw
+// clamped output or to debug the tonemap pass itself in isolation.
+static ENABLE_HDR_TONEMAP: bool = true;
+
+/// Format of the intermediate HDR color target the forward pipeline renders
+/// into, tonemapped down to the LDR swapchain at the end of the frame.
+const HDR_TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// Rate of `game_loop::GameLoop`'s fixed-step accumulator built in
+/// `Canvas::resumed`, so
+/// physics/animation speed is decoupled from however fast the display
+/// redraws.
+const FIXED_UPDATES_PER_SECOND: u32 = 60;
+/// Upper bound on a single frame's elapsed wall-clock time fed into the
+/// accumulator, so a stall (e.g. the tab losing focus) doesn't force a burst
+/// of catch-up updates once it resumes.
+const MAX_FRAME_TIME: f64 = 0.1;
 
 #[allow(dead_code)]
 const VERBOSE_LOGS: bool = false;
 
+/// How many block positions `WorldState::random_tick` samples per
+/// `update_tick` -- high enough that growth is visible within a few
+/// seconds, low enough that it's not a meaningful per-frame cost.
+const RANDOM_TICK_BUDGET: usize = 64;
+
 struct State {
     surface_config: wgpu::SurfaceConfiguration,
     surface: wgpu::Surface,
     device: wgpu::Device,
     queue: wgpu::Queue,
+    has_timestamp_query: bool,
 
     camera: camera::Camera,
     camera_controller: camera::CameraController,
     camera_uniform: camera::CameraUniform,
+    camera_mode: camera::CameraMode,
+    orbit_camera: camera::OrbitCamera,
+    orbit_camera_controller: camera::OrbitCameraController,
     light_uniform: light::LightUniform,
     world_state: world::WorldState,
 }
@@ -54,18 +104,21 @@ struct VertexBufers {
     blocks: wgpu::Buffer,
     light_volume: wgpu::Buffer,
     character_entity: wgpu::Buffer,
+    selection_outline: wgpu::Buffer,
 }
 
 struct IndexBufers {
     blocks: wgpu::Buffer,
     light_volume: wgpu::Buffer,
     character_entity: wgpu::Buffer,
+    selection_outline: wgpu::Buffer,
 }
 
 struct IndexCounts {
     blocks: usize,
     light_volume: usize,
     character_entity: usize,
+    selection_outline: usize,
 }
 
 struct Scene {
@@ -84,12 +137,38 @@ struct Scene {
     depth_texture: texture::Texture,
     pipeline: wgpu::RenderPipeline,
 
+    // `shadow_map_texture` is a `light::NUM_CASCADES`-layer `D2Array` depth
+    // texture; `shadow_cascade_layer_views` holds one single-layer view per
+    // cascade so each can be bound as its own render pass's depth attachment,
+    // while `shadow_map_texture.view` (spanning all layers) stays what the
+    // forward pass's `texture_bind_group` samples from.
     shadow_map_texture: texture::Texture,
+    shadow_cascade_layer_views: Vec<wgpu::TextureView>,
     shadow_map_pipeline: wgpu::RenderPipeline,
 
+    // Forward pass renders into this `Rgba16Float` target instead of the LDR
+    // swapchain, so highlights above 1.0 survive until the tonemap pass.
+    hdr_texture: texture::Texture,
+    tonemap_bind_group_layout: wgpu::BindGroupLayout,
+    tonemap_bind_group: wgpu::BindGroup,
+    tonemap_pipeline: wgpu::RenderPipeline,
+
     pipeline_wire: Option<wgpu::RenderPipeline>,
     pipeline_wire_no_instancing: Option<wgpu::RenderPipeline>,
     pipeline_solid_color: Option<wgpu::RenderPipeline>,
+
+    // Auxiliary one-off draws (character model, light-volume wireframe),
+    // data-driven instead of `const`-gated -- see `render_world` module docs
+    // and `Game::render_system`.
+    render_world: render_world::RenderWorld,
+    // The `render_world` entity `update_tick` toggles visibility on based on
+    // `WorldState::highlighted_block`.
+    selection_outline_entity: render_world::EntityId,
+
+    // `RefCell`/`Cell` so `render_frame` can record timings through the same
+    // shared `&Scene` borrow it uses for every other GPU resource.
+    timings: std::cell::RefCell<timings::TimingQueries>,
+    last_timings: std::cell::Cell<timings::Timings>,
 }
 
 struct Game {
@@ -148,6 +227,11 @@ impl State {
             .await
             .expect("Unable to find a suitable GPU adapter!");
 
+        let has_timestamp_query = adapter.features().contains(wgpu::Features::TIMESTAMP_QUERY);
+        if !has_timestamp_query {
+            log::warn!("Adapter doesn't support TIMESTAMP_QUERY, GPU timings will use CPU Instant fallback");
+        }
+
         let supported_formats = surface.get_supported_formats(&adapter);
         log::warn!("Supported formats: {:?}", supported_formats);
 
@@ -211,6 +295,15 @@ impl State {
             [2048, 2048],
         );
 
+        let orbit_camera_controller = camera::OrbitCameraController::new(0.005, 1.0, 20.0);
+        let orbit_camera = camera::OrbitCamera::new(
+            camera.target,
+            surface_config.width as f32 / surface_config.height as f32,
+            70.0,
+            0.1,
+            zfar,
+        );
+
         let mut camera_uniform = camera::CameraUniform::new();
         camera_uniform.update_view_proj(&camera);
 
@@ -222,14 +315,34 @@ impl State {
             surface,
             device,
             queue,
+            has_timestamp_query,
 
             camera,
             camera_controller,
             camera_uniform,
+            camera_mode: camera::CameraMode::FlyCam,
+            orbit_camera,
+            orbit_camera_controller,
             light_uniform,
             world_state,
         }
     }
+
+    /// Acquires the next swapchain frame, reconfiguring the surface and
+    /// retrying once if it's out of date (e.g. after the window was resized
+    /// between frames). Split out of `Game::render` so `Canvas` can acquire
+    /// the frame itself and hand it to `canvas::Loop::render`.
+    fn acquire_frame(&self) -> SurfaceTexture {
+        match self.surface.get_current_texture() {
+            Ok(frame) => frame,
+            Err(_) => {
+                self.surface.configure(&self.device, &self.surface_config);
+                self.surface
+                    .get_current_texture()
+                    .expect("Failed to acquire next surface texture!")
+            }
+        }
+    }
 }
 
 impl Scene {
@@ -350,6 +463,11 @@ impl Scene {
             source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("shader.wgsl"))),
         });
 
+        let tonemap_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Tonemap Shader"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("tonemap.wgsl"))),
+        });
+
         log::info!("Creating shadow map render pipeline");
         let shadow_map_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: None,
@@ -400,6 +518,16 @@ impl Scene {
             push_constant_ranges: &[],
         });
 
+        // The forward pass below renders into `hdr_texture` when HDR tonemapping
+        // is enabled, and straight into the swapchain format otherwise -- the
+        // pipeline's target format has to match whichever view it's actually
+        // bound against.
+        let forward_pass_color_format = if ENABLE_HDR_TONEMAP {
+            HDR_TEXTURE_FORMAT
+        } else {
+            surface_config.format
+        };
+
         log::info!("Creating forward-pass render pipeline");
         let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: None,
@@ -413,7 +541,7 @@ impl Scene {
                 module: &shader,
                 entry_point: "fs_main",
                 targets: &[Some(wgpu::ColorTargetState {
-                    format: surface_config.format,
+                    format: forward_pass_color_format,
                     blend: Some(wgpu::BlendState {
                         color: wgpu::BlendComponent {
                             operation: wgpu::BlendOperation::Add,
@@ -454,7 +582,7 @@ impl Scene {
                         module: &shader,
                         entry_point: "fs_solid_color",
                         targets: &[Some(wgpu::ColorTargetState {
-                            format: surface_config.format,
+                            format: forward_pass_color_format,
                             blend: Some(wgpu::BlendState {
                                 color: wgpu::BlendComponent {
                                     operation: wgpu::BlendOperation::Add,
@@ -503,7 +631,7 @@ impl Scene {
                             module: &shader,
                             entry_point: "fs_wire",
                             targets: &[Some(wgpu::ColorTargetState {
-                                format: surface_config.format,
+                                format: forward_pass_color_format,
                                 blend: Some(wgpu::BlendState {
                                     color: wgpu::BlendComponent {
                                         operation: wgpu::BlendOperation::Add,
@@ -540,6 +668,74 @@ impl Scene {
         let pipeline_wire = create_wire_pipeline("vs_main", Some(wgpu::Face::Back));
         let pipeline_wire_no_instancing = create_wire_pipeline("vs_wire_no_instancing", None);
 
+        let tonemap_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[
+                    // HDR color target
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                    // `LightUniform` carries `exposure`, which the tonemap
+                    // pass applies before the ACES curve.
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: wgpu::BufferSize::new(
+                                mem::size_of::<light::LightUniformRaw>() as u64,
+                            ),
+                        },
+                        count: None,
+                    },
+                ],
+            });
+
+        log::info!("Creating tonemap render pipeline");
+        let tonemap_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: None,
+            layout: Some(
+                &device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: None,
+                    bind_group_layouts: &[&tonemap_bind_group_layout],
+                    push_constant_ranges: &[],
+                }),
+            ),
+            vertex: wgpu::VertexState {
+                module: &tonemap_shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &tonemap_shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_config.format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
         let light_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Light VB"),
             contents: bytemuck::cast_slice(&[light_uniform.to_raw()]),
@@ -548,7 +744,36 @@ impl Scene {
 
         let face = face::Face::new();
         let sunlight_vtx_data = light_uniform.vertex_data_for_sunlight();
-        let character_vtx_data = world_state.character_entity.vertex_data();
+        // No previous tick yet, so `prev_position == position` and any alpha
+        // gives the same (initial) pose.
+        let character_vtx_data = world_state.character_entity.vertex_data(1.0);
+        // Nothing's highlighted yet this early, so seed the buffer with a
+        // degenerate (zero-extent) cuboid -- same vertex/index count as a
+        // real outline, just invisible -- rather than leaving it empty;
+        // `update_tick` only rewrites it once something's actually targeted,
+        // and the entity starts with `visible: false` regardless.
+        let selection_outline_vtx_data = world_state
+            .selection_outline_vertex_data()
+            .unwrap_or_else(|| {
+                let center = world::get_world_center();
+                let mut placeholder = vertex::QuadListRenderData {
+                    vertex_data: vec![],
+                    index_data: vec![],
+                };
+                vertex::Vertex::generate_quad_data_for_cuboid(
+                    &vertex::CuboidCoords {
+                        left: center.x as f32,
+                        right: center.x as f32,
+                        bottom: center.y as f32,
+                        top: center.y as f32,
+                        near: center.z as f32,
+                        far: center.z as f32,
+                    },
+                    None,
+                    &mut placeholder,
+                );
+                placeholder
+            });
 
         let vertex_buffers = VertexBufers {
             blocks: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -566,6 +791,11 @@ impl Scene {
                 contents: bytemuck::cast_slice(&character_vtx_data.vertex_data),
                 usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
             }),
+            selection_outline: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Selection Outline Vertex Buffer"),
+                contents: bytemuck::cast_slice(&selection_outline_vtx_data.vertex_data),
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            }),
         };
 
         let index_buffers = IndexBufers {
@@ -584,12 +814,18 @@ impl Scene {
                 contents: bytemuck::cast_slice(&character_vtx_data.index_data),
                 usage: wgpu::BufferUsages::INDEX,
             }),
+            selection_outline: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Selection Outline Index Buffer"),
+                contents: bytemuck::cast_slice(&selection_outline_vtx_data.index_data),
+                usage: wgpu::BufferUsages::INDEX,
+            }),
         };
 
         let index_counts = IndexCounts {
             blocks: face.index_data.len(),
             light_volume: sunlight_vtx_data.index_data.len(),
             character_entity: character_vtx_data.index_data.len(),
+            selection_outline: selection_outline_vtx_data.index_data.len(),
         };
 
         let texture_atlas = texture::Texture::create_pixel_art_image_texture(
@@ -614,10 +850,16 @@ impl Scene {
         });
 
         // Shadow Map
-        let shadow_map_texture = texture::Texture::create_depth_texture(
+        //
+        // One `D2Array` depth texture with `light::NUM_CASCADES` layers,
+        // rather than `light::NUM_CASCADES` separate textures, so the
+        // existing `texture_bind_group` layout (a single texture + sampler
+        // binding) still samples it as one resource.
+        let shadow_map_texture = texture::Texture::create_depth_texture_array(
             "shadow_map_texture",
             &device,
             light_uniform.shadow_map_pixel_size,
+            light::NUM_CASCADES as u32,
             &wgpu::SamplerDescriptor {
                 address_mode_u: wgpu::AddressMode::ClampToEdge,
                 address_mode_v: wgpu::AddressMode::ClampToEdge,
@@ -626,12 +868,30 @@ impl Scene {
                 mag_filter: wgpu::FilterMode::Nearest,
                 min_filter: wgpu::FilterMode::Nearest,
                 mipmap_filter: wgpu::FilterMode::Nearest,
-                compare: None,
+                // `Some` gives us a `sampler_comparison` in the shader, which
+                // is what both `ShadowSettings::HardwareComparison` (a single
+                // tap, using the hardware's free 2x2 PCF) and
+                // `ShadowSettings::Pcf` (multiple taps averaged manually)
+                // sample through.
+                compare: Some(wgpu::CompareFunction::LessEqual),
                 lod_min_clamp: -100.0,
                 lod_max_clamp: 100.0,
                 ..Default::default()
             },
         );
+        let shadow_cascade_layer_views: Vec<wgpu::TextureView> = (0..light::NUM_CASCADES)
+            .map(|cascade_idx| {
+                shadow_map_texture
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor {
+                        label: Some("shadow_map_texture cascade layer view"),
+                        dimension: Some(wgpu::TextureViewDimension::D2),
+                        base_array_layer: cascade_idx as u32,
+                        array_layer_count: Some(1),
+                        ..Default::default()
+                    })
+            })
+            .collect();
 
         // Create bind groups
         let albedo_only_texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
@@ -766,6 +1026,63 @@ impl Scene {
             },
         );
 
+        let hdr_texture = texture::Texture::create_color_texture(
+            "hdr_texture",
+            &device,
+            [surface_config.width, surface_config.height],
+            HDR_TEXTURE_FORMAT,
+            &wgpu::SamplerDescriptor {
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                address_mode_w: wgpu::AddressMode::ClampToEdge,
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                mipmap_filter: wgpu::FilterMode::Nearest,
+                ..Default::default()
+            },
+        );
+
+        let tonemap_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &tonemap_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&hdr_texture.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&hdr_texture.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: light_buf.as_entire_binding(),
+                },
+            ],
+            label: None,
+        });
+
+        let timings = timings::TimingQueries::new(device, queue);
+
+        let mut render_world = render_world::RenderWorld::new();
+        render_world.spawn(render_world::RenderEntity {
+            mesh: render_world::MeshKind::LightVolume,
+            pipeline: render_world::RenderPipelineKind::WireNoInstancing,
+            visible: RENDER_LIGHT_DEBUG_DATA,
+        });
+        render_world.spawn(render_world::RenderEntity {
+            mesh: render_world::MeshKind::CharacterEntity,
+            pipeline: render_world::RenderPipelineKind::SolidColor,
+            visible: RENDER_CHARACTER_ENTITY,
+        });
+        // Visibility toggles every frame in `Game::update_tick` based on
+        // whether `WorldState::highlighted_block` is currently `Some`, unlike
+        // the two entities above whose visibility is fixed at spawn time.
+        let selection_outline_entity = render_world.spawn(render_world::RenderEntity {
+            mesh: render_world::MeshKind::SelectionOutline,
+            pipeline: render_world::RenderPipelineKind::WireNoInstancing,
+            visible: false,
+        });
+
         Scene {
             vertex_buffers,
             index_buffers,
@@ -784,10 +1101,22 @@ impl Scene {
 
             shadow_map_pipeline,
             shadow_map_texture,
+            shadow_cascade_layer_views,
+
+            hdr_texture,
+            tonemap_bind_group_layout,
+            tonemap_bind_group,
+            tonemap_pipeline,
 
             pipeline_wire,
             pipeline_wire_no_instancing,
             pipeline_solid_color,
+
+            render_world,
+            selection_outline_entity,
+
+            timings: std::cell::RefCell::new(timings),
+            last_timings: std::cell::Cell::new(timings::Timings::default()),
         }
     }
 }
@@ -832,19 +1161,108 @@ impl Game {
                     ..Default::default()
                 },
             );
+            self.scene.hdr_texture = texture::Texture::create_color_texture(
+                "hdr_texture",
+                &self.state.device,
+                [
+                    self.state.surface_config.width,
+                    self.state.surface_config.height,
+                ],
+                HDR_TEXTURE_FORMAT,
+                &wgpu::SamplerDescriptor {
+                    address_mode_u: wgpu::AddressMode::ClampToEdge,
+                    address_mode_v: wgpu::AddressMode::ClampToEdge,
+                    address_mode_w: wgpu::AddressMode::ClampToEdge,
+                    mag_filter: wgpu::FilterMode::Linear,
+                    min_filter: wgpu::FilterMode::Linear,
+                    mipmap_filter: wgpu::FilterMode::Nearest,
+                    ..Default::default()
+                },
+            );
+            // Bind groups capture the HDR texture's view by reference, so
+            // rebuild it whenever the texture it names is recreated.
+            self.scene.tonemap_bind_group =
+                self.state
+                    .device
+                    .create_bind_group(&wgpu::BindGroupDescriptor {
+                        layout: &self.scene.tonemap_bind_group_layout,
+                        entries: &[
+                            wgpu::BindGroupEntry {
+                                binding: 0,
+                                resource: wgpu::BindingResource::TextureView(
+                                    &self.scene.hdr_texture.view,
+                                ),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 1,
+                                resource: wgpu::BindingResource::Sampler(
+                                    &self.scene.hdr_texture.sampler,
+                                ),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 2,
+                                resource: self.scene.light_buf.as_entire_binding(),
+                            },
+                        ],
+                        label: None,
+                    });
             self.state.camera.aspect =
                 self.state.surface_config.width as f32 / self.state.surface_config.height as f32;
+            self.state.orbit_camera.aspect = self.state.camera.aspect;
         }
     }
 
-    pub fn update_tick(&mut self, left_mouse_clicked: &mut bool, right_mouse_clicked: &mut bool) {
+    /// Rewrites the selection-outline vertex buffer from whatever's
+    /// currently under the crosshair (see
+    /// `WorldState::selection_outline_vertex_data`) and shows/hides the
+    /// `render_world` entity to match, called anywhere `highlight_colliding_block`
+    /// might have changed `highlighted_block`.
+    fn sync_selection_outline(state: &mut State, scene: &mut Scene) {
+        match state.world_state.selection_outline_vertex_data() {
+            Some(vtx_data) => {
+                state.queue.write_buffer(
+                    &scene.vertex_buffers.selection_outline,
+                    0,
+                    bytemuck::cast_slice(&vtx_data.vertex_data),
+                );
+                scene
+                    .render_world
+                    .set_visible(scene.selection_outline_entity, true);
+            }
+            None => {
+                scene
+                    .render_world
+                    .set_visible(scene.selection_outline_entity, false);
+            }
+        }
+    }
+
+    /// `fixed_dt` is `game_loop::GameLoop::fixed_time_step()` -- this runs
+    /// once per accumulator step, so it must use the fixed step rather than
+    /// whatever the display's actual frame time happens to be.
+    pub fn update_tick(&mut self, action_handler: &input::ActionHandler, fixed_dt: f64) {
         let state = &mut self.state;
         let scene = &mut self.scene;
 
+        // The flycam always keeps updating regardless of render mode, since
+        // world logic (movement, collision, block breaking) is always
+        // relative to it -- only the camera fed into `camera_uniform` below
+        // switches.
         let update_result = state
             .camera_controller
             .update_camera(&mut state.camera, &state.world_state);
-        state.camera_uniform.update_view_proj(&state.camera);
+
+        state.orbit_camera.focus = state.camera.eye;
+        state
+            .orbit_camera_controller
+            .update_orbit_camera(&mut state.orbit_camera);
+
+        match state.camera_mode {
+            camera::CameraMode::FlyCam => state.camera_uniform.update_view_proj(&state.camera),
+            camera::CameraMode::Orbit => {
+                state.camera_uniform.update_view_proj(&state.orbit_camera)
+            }
+        }
         state.queue.write_buffer(
             &scene.camera_staging_buf,
             0,
@@ -852,6 +1270,8 @@ impl Game {
         );
 
         state.light_uniform.update_light_space_proj(&state.camera);
+        state.light_uniform.update_cascades(&state.camera);
+        state.light_uniform.point_lights = state.world_state.point_lights();
         state.queue.write_buffer(
             &scene.light_buf,
             0,
@@ -866,13 +1286,8 @@ impl Game {
         let mut chunk_mods: Vec<ChunkModification> = vec![];
 
         if update_result.did_move {
-            let chunks_modified = state.world_state.highlight_colliding_block(&state.camera);
-            for chunk_idx in chunks_modified {
-                chunk_mods.push(ChunkModification {
-                    new_chunk: chunk_idx,
-                    old_chunk: chunk_idx,
-                });
-            }
+            state.world_state.highlight_colliding_block(&state.camera);
+            Self::sync_selection_outline(state, scene);
 
             let sunlight_vtx_data = state.light_uniform.vertex_data_for_sunlight();
             state.queue.write_buffer(
@@ -891,16 +1306,16 @@ impl Game {
         }
 
         // Break a block with the camera!
-        if *left_mouse_clicked || *right_mouse_clicked {
-            let chunks_modified = if *right_mouse_clicked {
+        let place_block = action_handler.button_just_pressed(input::PLACE_BLOCK);
+        let break_block = action_handler.button_just_pressed(input::BREAK_BLOCK);
+        if place_block || break_block {
+            let chunks_modified = if place_block {
                 state
                     .world_state
                     .place_block(&state.camera, world::BlockType::Sand)
             } else {
                 state.world_state.break_block(&state.camera)
             };
-            *left_mouse_clicked = false;
-            *right_mouse_clicked = false;
 
             for chunk_idx in chunks_modified {
                 chunk_mods.push(ChunkModification {
@@ -910,13 +1325,8 @@ impl Game {
             }
 
             if !update_result.did_move {
-                let chunks_modified = state.world_state.highlight_colliding_block(&state.camera);
-                for chunk_idx in chunks_modified {
-                    chunk_mods.push(ChunkModification {
-                        new_chunk: chunk_idx,
-                        old_chunk: chunk_idx,
-                    });
-                }
+                state.world_state.highlight_colliding_block(&state.camera);
+                Self::sync_selection_outline(state, scene);
             }
         }
 
@@ -954,6 +1364,26 @@ impl Game {
             scene.chunk_order = new_chunk_order;
         }
 
+        // Install whatever chunks the generation worker pool finished since
+        // last frame and fold them into this frame's remesh set -- they may
+        // have been requested several frames ago by `maybe_allocate_chunk`,
+        // not necessarily by anything above in this same frame.
+        for chunk_idx in state.world_state.tick() {
+            chunk_mods.push(ChunkModification {
+                new_chunk: chunk_idx,
+                old_chunk: chunk_idx,
+            });
+        }
+
+        // Grow/spread whatever vegetation random chance picks this frame
+        // (flowers, grass, saplings) -- see `WorldState::random_tick`.
+        for chunk_idx in state.world_state.random_tick(RANDOM_TICK_BUDGET) {
+            chunk_mods.push(ChunkModification {
+                new_chunk: chunk_idx,
+                old_chunk: chunk_idx,
+            });
+        }
+
         if !chunk_mods.is_empty() {
             #[cfg(not(target_arch = "wasm32"))]
             let chunk_mod_time = std::time::Instant::now();
@@ -971,67 +1401,83 @@ impl Game {
                 );
             }
 
-            let new_chunk_datas = chunk_mods
-                .iter()
-                .map(|chunk_mod| {
-                    let new_chunk_data = state
-                        .world_state
-                        .compute_chunk_mesh(chunk_mod.new_chunk, &state.camera);
-
-                    let render_descriptor_idx = state
+            // Render-descriptor bookkeeping moves each chunk_mod's slot from
+            // `old_chunk` to `new_chunk` immediately, ahead of that chunk's
+            // mesh actually being ready, so `get_render_descriptor_idx` is
+            // already correct by the time the drain loop below re-looks it
+            // up for a finished `ChunkData`.
+            for chunk_mod in chunk_mods.iter() {
+                let render_descriptor_idx = state
+                    .world_state
+                    .get_render_descriptor_idx(chunk_mod.old_chunk);
+                if chunk_mod.new_chunk != chunk_mod.old_chunk {
+                    state.world_state.set_render_descriptor_idx(
+                        chunk_mod.old_chunk,
+                        world::NO_RENDER_DESCRIPTOR_INDEX,
+                    );
+                    state
                         .world_state
-                        .get_render_descriptor_idx(chunk_mod.old_chunk);
-                    if chunk_mod.new_chunk != chunk_mod.old_chunk {
-                        state.world_state.set_render_descriptor_idx(
-                            chunk_mod.old_chunk,
-                            world::NO_RENDER_DESCRIPTOR_INDEX,
-                        );
-                        state
-                            .world_state
-                            .set_render_descriptor_idx(chunk_mod.new_chunk, render_descriptor_idx);
-                    }
+                        .set_render_descriptor_idx(chunk_mod.new_chunk, render_descriptor_idx);
+                }
+            }
 
-                    (new_chunk_data, render_descriptor_idx)
-                })
-                .collect::<Vec<_>>();
+            // Meshing (greedy-instancing each chunk's blocks into
+            // `InstanceRaw`s) is the expensive part of a chunk update, so
+            // instead of blocking this frame on it, hand each dirty chunk to
+            // `chunk_mesh_pool` and pick up its `ChunkData` whenever it's
+            // ready -- see the drain loop below, which runs every frame so
+            // replies from chunks dispatched several frames ago still get
+            // installed.
+            for chunk_mod in chunk_mods.iter() {
+                state
+                    .world_state
+                    .dispatch_chunk_mesh(chunk_mod.new_chunk, &state.camera);
+            }
 
             #[cfg(not(target_arch = "wasm32"))]
             if VERBOSE_LOGS && update_result.did_move_chunks {
                 println!(
-                    "Took {}ms to update chunks",
+                    "Took {}ms to dispatch chunk meshing",
                     chunk_mod_time.elapsed().as_millis()
                 );
             }
+        }
 
-            for (new_chunk_data, render_descriptor_idx) in new_chunk_datas.into_iter() {
-                let chunk_render_descriptor =
-                    &mut scene.chunk_render_descriptors[render_descriptor_idx];
-
-                for typed_instances in new_chunk_data.typed_instances_vec.iter() {
-                    let maybe_instance_buffer = chunk_render_descriptor
-                        .annotated_instance_buffers
-                        .iter_mut()
-                        .find(|ib| ib.data_type == typed_instances.data_type);
-
-                    if let Some(instance_buffer) = maybe_instance_buffer {
-                        state.queue.write_buffer(
-                            &instance_buffer.buffer,
-                            0,
-                            bytemuck::cast_slice(&typed_instances.instance_data),
-                        );
-                        instance_buffer.len = typed_instances.instance_data.len();
-                    }
+        // Install whatever chunk_mesh_pool finished meshing since last
+        // frame. The render-descriptor slot a chunk owns may have moved (or
+        // been freed entirely) between dispatch and now, so this re-looks-up
+        // `get_render_descriptor_idx` rather than trusting an index captured
+        // at dispatch time, and skips chunks that scrolled out of view
+        // before their mesh reply arrived.
+        for new_chunk_data in state.world_state.drain_meshed_chunks() {
+            let render_descriptor_idx = state
+                .world_state
+                .get_render_descriptor_idx(new_chunk_data.position);
+            if render_descriptor_idx == world::NO_RENDER_DESCRIPTOR_INDEX {
+                continue;
+            }
+
+            let chunk_render_descriptor =
+                &mut scene.chunk_render_descriptors[render_descriptor_idx];
+
+            for typed_instances in new_chunk_data.typed_instances_vec.iter() {
+                let maybe_instance_buffer = chunk_render_descriptor
+                    .annotated_instance_buffers
+                    .iter_mut()
+                    .find(|ib| ib.data_type == typed_instances.data_type);
+
+                if let Some(instance_buffer) = maybe_instance_buffer {
+                    state.queue.write_buffer(
+                        &instance_buffer.buffer,
+                        0,
+                        bytemuck::cast_slice(&typed_instances.instance_data),
+                    );
+                    instance_buffer.len = typed_instances.instance_data.len();
                 }
             }
         }
 
-        state.world_state.physics_tick();
-        let updated_character_vtx_data = state.world_state.character_entity.vertex_data();
-        state.queue.write_buffer(
-            &scene.vertex_buffers.character_entity,
-            0,
-            bytemuck::cast_slice(&updated_character_vtx_data.vertex_data),
-        );
+        state.world_state.physics_tick(fixed_dt, &state.camera);
     }
 
     fn render_chunk<'a>(
@@ -1075,27 +1521,84 @@ impl Game {
         }
     }
 
-    pub fn render_frame(&mut self, spawner: &Spawner) -> SurfaceTexture {
-        let state = &self.state;
+    /// Draws every visible `render_world::RenderEntity` (the character model,
+    /// the light-volume wireframe, ...), switching pipeline and bind groups
+    /// at most once per `RenderPipelineKind` rather than once per entity --
+    /// see `render_world`'s module docs.
+    fn render_system<'a>(&'a self, rpass: &mut wgpu::RenderPass<'a>) {
         let scene = &self.scene;
 
-        let frame = match state.surface.get_current_texture() {
-            Ok(frame) => frame,
-            Err(_) => {
-                state
-                    .surface
-                    .configure(&state.device, &state.surface_config);
-                state
-                    .surface
-                    .get_current_texture()
-                    .expect("Failed to acquire next surface texture!")
+        for (pipeline_kind, entities) in scene.render_world.visible_grouped_by_pipeline() {
+            let pipe = match pipeline_kind {
+                render_world::RenderPipelineKind::SolidColor => &scene.pipeline_solid_color,
+                render_world::RenderPipelineKind::WireNoInstancing => {
+                    &scene.pipeline_wire_no_instancing
+                }
+            };
+            let Some(ref pipe) = pipe else {
+                continue;
+            };
+            rpass.set_pipeline(pipe);
+            if pipeline_kind == render_world::RenderPipelineKind::SolidColor {
+                rpass.set_bind_group(0, &scene.texture_bind_group, &[]);
+                rpass.set_bind_group(1, &scene.camera_bind_group, &[]);
+                rpass.set_bind_group(2, &scene.light_bind_group, &[]);
             }
-        };
+
+            for entity in entities {
+                let (vertex_buffer, index_buffer, index_count) = match entity.mesh {
+                    render_world::MeshKind::CharacterEntity => (
+                        &scene.vertex_buffers.character_entity,
+                        &scene.index_buffers.character_entity,
+                        scene.index_counts.character_entity,
+                    ),
+                    render_world::MeshKind::LightVolume => (
+                        &scene.vertex_buffers.light_volume,
+                        &scene.index_buffers.light_volume,
+                        scene.index_counts.light_volume,
+                    ),
+                    render_world::MeshKind::SelectionOutline => (
+                        &scene.vertex_buffers.selection_outline,
+                        &scene.index_buffers.selection_outline,
+                        scene.index_counts.selection_outline,
+                    ),
+                };
+                rpass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                rpass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                rpass.draw_indexed(0..index_count as u32, 0, 0..1);
+            }
+        }
+    }
+
+    pub fn timings(&self) -> timings::Timings {
+        self.scene.last_timings.get()
+    }
+
+    /// `alpha` is `game_loop::GameLoop::blending_factor()`, i.e. how far
+    /// between the previous and current fixed update we are -- passed down
+    /// so per-entity interpolation (e.g. `CharacterEntity::vertex_data`)
+    /// renders smooth motion even when the render rate and the fixed update
+    /// rate diverge.
+    ///
+    /// `frame` is acquired by the caller (`Canvas`) rather than here, so the
+    /// `wgpu::Device::push_error_scope`/`pop_error_scope` pair bracketing
+    /// this call can live in the reusable harness alongside `ErrorFuture`
+    /// instead of in game-specific code -- see `canvas` module docs.
+    fn render_frame(&mut self, frame: SurfaceTexture, alpha: f64) {
+        let state = &self.state;
+        let scene = &self.scene;
+
+        let updated_character_vtx_data = state.world_state.character_entity.vertex_data(alpha);
+        state.queue.write_buffer(
+            &scene.vertex_buffers.character_entity,
+            0,
+            bytemuck::cast_slice(&updated_character_vtx_data.vertex_data),
+        );
+
         let view = frame
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
 
-        state.device.push_error_scope(wgpu::ErrorFilter::Validation);
         let mut encoder = state
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
@@ -1106,12 +1609,72 @@ impl Game {
             0,
             mem::size_of::<camera::CameraUniform>().try_into().unwrap(),
         );
+        scene
+            .timings
+            .borrow_mut()
+            .begin(&mut encoder, timings::Pass::Geometry);
+        // Render the scene into each cascade's shadow-map layer in turn,
+        // temporarily overwriting just the `light_space_matrix` field of
+        // `light_buf` with that cascade's view-proj before each pass (the
+        // rest of the uniform, including `cascade_view_projs` written by
+        // `to_raw()` above, doesn't change between cascades). `shader.wgsl`
+        // itself only ever samples cascade 0's layer via `light_space_matrix`
+        // today -- per-fragment cascade selection would need a shader change
+        // this snapshot doesn't have -- so cascade 0's matrix is written back
+        // once the loop finishes, leaving the forward pass sampling the
+        // nearest (crispest) cascade.
+        //
+        // Each chunk's full-height world-space AABB, reused across cascades
+        // below to cull against that cascade's light-space frustum before
+        // submitting it to the shadow pass -- mirrors the camera-frustum
+        // cull the forward pass does further down with
+        // `Camera::filter_visible_chunks`.
+        let shadow_chunk_aabbs: Vec<(glam::Vec3, glam::Vec3)> = scene
+            .chunk_order
+            .iter()
+            .map(|&[chunk_x, chunk_z]| {
+                let min = glam::Vec3::new(
+                    (chunk_x * CHUNK_XZ_SIZE) as f32,
+                    0.0,
+                    (chunk_z * CHUNK_XZ_SIZE) as f32,
+                );
+                let max = min
+                    + glam::Vec3::new(
+                        CHUNK_XZ_SIZE as f32,
+                        CHUNK_Y_SIZE as f32,
+                        CHUNK_XZ_SIZE as f32,
+                    );
+                (min, max)
+            })
+            .collect();
+        for (cascade_idx, cascade_view_proj) in
+            state.light_uniform.cascade_view_projs.into_iter().enumerate()
         {
+            let cascade_frustum = frustum::Frustum::from_matrix(cascade_view_proj);
+            let visible_chunk_order: Vec<[usize; 2]> = if ENABLE_FRUSTUM_CULLING {
+                scene
+                    .chunk_order
+                    .iter()
+                    .zip(shadow_chunk_aabbs.iter())
+                    .filter_map(|(&chunk_idx, &(min, max))| {
+                        cascade_frustum.intersects_aabb(min, max).then_some(chunk_idx)
+                    })
+                    .collect()
+            } else {
+                scene.chunk_order.clone()
+            };
+
+            state.queue.write_buffer(
+                &scene.light_buf,
+                light::LIGHT_SPACE_MATRIX_BYTE_OFFSET,
+                bytemuck::cast_slice(&[cascade_view_proj.to_cols_array_2d()]),
+            );
+
             let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: None,
                 color_attachments: &[],
                 depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: &scene.shadow_map_texture.view,
+                    view: &scene.shadow_cascade_layer_views[cascade_idx],
                     depth_ops: Some(wgpu::Operations {
                         load: wgpu::LoadOp::Clear(1.0),
                         store: true,
@@ -1130,8 +1693,12 @@ impl Game {
                 wgpu::IndexFormat::Uint16,
             );
 
-            for data_type in [ChunkDataType::Opaque, ChunkDataType::SemiTransluscent] {
-                for chunk_idx in scene.chunk_order.iter().rev() {
+            for data_type in [
+                ChunkDataType::Opaque,
+                ChunkDataType::BinaryTransparency,
+                ChunkDataType::SemiTransluscent,
+            ] {
+                for chunk_idx in visible_chunk_order.iter().rev() {
                     self.render_chunk(&mut rpass, *chunk_idx, data_type);
                 }
             }
@@ -1145,6 +1712,15 @@ impl Game {
                 rpass.draw_indexed(0..scene.index_counts.character_entity as u32, 0, 0..1);
             }
         }
+        state.queue.write_buffer(
+            &scene.light_buf,
+            light::LIGHT_SPACE_MATRIX_BYTE_OFFSET,
+            bytemuck::cast_slice(&[state.light_uniform.cascade_view_projs[0].to_cols_array_2d()]),
+        );
+        scene
+            .timings
+            .borrow_mut()
+            .end(&mut encoder, timings::Pass::Geometry);
 
         let sky_color = wgpu::Color {
             r: color::srgb_to_rgb(120.0 / 255.0),
@@ -1152,11 +1728,58 @@ impl Game {
             b: color::srgb_to_rgb(255.0 / 255.0),
             a: 1.0,
         };
+        // Cull chunks outside the camera's frustum before the forward pass --
+        // unlike the shadow pass above, this is from the camera's point of
+        // view, so an off-screen chunk here may still need to cast a visible
+        // shadow and must stay in the shadow pass's `scene.chunk_order`.
+        // Runs `Camera::filter_visible_chunks`'s plane/AABB test against each
+        // visible chunk's full-height world-space AABB.
+        let chunk_aabbs: Vec<collision::Aabb3<f32>> = scene
+            .chunk_order
+            .iter()
+            .map(|&[chunk_x, chunk_z]| {
+                let min = cgmath::Point3::new(
+                    (chunk_x * CHUNK_XZ_SIZE) as f32,
+                    0.0,
+                    (chunk_z * CHUNK_XZ_SIZE) as f32,
+                );
+                let max = min
+                    + cgmath::Vector3::new(
+                        CHUNK_XZ_SIZE as f32,
+                        CHUNK_Y_SIZE as f32,
+                        CHUNK_XZ_SIZE as f32,
+                    );
+                collision::Aabb3::new(min, max)
+            })
+            .collect();
+        let visible_chunk_order: Vec<[usize; 2]> = if ENABLE_FRUSTUM_CULLING {
+            state
+                .camera
+                .filter_visible_chunks(&chunk_aabbs)
+                .into_iter()
+                .map(|i| scene.chunk_order[i])
+                .collect()
+        } else {
+            scene.chunk_order.clone()
+        };
+
+        // Minecrust has no dedicated HUD/UI pass yet, so `Pass::Ui` currently
+        // brackets the whole forward pass below. Narrow this once a real UI
+        // overlay pass exists.
+        scene
+            .timings
+            .borrow_mut()
+            .begin(&mut encoder, timings::Pass::Ui);
+        let forward_pass_view = if ENABLE_HDR_TONEMAP {
+            &scene.hdr_texture.view
+        } else {
+            &view
+        };
         {
             let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: None,
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
+                    view: forward_pass_view,
                     resolve_target: None,
                     ops: wgpu::Operations {
                         load: wgpu::LoadOp::Clear(sky_color),
@@ -1184,239 +1807,609 @@ impl Game {
 
             for data_type in [
                 ChunkDataType::Opaque,
+                ChunkDataType::BinaryTransparency,
                 ChunkDataType::Transluscent,
                 ChunkDataType::SemiTransluscent,
             ] {
-                for chunk_idx in scene.chunk_order.iter().rev() {
+                for chunk_idx in visible_chunk_order.iter().rev() {
                     self.render_chunk(&mut rpass, *chunk_idx, data_type);
                 }
             }
 
-            if RENDER_LIGHT_DEBUG_DATA {
-                // Draw light volume wireframe
-                if let Some(ref pipe) = &scene.pipeline_wire_no_instancing {
-                    rpass.set_pipeline(pipe);
-                    rpass.set_vertex_buffer(0, scene.vertex_buffers.light_volume.slice(..));
-                    rpass.set_index_buffer(
-                        scene.index_buffers.light_volume.slice(..),
-                        wgpu::IndexFormat::Uint16,
-                    );
-                    rpass.draw_indexed(0..scene.index_counts.light_volume as u32, 0, 0..1);
-
-                    rpass.set_pipeline(&scene.pipeline);
-                }
-            }
-
-            if RENDER_CHARACTER_ENTITY {
-                if let Some(ref pipe) = &scene.pipeline_solid_color {
-                    rpass.set_pipeline(pipe);
-                    rpass.set_bind_group(0, &scene.texture_bind_group, &[]);
-                    rpass.set_bind_group(1, &scene.camera_bind_group, &[]);
-                    rpass.set_bind_group(2, &scene.light_bind_group, &[]);
-                    rpass.set_vertex_buffer(0, scene.vertex_buffers.character_entity.slice(..));
-                    rpass.set_index_buffer(
-                        scene.index_buffers.character_entity.slice(..),
-                        wgpu::IndexFormat::Uint16,
-                    );
-                    rpass.draw_indexed(0..scene.index_counts.character_entity as u32, 0, 0..1);
-                    rpass.set_pipeline(&scene.pipeline);
-                }
+            self.render_system(&mut rpass);
+            rpass.set_pipeline(&scene.pipeline);
+        }
+        scene.timings.borrow_mut().end(&mut encoder, timings::Pass::Ui);
+
+        // When HDR tonemapping is disabled, the forward pass above already
+        // rendered straight into the swapchain `view`, so there's nothing left
+        // for this pass to do.
+        if ENABLE_HDR_TONEMAP {
+            scene
+                .timings
+                .borrow_mut()
+                .begin(&mut encoder, timings::Pass::Tonemap);
+            {
+                let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: None,
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Load,
+                            store: true,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                });
+                rpass.set_pipeline(&scene.tonemap_pipeline);
+                rpass.set_bind_group(0, &scene.tonemap_bind_group, &[]);
+                // Single fullscreen triangle generated from `vertex_index` in
+                // `tonemap.wgsl`, no vertex buffer.
+                rpass.draw(0..3, 0..1);
             }
+            scene
+                .timings
+                .borrow_mut()
+                .end(&mut encoder, timings::Pass::Tonemap);
         }
 
+        scene.timings.borrow().resolve(&mut encoder);
+        scene.timings.borrow().copy_to_readback(&mut encoder);
+
         state.queue.submit(Some(encoder.finish()));
 
-        // If an error occurs, report it and panic.
-        spawner.spawn_local(ErrorFuture {
-            inner: state.device.pop_error_scope(),
-        });
+        // Reads back this frame's passes: the GPU path blocks on
+        // `device.poll` until the submission above has finished, and the CPU
+        // fallback's `Instant` deltas were finalized by the `end` calls above
+        // (so they time command recording, not GPU execution). Good enough
+        // for a debug overlay, not for tight profiling.
+        scene
+            .last_timings
+            .set(scene.timings.borrow().read_timings(&state.device));
+
+        frame.present();
+    }
+}
+
+impl canvas::Loop for Game {
+    fn update(&mut self, _input: &canvas::Input, action_handler: &input::ActionHandler, fixed_dt: f64) {
+        // Movement and camera look are wired directly off raw `winit` events
+        // in `Canvas::window_event`/`device_event` today (see those for why),
+        // so `_input` isn't consumed here -- it's part of `Loop`'s contract
+        // for implementors that don't have a `Canvas` translating events for
+        // them (e.g. a headless test driving `update`/`render` directly).
+        self.update_tick(action_handler, fixed_dt);
+    }
 
-        return frame;
+    fn render(&mut self, frame: SurfaceTexture, alpha: f64) -> canvas::RenderResult {
+        self.render_frame(frame, alpha);
+        canvas::RenderResult::Continue
     }
 }
 
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
-#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
-pub fn run(width: usize, height: usize) {
-    cfg_if::cfg_if! {
-        if #[cfg(target_arch = "wasm32")] {
-            std::panic::set_hook(Box::new(console_error_panic_hook::hook));
-            console_log::init_with_level(log::Level::Info).expect("Couldn't initialize logger");
-        } else {
-            env_logger::init();
+/// The reusable harness described in `canvas` module docs: owns the
+/// `Window`/wgpu `State` (via `Game`) and drives any `canvas::Loop`
+/// implementor as the `ApplicationHandler` of an `EventLoop` it does not own
+/// (`run()` or the host calling `pump_events` keeps that), translating `winit` events into a
+/// `canvas::Input` snapshot along the way. Replaces the old
+/// `event_loop.run(move |event, _, control_flow| { .. })` closure, which
+/// captured `window`, `game_loop`, `cursor_grabbed`, etc. as locals -- tying
+/// the whole engine to one blocking call, with no way to reuse it for
+/// different game logic or unit-test tick/render in isolation. Those
+/// captures are now fields here, on an `ApplicationHandler`, so a host can
+/// either let `run()` block as before or step the loop itself via
+/// `pump_events`.
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    // Created lazily in `resumed()` rather than eagerly before the loop
+    // starts, since that's the only point winit guarantees a window is
+    // actually safe to create (this matters most on Android, where the
+    // surface can be torn down and recreated across `resumed`/`suspended`).
+    window: Option<winit::window::Window>,
+    game_loop: Option<game_loop::GameLoop<Game>>,
+    curr_modifier_state: winit::event::ModifiersState,
+    cursor_grabbed: bool,
+    action_handler: input::ActionHandler,
+    text_input: text_input::TextInputBuffer,
+    spawner: Spawner,
+    #[cfg(not(target_arch = "wasm32"))]
+    gamepad_backend: Option<gamepad::GamepadBackend>,
+    analog_from_button: analog_from_button::AnalogFromButton,
+
+    // Raw state accumulated between `canvas::Loop::update` calls and folded
+    // into a `canvas::Input` snapshot right before each one -- see that
+    // struct's docs. `keys_held` is level state (cleared only on release);
+    // the rest are edge/one-shot and drained once read.
+    keys_held: HashSet<VirtualKeyCode>,
+    mouse_delta: (f64, f64),
+    clicks: Vec<MouseButton>,
+    resized_to: Option<winit::dpi::PhysicalSize<u32>>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize) -> Self {
+        Canvas {
+            width,
+            height,
+            window: None,
+            game_loop: None,
+            curr_modifier_state: winit::event::ModifiersState::empty(),
+            cursor_grabbed: false,
+            action_handler: input::ActionHandler::default(),
+            text_input: text_input::TextInputBuffer::default(),
+            spawner: Spawner::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            gamepad_backend: gamepad::GamepadBackend::new(),
+            analog_from_button: analog_from_button::AnalogFromButton::new(),
+
+            keys_held: HashSet::new(),
+            mouse_delta: (0.0, 0.0),
+            clicks: Vec::new(),
+            resized_to: None,
         }
     }
 
-    let event_loop = EventLoopBuilder::<DomControlsUserEvent>::with_user_event().build();
-    unsafe {
-        dom_controls::set_global_event_loop_proxy(&event_loop);
-    }
+    /// Routes a `KeyboardInput`/`ReceivedCharacter` event into `text_input`
+    /// while it's active, popping the overlay layout once it stops being
+    /// active (on Enter-submit or Escape-cancel) and dispatching any
+    /// submitted line as a command or chat message.
+    fn dispatch_text_input_event(
+        &mut self,
+        game_loop: &mut game_loop::GameLoop<Game>,
+        event: &WindowEvent,
+    ) {
+        let submitted = self.text_input.process_window_event(event);
 
-    let window = winit::window::WindowBuilder::new()
-        .with_title("Minecrust")
-        .with_inner_size(winit::dpi::LogicalSize {
-            width: width as i32,
-            height: height as i32,
-        })
-        .build(&event_loop)
-        .unwrap();
-
-    #[cfg(target_arch = "wasm32")]
-    {
-        use winit::platform::web::WindowExtWebSys;
-        web_sys::window()
-            .and_then(|win| win.document())
-            .and_then(|doc| {
-                let dst = doc.get_element_by_id("wasm-container")?;
-                let canvas = web_sys::Element::from(window.canvas());
-                dst.append_child(&canvas).ok()?;
-                Some(())
-            })
-            .expect("Couldn't append canvas to document body.");
+        if !self.text_input.is_active() {
+            self.action_handler.pop_overlay_layout();
+        }
+
+        let Some(line) = submitted else {
+            return;
+        };
+        let world_state = &mut game_loop.game.state.world_state;
+        match text_input::parse_line(&line) {
+            text_input::ChatMessage::Command { name, args } => match name.as_str() {
+                "fly" => world_state.is_flying = !world_state.is_flying,
+                "give" => {
+                    if let Some(block_type) = world::BlockType::from_name(args.trim()) {
+                        world_state.place_block_type = block_type;
+                    } else {
+                        log::warn!("/give: unknown block type {:?}", args.trim());
+                    }
+                }
+                "tp" => {
+                    let coords: Vec<f32> = args
+                        .split_whitespace()
+                        .filter_map(|part| part.parse().ok())
+                        .collect();
+                    if let [x, y, z] = coords[..] {
+                        let position = glam::Vec3::new(x, y, z);
+                        world_state.character_entity.dynamics.position = position;
+                        world_state.character_entity.dynamics.prev_position = position;
+                    } else {
+                        log::warn!("/tp: expected 3 coordinates, got {:?}", args);
+                    }
+                }
+                _ => log::warn!("unknown command: /{} {}", name, args),
+            },
+            // No networking in this build -- broadcasting a chat message is a
+            // log line rather than a send.
+            text_input::ChatMessage::Chat(text) => log::info!("chat: {}", text),
+        }
     }
+}
 
-    let mut game = block_on(Game::new(&window));
+impl winit::application::ApplicationHandler<DomControlsUserEvent> for Canvas {
+    fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+        // Android can resume a suspended app into an already-initialized
+        // `Canvas`; everything else only ever resumes once.
+        if self.window.is_some() {
+            return;
+        }
 
-    let mut curr_modifier_state: winit::event::ModifiersState =
-        winit::event::ModifiersState::empty();
-    let mut cursor_grabbed = false;
+        let window = event_loop
+            .create_window(
+                winit::window::WindowAttributes::default()
+                    .with_title("Minecrust")
+                    .with_inner_size(winit::dpi::LogicalSize {
+                        width: self.width as i32,
+                        height: self.height as i32,
+                    }),
+            )
+            .expect("Failed to create window");
 
-    let mut left_mouse_clicked = false;
-    let mut right_mouse_clicked = false;
+        #[cfg(target_arch = "wasm32")]
+        {
+            use winit::platform::web::WindowExtWebSys;
+            web_sys::window()
+                .and_then(|win| win.document())
+                .and_then(|doc| {
+                    let dst = doc.get_element_by_id("wasm-container")?;
+                    let canvas = web_sys::Element::from(window.canvas());
+                    dst.append_child(&canvas).ok()?;
+                    Some(())
+                })
+                .expect("Couldn't append canvas to document body.");
+
+            // Remove Loader element from DOM
+            web_sys::window()
+                .and_then(|win| win.document())
+                .and_then(|doc| {
+                    let loader_elem = doc.get_element_by_id("loader")?;
+                    loader_elem.remove();
+                    Some(())
+                });
+        }
 
-    // Remove Loader element from DOM
-    #[cfg(target_arch = "wasm32")]
-    {
-        web_sys::window()
-            .and_then(|win| win.document())
-            .and_then(|doc| {
-                let loader_elem = doc.get_element_by_id("loader")?;
-                loader_elem.remove();
-                Some(())
-            });
+        // `GameLoop` owns `Game` so its `update`/`render` closures reach game
+        // state through `g.game` -- see `game_loop::GameLoop`'s docs. Decouples
+        // simulation speed from however fast `RedrawRequested` actually fires.
+        self.game_loop = Some(game_loop::GameLoop::new(
+            block_on(Game::new(&window)),
+            FIXED_UPDATES_PER_SECOND,
+            MAX_FRAME_TIME,
+        ));
+        self.window = Some(window);
     }
 
-    let spawner = Spawner::new();
-    event_loop.run(move |event, _, control_flow| {
-        *control_flow = ControlFlow::Poll;
+    fn window_event(
+        &mut self,
+        event_loop: &winit::event_loop::ActiveEventLoop,
+        window_id: winit::window::WindowId,
+        event: WindowEvent,
+    ) {
+        let (Some(window), Some(game_loop)) = (self.window.as_ref(), self.game_loop.as_mut())
+        else {
+            return;
+        };
 
         match event {
-            Event::WindowEvent { event, window_id } => match event {
-                WindowEvent::CloseRequested => {
-                    if window_id == window.id() {
-                        *control_flow = ControlFlow::Exit;
-                    }
-                }
-                WindowEvent::ModifiersChanged(modifiers) => {
-                    curr_modifier_state = modifiers;
+            WindowEvent::CloseRequested => {
+                if window_id == window.id() {
+                    event_loop.exit();
                 }
-                WindowEvent::KeyboardInput { input, .. } => {
-                    match (input.virtual_keycode, input.state) {
-                        (Some(VirtualKeyCode::W), ElementState::Pressed) => {
-                            if curr_modifier_state.logo() {
-                                *control_flow = ControlFlow::Exit;
-                                return;
-                            }
-                            game.state.camera_controller.process_window_event(&event);
-                        }
-                        (Some(VirtualKeyCode::Escape), ElementState::Pressed) => {
-                            window.set_cursor_visible(true);
-                            window
-                                .set_cursor_grab(winit::window::CursorGrabMode::None)
-                                .expect("Failed to release curosr");
-                            cursor_grabbed = false;
+            }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.curr_modifier_state = modifiers;
+            }
+            WindowEvent::Focused(false) => {
+                // The matching release events for whatever was held when focus was
+                // lost will never arrive, so without this the player keeps walking
+                // or the jump stays latched once focus returns (native and wasm
+                // both steal focus mid-press under normal use, e.g. alt-tab or a
+                // browser permission prompt).
+                self.action_handler.release_all();
+                game_loop.game.state.camera_controller.clear_stuck_input();
+                game_loop.game.state.world_state.clear_stuck_input();
+            }
+            WindowEvent::KeyboardInput { .. } if self.text_input.is_active() => {
+                self.dispatch_text_input_event(game_loop, &event);
+            }
+            WindowEvent::KeyboardInput { input, .. } => {
+                if let Some(keycode) = input.virtual_keycode {
+                    self.analog_from_button
+                        .handle_key(keycode, input.state == ElementState::Pressed);
+
+                    // Feeds `canvas::Input::keys_held` for `canvas::Loop::update`.
+                    match input.state {
+                        ElementState::Pressed => {
+                            self.keys_held.insert(keycode);
                         }
-                        _ => {
-                            game.state.camera_controller.process_window_event(&event);
+                        ElementState::Released => {
+                            self.keys_held.remove(&keycode);
                         }
                     }
                 }
-                WindowEvent::MouseInput { state, button, .. } => match (state, button) {
-                    (ElementState::Pressed, MouseButton::Left) => {
-                        if !cursor_grabbed {
-                            window
-                                .set_cursor_grab(winit::window::CursorGrabMode::Locked)
-                                .expect("Failed to grab curosr");
-                            window.set_cursor_visible(false);
-                            cursor_grabbed = true;
-                        } else {
-                            left_mouse_clicked = true;
+                self.action_handler.process_window_event(&event);
+
+                match (input.virtual_keycode, input.state) {
+                    (Some(VirtualKeyCode::W), ElementState::Pressed) => {
+                        if self.curr_modifier_state.logo() {
+                            event_loop.exit();
+                            return;
                         }
+                        game_loop.game.state.camera_controller.process_window_event(&event);
                     }
-                    (ElementState::Pressed, MouseButton::Right) => {
-                        right_mouse_clicked = true;
+                    (Some(VirtualKeyCode::Escape), ElementState::Pressed) => {
+                        window.set_cursor_visible(true);
+                        window
+                            .set_cursor_grab(winit::window::CursorGrabMode::None)
+                            .expect("Failed to release curosr");
+                        self.cursor_grabbed = false;
                     }
-                    _ => (),
-                },
-
-                WindowEvent::Resized(physical_size) => {
-                    game.resize(physical_size);
-                }
-                WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
-                    game.resize(*new_inner_size);
-                }
-                _ => (),
-            },
-
-            Event::DeviceEvent { event, .. } => match event {
-                DeviceEvent::MouseMotion { .. } => {
-                    if cursor_grabbed {
-                        game.state.camera_controller.process_device_event(&event);
+                    (Some(VirtualKeyCode::C), ElementState::Pressed) => {
+                        game_loop.game.state.camera_mode = match game_loop.game.state.camera_mode {
+                            camera::CameraMode::FlyCam => camera::CameraMode::Orbit,
+                            camera::CameraMode::Orbit => camera::CameraMode::FlyCam,
+                        };
+                    }
+                    (Some(VirtualKeyCode::T), ElementState::Pressed) => {
+                        self.text_input.activate();
+                        self.action_handler.push_overlay_layout(input::TEXT_INPUT_OVERLAY);
+                    }
+                    _ => {
+                        game_loop
+                            .game
+                            .state
+                            .camera_controller
+                            .process_window_event(&event);
                     }
                 }
-                _ => (),
-            },
-
-            Event::UserEvent(event) => match event {
-                DomControlsUserEvent::AButtonPressed => {
-                    left_mouse_clicked = true;
+            }
+            WindowEvent::ReceivedCharacter(_) if self.text_input.is_active() => {
+                self.dispatch_text_input_event(game_loop, &event);
+            }
+            WindowEvent::MouseInput { state, button, .. }
+                if game_loop.game.state.camera_mode == camera::CameraMode::Orbit =>
+            {
+                if state == ElementState::Pressed {
+                    self.clicks.push(button);
                 }
-                DomControlsUserEvent::BButtonPressed => {
-                    right_mouse_clicked = true;
+                game_loop
+                    .game
+                    .state
+                    .orbit_camera_controller
+                    .process_window_event(&event);
+                self.action_handler.process_window_event(&event);
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                if state == ElementState::Pressed {
+                    self.clicks.push(button);
                 }
-                DomControlsUserEvent::WindowResized { size } => {
-                    log::info!("Web window resized: {:?}", size);
-
-                    game.resize(size.to_physical(window.scale_factor()));
-                    #[cfg(target_arch = "wasm32")]
-                    {
-                        // Web <canvas> element must be resized explicitly, can't use CSS rules
-                        window.set_inner_size(winit::dpi::PhysicalSize::new(
-                            game.state.surface_config.width as i32,
-                            game.state.surface_config.height as i32,
-                        ));
+                match (state, button) {
+                    (ElementState::Pressed, MouseButton::Left) if !self.cursor_grabbed => {
+                        window
+                            .set_cursor_grab(winit::window::CursorGrabMode::Locked)
+                            .expect("Failed to grab curosr");
+                        window.set_cursor_visible(false);
+                        self.cursor_grabbed = true;
+                    }
+                    _ => {
+                        self.action_handler.process_window_event(&event);
                     }
                 }
-                _ => {
-                    game.state
-                        .camera_controller
-                        .process_web_dom_button_event(&event);
+            }
+            WindowEvent::MouseWheel { .. }
+                if game_loop.game.state.camera_mode == camera::CameraMode::Orbit =>
+            {
+                game_loop
+                    .game
+                    .state
+                    .orbit_camera_controller
+                    .process_window_event(&event);
+            }
+
+            WindowEvent::Resized(physical_size) => {
+                game_loop.game.resize(physical_size);
+                self.resized_to = Some(physical_size);
+            }
+            WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                game_loop.game.resize(*new_inner_size);
+                self.resized_to = Some(*new_inner_size);
+            }
+
+            WindowEvent::RedrawRequested => {
+                let keep_running = game_loop.next_frame(
+                    |g| {
+                        let fixed_dt = g.fixed_time_step();
+                        // Drains the edge-triggered fields so each snapshot
+                        // only reflects what happened since the last update
+                        // tick; `keys_held` is level state and stays as-is.
+                        let input = canvas::Input {
+                            keys_held: self.keys_held.clone(),
+                            mouse_delta: std::mem::take(&mut self.mouse_delta),
+                            clicks: std::mem::take(&mut self.clicks),
+                            resized_to: self.resized_to.take(),
+                        };
+                        g.game.update(&input, &self.action_handler, fixed_dt);
+                    },
+                    |g| {
+                        let alpha = g.blending_factor();
+                        let frame = g.game.state.acquire_frame();
+
+                        // Lives here rather than in `Game::render` so the
+                        // panic-on-GPU-error plumbing stays part of the
+                        // reusable harness -- see `canvas` module docs.
+                        g.game.state.device.push_error_scope(wgpu::ErrorFilter::Validation);
+                        let render_result = g.game.render(frame, alpha);
+                        self.spawner.spawn_local(ErrorFuture {
+                            inner: g.game.state.device.pop_error_scope(),
+                        });
+
+                        if let canvas::RenderResult::Exit = render_result {
+                            g.exit_next_iteration = true;
+                        }
+                    },
+                );
+                if !keep_running {
+                    event_loop.exit();
                 }
-            },
+                self.action_handler.clear_frame_state();
 
-            Event::RedrawRequested(_) => {
-                game.update_tick(&mut left_mouse_clicked, &mut right_mouse_clicked);
+                game_loop.game.state.camera_controller.reset_mouse_delta();
+                game_loop.game.state.orbit_camera_controller.reset_mouse_delta();
+            }
 
-                let frame = game.render_frame(&spawner);
-                frame.present();
+            _ => (),
+        }
+    }
 
-                game.state.camera_controller.reset_mouse_delta();
+    fn device_event(
+        &mut self,
+        _event_loop: &winit::event_loop::ActiveEventLoop,
+        _device_id: winit::event::DeviceId,
+        event: DeviceEvent,
+    ) {
+        let Some(game_loop) = self.game_loop.as_mut() else {
+            return;
+        };
+
+        match event {
+            DeviceEvent::MouseMotion { delta } => {
+                self.mouse_delta.0 += delta.0;
+                self.mouse_delta.1 += delta.1;
+
+                match game_loop.game.state.camera_mode {
+                    camera::CameraMode::FlyCam => {
+                        if self.cursor_grabbed {
+                            game_loop
+                                .game
+                                .state
+                                .camera_controller
+                                .process_device_event(&event);
+                        }
+                    }
+                    camera::CameraMode::Orbit => {
+                        game_loop
+                            .game
+                            .state
+                            .orbit_camera_controller
+                            .process_device_event(&event);
+                    }
+                }
             }
+            _ => (),
+        }
+    }
 
-            Event::MainEventsCleared => {
-                // RedrawRequested will only trigger once, unless we manually
-                // request it.
-                window.request_redraw();
+    fn user_event(
+        &mut self,
+        _event_loop: &winit::event_loop::ActiveEventLoop,
+        event: DomControlsUserEvent,
+    ) {
+        let (Some(window), Some(game_loop)) = (self.window.as_ref(), self.game_loop.as_mut())
+        else {
+            return;
+        };
 
-                #[cfg(not(target_arch = "wasm32"))]
-                spawner.run_until_stalled();
+        match event {
+            DomControlsUserEvent::AButtonPressed => {
+                self.action_handler
+                    .set_source_held(input::InputSource::MouseButton(MouseButton::Left), true);
             }
+            DomControlsUserEvent::AButtonReleased => {
+                self.action_handler
+                    .set_source_held(input::InputSource::MouseButton(MouseButton::Left), false);
+            }
+            DomControlsUserEvent::BButtonPressed => {
+                self.action_handler
+                    .set_source_held(input::InputSource::MouseButton(MouseButton::Right), true);
+            }
+            DomControlsUserEvent::BButtonReleased => {
+                self.action_handler
+                    .set_source_held(input::InputSource::MouseButton(MouseButton::Right), false);
+            }
+            DomControlsUserEvent::TranslationJoystickMoved { vector } => {
+                // Also feeds `STRAFE_LEFT_RIGHT`/`MOVE_FORWARD_BACKWARD`-shaped
+                // analog sources for any future action bound to them; the
+                // camera controller below is still what actually drives
+                // movement from this joystick today.
+                self.action_handler
+                    .set_analog_value(input::AnalogSource::TranslationJoystickX, vector.0 as f32);
+                self.action_handler
+                    .set_analog_value(input::AnalogSource::TranslationJoystickY, vector.1 as f32);
+                game_loop
+                    .game
+                    .state
+                    .camera_controller
+                    .process_web_dom_button_event(&event);
+            }
+            DomControlsUserEvent::PitchYawJoystickMoved { vector } => {
+                self.action_handler
+                    .set_analog_value(input::AnalogSource::PitchYawJoystickX, vector.0 as f32);
+                self.action_handler
+                    .set_analog_value(input::AnalogSource::PitchYawJoystickY, vector.1 as f32);
+                game_loop
+                    .game
+                    .state
+                    .camera_controller
+                    .process_web_dom_button_event(&event);
+            }
+            DomControlsUserEvent::WindowResized { size } => {
+                log::info!("Web window resized: {:?}", size);
+
+                game_loop.game.resize(size.to_physical(window.scale_factor()));
+                #[cfg(target_arch = "wasm32")]
+                {
+                    // Web <canvas> element must be resized explicitly, can't use CSS rules
+                    window.set_inner_size(winit::dpi::PhysicalSize::new(
+                        game_loop.game.state.surface_config.width as i32,
+                        game_loop.game.state.surface_config.height as i32,
+                    ));
+                }
+            }
+            _ => {
+                game_loop
+                    .game
+                    .state
+                    .camera_controller
+                    .process_web_dom_button_event(&event);
+            }
+        }
+    }
 
-            _ => (),
+    fn about_to_wait(&mut self, _event_loop: &winit::event_loop::ActiveEventLoop) {
+        let Some(window) = self.window.as_ref() else {
+            return;
+        };
+
+        // `RedrawRequested` will only trigger once, unless we manually
+        // request it.
+        window.request_redraw();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        self.spawner.run_until_stalled();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(gamepad_backend) = self.gamepad_backend.as_mut() {
+            gamepad_backend.poll();
+        }
+
+        self.analog_from_button.tick();
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn run(width: usize, height: usize) {
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            std::panic::set_hook(Box::new(console_error_panic_hook::hook));
+            console_log::init_with_level(log::Level::Info).expect("Couldn't initialize logger");
+        } else {
+            env_logger::init();
         }
-    });
+    }
+
+    let event_loop = EventLoopBuilder::<DomControlsUserEvent>::with_user_event()
+        .build()
+        .expect("Failed to build event loop");
+    unsafe {
+        dom_controls::set_global_event_loop_proxy(&event_loop);
+    }
+    event_loop.set_control_flow(ControlFlow::Poll);
+
+    let mut canvas = Canvas::new(width, height);
+    event_loop.run_app(&mut canvas).expect("Event loop exited with an error");
+}
+
+/// Non-blocking alternative to `run()` for hosts (editors, test harnesses,
+/// anything with its own loop) that want to own the driving loop themselves
+/// instead of handing control to winit forever. Not available on `wasm32`,
+/// where the browser owns the loop and we can only ever run via `run()`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn pump_events(
+    event_loop: &mut winit::event_loop::EventLoop<DomControlsUserEvent>,
+    canvas: &mut Canvas,
+    timeout: Option<std::time::Duration>,
+) -> winit::platform::pump_events::PumpStatus {
+    use winit::platform::pump_events::EventLoopExtPumpEvents;
+    event_loop.pump_app_events(timeout, canvas)
 }
 
 /// A wrapper for `pop_error_scope` futures that panics if an error occurs.
@@ -1,7 +1,97 @@
 use crate::camera::Camera;
+use crate::frustum::Frustum;
 use crate::vertex::{CuboidCoords, QuadListRenderData, Vertex};
+use cgmath::SquareMatrix;
 use glam::{Mat4, Vec3};
 
+/// Number of cascaded shadow map splits. 3 is the usual sweet spot: enough to
+/// keep the near split crisp without the per-cascade render-pass cost of 4+.
+pub const NUM_CASCADES: usize = 3;
+
+/// Byte offset (and size) of `light_space_matrix` within `LightUniformRaw`,
+/// for `Game::render_frame`'s per-cascade shadow passes, which need to
+/// overwrite just that field between cascades rather than the whole uniform.
+/// Must track `LightUniformRaw`'s field layout.
+pub const LIGHT_SPACE_MATRIX_BYTE_OFFSET: u64 = 32;
+pub const LIGHT_SPACE_MATRIX_BYTE_SIZE: u64 = 64;
+
+/// Upper bound on simultaneous point lights (torches, glowstone, ...),
+/// fixed so `PointLightRaw`s fit in a plain uniform array instead of a
+/// storage buffer -- this engine doesn't use storage buffers anywhere else.
+pub const MAX_POINT_LIGHTS: usize = 32;
+
+/// A local emissive light source -- a placed torch or glowstone block, for
+/// example -- as opposed to the single directional sun. `WorldState` keeps
+/// one of these per emissive block and `LightUniform::point_lights` mirrors
+/// that list each frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointLight {
+    pub position: Vec3,
+    pub color: Vec3,
+    /// Distance at which `shader.wgsl`'s falloff curve reaches zero
+    /// attenuation.
+    pub radius: f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct PointLightRaw {
+    position: [f32; 3],
+    radius: f32,
+    color: [f32; 3],
+    _padding: f32,
+}
+
+impl PointLight {
+    fn to_raw(self) -> PointLightRaw {
+        PointLightRaw {
+            position: self.position.into(),
+            radius: self.radius,
+            color: self.color.into(),
+            _padding: 0.0,
+        }
+    }
+}
+
+/// How the main shader samples `shadow_map_texture` when testing a fragment
+/// against the light's depth buffer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShadowSettings {
+    /// Skip the shadow test entirely; every fragment is lit.
+    Disabled,
+    /// A single tap using the sampler's hardware 2x2 PCF (a `Comparison`
+    /// sampler with `compare: Some(LessEqual)`), cheap but hard edges at
+    /// shadow-map texel granularity.
+    HardwareComparison,
+    /// Average `kernel_radius`-texel-wide NxN taps around the projected UV
+    /// (an (2*kernel_radius+1)^2 grid) to soften edges, each tap using the
+    /// same hardware comparison sampler.
+    Pcf { kernel_radius: i32 },
+    /// Percentage-closer soft shadows: a blocker-search pass over a region
+    /// scaled by `light_size` estimates the average occluder depth, which
+    /// gives a per-fragment penumbra width (`penumbra_scale` tunes how
+    /// aggressively that width grows with caster distance), and a final PCF
+    /// pass of `sample_count` taps over a disk of that width softens the
+    /// edge -- contact shadows stay sharp, shadows far from their caster
+    /// don't.
+    ///
+    /// NOTE: `shader.wgsl`/`shadow_map.wgsl` (which would read `shadow_mode
+    /// == 3` and do the blocker search + variable-radius PCF) aren't present
+    /// in this checkout, so only the uniform plumbing for this variant lands
+    /// here -- see `to_raw`.
+    Pcss {
+        light_size: f32,
+        penumbra_scale: f32,
+        sample_count: u32,
+    },
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        ShadowSettings::Pcf { kernel_radius: 1 }
+    }
+}
+
 pub struct LightUniform {
     pub position: Vec3,
     pub color: Vec3,
@@ -12,6 +102,34 @@ pub struct LightUniform {
     pub sunlight_ortho_proj_coords: CuboidCoords,
     pub sunlight_ortho_proj: glam::Mat4,
     pub shadow_map_pixel_size: [u32; 2],
+    pub shadow_settings: ShadowSettings,
+    /// Depth bias applied before the shadow comparison, to push sampled
+    /// depth back along the light direction and prevent shadow acne on
+    /// front-facing surfaces. Tune per-scene; too small reintroduces acne,
+    /// too large causes peter-panning (shadows detaching from casters).
+    pub shadow_bias: f32,
+    /// Multiplies the HDR color before the ACES filmic curve in the tonemap
+    /// pass, so sunlight intensity can be pushed above 1.0 without clipping
+    /// at the albedo stage.
+    pub exposure: f32,
+
+    /// Light space view-proj matrix for each cascade, near-to-far, computed
+    /// by `update_cascades`. Each one is fit to a bounding sphere around its
+    /// slice of the camera frustum and texel-snapped, so it stays tight
+    /// (crisp near shadows) without swimming as the camera turns.
+    pub cascade_view_projs: [Mat4; NUM_CASCADES],
+    /// View-space depth of the far edge of each cascade, near-to-far: a
+    /// fragment at view-space depth `d` falls in the first cascade whose
+    /// split is greater than `d`.
+    pub cascade_split_depths: [f32; NUM_CASCADES],
+    /// Toggles cascaded shadow maps vs. the legacy single 125-unit ortho map
+    /// (`sunlight_ortho_proj`), so the two can be compared directly.
+    pub use_cascaded_shadows: bool,
+
+    /// Dynamic local lights (torches, glowstone, ...), mirrored each frame
+    /// from `WorldState`'s emissive blocks. Truncated to `MAX_POINT_LIGHTS`
+    /// in `to_raw` if the world ever has more than that placed at once.
+    pub point_lights: Vec<PointLight>,
 }
 
 #[repr(C)]
@@ -20,8 +138,31 @@ pub struct LightUniformRaw {
     position: [f32; 3],
     _padding: u32,
     color: [f32; 3],
-    _padding2: u32,
+    shadow_bias: f32,
     light_space_matrix: [[f32; 4]; 4],
+    exposure: f32,
+    _padding2: [u32; 3],
+    cascade_view_projs: [[[f32; 4]; 4]; NUM_CASCADES],
+    // Only the first `NUM_CASCADES` lanes are used; padded to a full vec4
+    // since WGSL array stride is 16 bytes for scalars.
+    cascade_split_depths: [f32; 4],
+    use_cascaded_shadows: u32,
+    // 0 = `ShadowSettings::Disabled`, 1 = `HardwareComparison`, 2 = `Pcf`,
+    // 3 = `Pcss`;
+    // see `ShadowSettings` for what the shader should do for each.
+    shadow_mode: u32,
+    shadow_pcf_kernel_radius: i32,
+    // `1.0 / shadow_map_pixel_size`, so the shader can derive per-tap UV
+    // offsets for `ShadowSettings::Pcf` without a separate uniform.
+    shadow_map_texel_size: [f32; 2],
+    // The following three fields are only meaningful when `shadow_mode ==
+    // 3` (`ShadowSettings::Pcss`); see that variant's doc comment.
+    shadow_light_size: f32,
+    shadow_penumbra_scale: f32,
+    shadow_sample_count: u32,
+    num_point_lights: u32,
+    _padding4: [u32; 3],
+    point_lights: [PointLightRaw; MAX_POINT_LIGHTS],
 }
 
 impl LightUniform {
@@ -51,6 +192,15 @@ impl LightUniform {
             sunlight_ortho_proj_coords,
             sunlight_ortho_proj,
             shadow_map_pixel_size,
+            shadow_settings: ShadowSettings::default(),
+            shadow_bias: 0.005,
+            exposure: 1.0,
+
+            cascade_view_projs: [glam::Mat4::IDENTITY; NUM_CASCADES],
+            cascade_split_depths: [0.0; NUM_CASCADES],
+            use_cascaded_shadows: true,
+
+            point_lights: vec![],
         }
     }
 
@@ -62,17 +212,185 @@ impl LightUniform {
         )
     }
 
+    /// The legacy single shadow map's light-space frustum, i.e. the view
+    /// volume that `sunlight_ortho_proj * get_light_view_proj()` projects
+    /// into. Chunk AABBs outside this frustum can't land in the shadow map
+    /// and so can be skipped in the shadow pass; see
+    /// `Frustum::intersects_aabb`. Cascaded shadow maps cull against
+    /// `Frustum::from_matrix(cascade_view_projs[i])` directly instead, since
+    /// each cascade's `update_cascades`-fit volume is independent of this
+    /// one (different center, different extent -- the far cascade in
+    /// particular is routinely larger). Don't AND this frustum into the
+    /// per-cascade cull as a "coarse pre-filter": it isn't a superset of the
+    /// cascades' volumes, so doing that drops valid shadow casters instead
+    /// of just skipping redundant work.
+    pub fn light_frustum(&self) -> Frustum {
+        Frustum::from_matrix(self.sunlight_ortho_proj * self.get_light_view_proj())
+    }
+
     pub fn to_raw(&self) -> LightUniformRaw {
         let light_space_matrix =
             (self.sunlight_ortho_proj * self.get_light_view_proj()).to_cols_array_2d();
 
+        let mut cascade_split_depths = [0.0; 4];
+        cascade_split_depths[..NUM_CASCADES].copy_from_slice(&self.cascade_split_depths);
+
+        let (shadow_mode, shadow_pcf_kernel_radius, shadow_light_size, shadow_penumbra_scale, shadow_sample_count) =
+            match self.shadow_settings {
+                ShadowSettings::Disabled => (0, 0, 0.0, 0.0, 0),
+                ShadowSettings::HardwareComparison => (1, 0, 0.0, 0.0, 0),
+                ShadowSettings::Pcf { kernel_radius } => (2, kernel_radius, 0.0, 0.0, 0),
+                ShadowSettings::Pcss { light_size, penumbra_scale, sample_count } => {
+                    (3, 0, light_size, penumbra_scale, sample_count)
+                }
+            };
+        let shadow_map_texel_size = [
+            1.0 / self.shadow_map_pixel_size[0] as f32,
+            1.0 / self.shadow_map_pixel_size[1] as f32,
+        ];
+
+        if self.point_lights.len() > MAX_POINT_LIGHTS {
+            log::warn!(
+                "{} point lights placed, only the first {MAX_POINT_LIGHTS} will render",
+                self.point_lights.len()
+            );
+        }
+        let mut point_lights = [PointLight {
+            position: Vec3::ZERO,
+            color: Vec3::ZERO,
+            radius: 0.0,
+        }
+        .to_raw(); MAX_POINT_LIGHTS];
+        let num_point_lights = self.point_lights.len().min(MAX_POINT_LIGHTS);
+        for (raw, point_light) in point_lights.iter_mut().zip(self.point_lights.iter()) {
+            *raw = point_light.to_raw();
+        }
+
         LightUniformRaw {
             position: self.position.into(),
             _padding: 0,
             color: self.color.into(),
-            _padding2: 0,
+            shadow_bias: self.shadow_bias,
             light_space_matrix,
+            exposure: self.exposure,
+            _padding2: [0; 3],
+            cascade_view_projs: self
+                .cascade_view_projs
+                .map(|view_proj| view_proj.to_cols_array_2d()),
+            cascade_split_depths,
+            use_cascaded_shadows: self.use_cascaded_shadows as u32,
+            shadow_mode,
+            shadow_pcf_kernel_radius,
+            shadow_map_texel_size,
+            shadow_light_size,
+            shadow_penumbra_scale,
+            shadow_sample_count,
+            num_point_lights: num_point_lights as u32,
+            _padding4: [0; 3],
+            point_lights,
+        }
+    }
+
+    /// Recomputes `cascade_view_projs` and `cascade_split_depths` for the
+    /// camera's current frustum. Call once per frame alongside
+    /// `update_light_space_proj`.
+    ///
+    /// Each cascade's view-proj is fit to a world-space bounding sphere
+    /// around the camera frustum slice `[split_near, split_far]`, rather than
+    /// a tight AABB, so the ortho extents stay constant as the camera
+    /// rotates -- an AABB fit would resize (and therefore shimmer) every
+    /// frame the view direction changes. The light-space origin is then
+    /// snapped to whole shadow-map texels, which stops the remaining
+    /// sub-texel swimming as the camera translates.
+    pub fn update_cascades(&mut self, camera: &Camera) {
+        let cascade_splits = Self::compute_cascade_splits(camera.znear, camera.zfar);
+
+        let sun_forward = (self.sun_target - self.sun_position).normalize();
+        let shadow_map_size = self.shadow_map_pixel_size[0].max(self.shadow_map_pixel_size[1]) as f32;
+
+        let mut split_near = camera.znear;
+        for (cascade_idx, &split_far) in cascade_splits.iter().enumerate() {
+            let corners = Self::frustum_corners_world_space(camera, split_near, split_far);
+
+            let center = corners.iter().fold(Vec3::ZERO, |acc, &c| acc + c) / corners.len() as f32;
+            let radius = corners
+                .iter()
+                .fold(0.0_f32, |max_dist, &c| max_dist.max((c - center).length()));
+
+            let light_view = Mat4::look_at_rh(center - sun_forward * radius * 2.0, center, Vec3::Y);
+
+            // Snap the world origin's light-space position to whole shadow-map
+            // texels so that sub-texel translation of `center` (which happens
+            // every frame as the camera moves) doesn't change which texel each
+            // world point rounds to -- that change is what causes shadow edges
+            // to swim.
+            let texels_per_unit = shadow_map_size / (radius * 2.0);
+            let light_space_origin = light_view.transform_point3(Vec3::ZERO);
+            let snapped_origin = (light_space_origin * texels_per_unit).round() / texels_per_unit;
+            let light_view = Mat4::from_translation(snapped_origin - light_space_origin) * light_view;
+
+            // The eye sits `2 * radius` behind `center`, so the sphere spans
+            // view-space depths `radius..3 * radius`; `0..4 * radius` covers it
+            // plus casters between the eye and the sphere.
+            let light_proj =
+                Mat4::orthographic_rh(-radius, radius, -radius, radius, 0.0, radius * 4.0);
+
+            self.cascade_view_projs[cascade_idx] = light_proj * light_view;
+            self.cascade_split_depths[cascade_idx] = split_far;
+
+            split_near = split_far;
+        }
+    }
+
+    /// Splits `[near, far]` into `NUM_CASCADES` view-space depths, blending
+    /// a logarithmic split scheme (keeps the near cascade, where aliasing is
+    /// most visible, small) with a uniform one (keeps the far cascade from
+    /// shrinking to nothing), per the practical-split-scheme formula common
+    /// in CSM implementations.
+    fn compute_cascade_splits(near: f32, far: f32) -> [f32; NUM_CASCADES] {
+        const LAMBDA: f32 = 0.5;
+        let mut splits = [0.0; NUM_CASCADES];
+        for (i, split) in splits.iter_mut().enumerate() {
+            let p = (i + 1) as f32 / NUM_CASCADES as f32;
+            let log_split = near * (far / near).powf(p);
+            let uniform_split = near + (far - near) * p;
+            *split = LAMBDA * log_split + (1.0 - LAMBDA) * uniform_split;
+        }
+        splits
+    }
+
+    /// Unprojects the 8 corners of the camera's frustum slice between
+    /// `split_near` and `split_far` (in view space) into world space, via
+    /// `camera.inverse_view_proj` rather than deriving and inverting a
+    /// fresh view-proj matrix per cascade.
+    ///
+    /// `split_near`/`split_far` only cover part of `camera`'s full
+    /// `znear..zfar` range, so rather than building a split-specific `proj`
+    /// (which `camera.inverse_view_proj` wasn't built from), each depth is
+    /// mapped to the NDC z that `camera`'s actual projection would produce
+    /// for a point that far down the view axis, and that z is used in place
+    /// of the usual +/-1 cube corners.
+    fn frustum_corners_world_space(camera: &Camera, split_near: f32, split_far: f32) -> [Vec3; 8] {
+        let proj = cgmath::perspective(cgmath::Deg(camera.fovy), camera.aspect, camera.znear, camera.zfar);
+        let ndc_z_at_depth = |view_depth: f32| -> f32 {
+            let clip = proj * cgmath::Vector4::new(0.0, 0.0, -view_depth, 1.0);
+            clip.z / clip.w
+        };
+        let ndc_near_z = ndc_z_at_depth(split_near);
+        let ndc_far_z = ndc_z_at_depth(split_far);
+
+        let mut corners = [Vec3::ZERO; 8];
+        let mut i = 0;
+        for &x in &[-1.0_f32, 1.0] {
+            for &y in &[-1.0_f32, 1.0] {
+                for &z in &[ndc_near_z, ndc_far_z] {
+                    let corner = camera.inverse_view_proj * cgmath::Vector4::new(x, y, z, 1.0);
+                    corners[i] = Vec3::new(corner.x / corner.w, corner.y / corner.w, corner.z / corner.w);
+                    i += 1;
+                }
+            }
         }
+        corners
     }
 
     pub fn update_light_space_proj(&mut self, camera: &Camera) {
@@ -1,71 +1,271 @@
 use bmp::{Image, Pixel};
 
 use crate::world::CHUNK_XZ_SIZE;
-use noise::NoiseFn;
+use noise::{NoiseFn, Seedable};
 
 const BASE_FREQUENCY: f64 = 5.0 / 16.0;
 const NUM_OCTAVES: usize = 4;
 
-struct WorldNoise {
-    noise: Option<noise::OpenSimplex>,
+/// Builds the `OpenSimplex` field every noise sample in this module reads --
+/// elevation, biome moisture, and biome-boundary jitter all read from it at
+/// their own frequency and coordinate offset (see `MOISTURE_OFFSET` et al.)
+/// so they come out decorrelated despite sharing one underlying field.
+///
+/// This used to be a lazily-initialized `static mut` so every caller shared
+/// one instance, but that made it both a data race (unsynchronized `unsafe`
+/// access from `ChunkGenPool` worker threads) and non-deterministic
+/// (`OpenSimplex::new()` seeds itself once per process run, so the same
+/// `WORLD_SEED` produced a different world every launch). Building a fresh,
+/// `seed`-derived instance per call sidesteps both: the permutation table is
+/// cheap enough to construct that there's no need to cache or share it, so
+/// every caller -- regardless of which thread it runs on -- gets the same
+/// noise field back for the same seed.
+fn terrain_noise(seed: u64) -> noise::OpenSimplex {
+    noise::OpenSimplex::new().set_seed(seed as u32)
 }
-static mut NOISE_GENERATOR: WorldNoise = WorldNoise { noise: None };
 
 type ChunkElevationMap = [[u16; CHUNK_XZ_SIZE]; CHUNK_XZ_SIZE];
 
-// Source: https://www.redblobgames.com/maps/terrain-from-noise/
+/// How each octave's raw noise sample (already mapped from `[-1.0, 1.0]` to
+/// `[0.0, 1.0]`) turns into that octave's contribution to the elevation sum.
+/// `Fbm` is the original rounded-hills behavior; `Ridged` and `Billow` both
+/// key off the octave's distance from its midpoint (`0.5`) instead of its
+/// raw value, which is what turns smooth hills into sharp ridgelines or
+/// folded, valley-like terrain.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FractalMode {
+    /// Plain fractal Brownian motion: octaves summed as sampled.
+    Fbm,
+    /// `1.0 - |2*noise - 1.0|`, squared -- peaks where an octave crosses its
+    /// midpoint, so adjacent octaves' peaks chain into sharp ridgelines.
+    Ridged,
+    /// `|2*noise - 1.0|` -- valleys where an octave crosses its midpoint,
+    /// folding the terrain back on itself instead of smoothing through it.
+    Billow,
+}
+
+/// Tunable knobs for `sample_elevation_normalized`'s fractal noise sum, in
+/// place of the fixed 4-octave FBM it used to hardcode. `DEFAULT` reproduces
+/// that original behavior exactly (same octave count, same per-octave
+/// frequency/amplitude falloff, same redistribution exponent, no warping),
+/// so existing terrain doesn't change until a caller opts into something
+/// else.
+#[derive(Clone, Copy, Debug)]
+pub struct TerrainParams {
+    /// How many noise samples are summed per point.
+    pub octaves: usize,
+    /// Frequency multiplier applied per octave -- `2.0` doubles the noise's
+    /// "zoom level" each octave, same as the old hardcoded `2^i`.
+    pub lacunarity: f64,
+    /// Amplitude multiplier applied per octave -- `0.5` halves each
+    /// successive octave's contribution, same as the old hardcoded `1/2^i`.
+    pub gain: f64,
+    pub mode: FractalMode,
+    /// Exponent the final normalized sum is raised to before the caller
+    /// rescales it -- `1.4` pulls the median elevation down towards sea
+    /// level without touching the `[0.0, 1.0]` endpoints.
+    pub redistribution_exponent: f64,
+    /// Strength of the domain warp applied to `(nx, nz)` before the octave
+    /// loop samples it -- `0.0` disables warping entirely (the default).
+    pub warp_strength: f64,
+    /// Per-block frequency of the two 3D cave noise fields' horizontal axes
+    /// (see `should_carve_cave`).
+    pub cave_frequency: f64,
+    /// Per-block frequency of the cave noise fields' vertical axis, lower
+    /// than `cave_frequency` so tunnels stretch out sideways rather than
+    /// boring straight up and down.
+    pub cave_vertical_frequency: f64,
+    /// A voxel carves when both cave noise fields' absolute value falls
+    /// below this. ANDing two independently-offset fields narrows their
+    /// isosurface intersection down to connected, worm-like tunnels instead
+    /// of the wide sheets a single thresholded field produces.
+    pub cave_threshold: f64,
+}
+
+impl TerrainParams {
+    pub const DEFAULT: TerrainParams = TerrainParams {
+        octaves: NUM_OCTAVES,
+        lacunarity: 2.0,
+        gain: 0.5,
+        mode: FractalMode::Fbm,
+        redistribution_exponent: 1.4,
+        warp_strength: 0.0,
+        cave_frequency: 0.05,
+        cave_vertical_frequency: 0.08,
+        cave_threshold: 0.06,
+    };
+}
+
+impl Default for TerrainParams {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Coordinate offsets the two domain-warp channels sample `terrain_noise`
+/// at, far enough from each other and from the main elevation channel's own
+/// coordinates that all three come out decorrelated despite sharing one
+/// underlying field.
+const WARP_OFFSET_X: (f64, f64) = (3000.0, 0.0);
+const WARP_OFFSET_Z: (f64, f64) = (0.0, 3000.0);
+
+/// The fractal elevation sum `generate_chunk_elevation_map` and biome
+/// classification both read, at the same world-space coordinate and
+/// frequency, normalized to `[0.0, 1.0]` before either rescales it --
+/// sharing this is what keeps a biome boundary lined up with the terrain
+/// height it borders instead of drifting against an independently-sampled
+/// field.
+///
+/// Source: https://www.redblobgames.com/maps/terrain-from-noise/
+fn sample_elevation_normalized(
+    noise: noise::OpenSimplex,
+    params: TerrainParams,
+    world_x: usize,
+    world_z: usize,
+) -> f64 {
+    let mut nx: f64 = ((world_x as f64) / (CHUNK_XZ_SIZE as f64)) * BASE_FREQUENCY;
+    let mut nz: f64 = ((world_z as f64) / (CHUNK_XZ_SIZE as f64)) * BASE_FREQUENCY;
+
+    if params.warp_strength != 0.0 {
+        let warp_x = noise.get([nx + WARP_OFFSET_X.0, nz + WARP_OFFSET_X.1]);
+        let warp_z = noise.get([nx + WARP_OFFSET_Z.0, nz + WARP_OFFSET_Z.1]);
+        nx += params.warp_strength * warp_x;
+        nz += params.warp_strength * warp_z;
+    }
+
+    let mut elevation = 0.0_f64;
+    let mut amplitude = 1.0_f64;
+    let mut frequency = 1.0_f64;
+    let mut sum_of_amplitudes = 0.0_f64;
+
+    for _ in 0..params.octaves {
+        // Normalize [-1.0, 1.0] to [0.0, 1.0]
+        let noise_normalized = (noise.get([frequency * nx, frequency * nz]) + 1.0) / 2.0;
+        let octave_value = match params.mode {
+            FractalMode::Fbm => noise_normalized,
+            FractalMode::Ridged => {
+                let ridged = 1.0 - (2.0 * noise_normalized - 1.0).abs();
+                ridged * ridged
+            }
+            FractalMode::Billow => (2.0 * noise_normalized - 1.0).abs(),
+        };
+
+        elevation += amplitude * octave_value;
+        sum_of_amplitudes += amplitude;
+        amplitude *= params.gain;
+        frequency *= params.lacunarity;
+    }
+
+    elevation /= sum_of_amplitudes;
+    f64::powf(elevation, params.redistribution_exponent)
+}
+
 pub fn generate_chunk_elevation_map(
+    seed: u64,
+    params: TerrainParams,
     [chunk_x, chunk_z]: [usize; 2],
     min_elevation: u16,
     max_elevation: u16,
 ) -> ChunkElevationMap {
-    let noise = unsafe {
-        match NOISE_GENERATOR.noise {
-            None => {
-                NOISE_GENERATOR.noise = Some(noise::OpenSimplex::new());
-                NOISE_GENERATOR.noise.unwrap()
-            }
-            _ => NOISE_GENERATOR.noise.unwrap(),
-        }
-    };
+    let noise = terrain_noise(seed);
 
     let base_x = chunk_x * CHUNK_XZ_SIZE;
     let base_z = chunk_z * CHUNK_XZ_SIZE;
 
-    let mut elevation_map_f64 = [[0.0_f64; CHUNK_XZ_SIZE]; CHUNK_XZ_SIZE];
-
     let max_height = max_elevation - min_elevation;
 
+    let mut elevation_map_out: ChunkElevationMap = [[0_u16; CHUNK_XZ_SIZE]; CHUNK_XZ_SIZE];
     for (x, z) in iproduct!(0..CHUNK_XZ_SIZE, 0..CHUNK_XZ_SIZE) {
         let (world_x, world_z) = (base_x + x, base_z + z);
-        let nx: f64 = ((world_x as f64) / (CHUNK_XZ_SIZE as f64)) * BASE_FREQUENCY;
-        let nz: f64 = ((world_z as f64) / (CHUNK_XZ_SIZE as f64)) * BASE_FREQUENCY;
+        let elevation = sample_elevation_normalized(noise, params, world_x, world_z);
+        elevation_map_out[x][z] = (elevation * max_height as f64).floor() as u16 - min_elevation;
+    }
 
-        let mut elevation = 0.0_f64;
-        let mut sum_of_amplitudes = 0.0_f64;
+    elevation_map_out
+}
 
-        for i in 0..NUM_OCTAVES {
-            let octave = i32::pow(2, i as u32) as f64;
-            let amplitude = 1.0 / octave;
+/// Coordinate offsets the two cave noise fields sample `terrain_noise` at,
+/// far enough apart (and from the surface/warp channels' own offsets) that
+/// all of them come out decorrelated despite sharing one underlying field.
+const CAVE_OFFSET_A: (f64, f64, f64) = (6000.0, 0.0, 0.0);
+const CAVE_OFFSET_B: (f64, f64, f64) = (0.0, 6000.0, 0.0);
 
-            // Normalize [-1.0, 1.0] to [0.0, 1.0]
-            let noise_normalized = (noise.get([octave * nx, octave * nz]) + 1.0) / 2.0;
-            elevation += amplitude * noise_normalized;
-            sum_of_amplitudes += amplitude;
-        }
+/// Distance, in blocks, over which a column's cave threshold fades from
+/// `params.cave_threshold` down to zero as a voxel approaches bedrock
+/// (`min_height`) or the surface (`ground_elevation`) -- without this,
+/// tunnels would daylight through the ground or bottom out into an open
+/// world floor instead of staying enclosed.
+const CAVE_TAPER_DISTANCE: f64 = 6.0;
 
-        elevation /= sum_of_amplitudes;
-        elevation = f64::powf(elevation, 1.4);
-        elevation_map_f64[x][z] = elevation;
+/// Whether the voxel at `(world_x, y, world_z)` should be carved to air: a
+/// "worm" tunnel network emerges where two independently-offset 3D
+/// `OpenSimplex` fields (see `CAVE_OFFSET_A`/`CAVE_OFFSET_B`) both pass near
+/// their zero isosurface. Within `CAVE_TAPER_DISTANCE` of bedrock or the
+/// surface the threshold shrinks linearly with distance, so carving there
+/// grows rarer the closer a voxel is; a voxel on the bedrock layer or at the
+/// surface itself is never carved.
+fn should_carve_cave(
+    noise: noise::OpenSimplex,
+    params: TerrainParams,
+    world_x: usize,
+    y: usize,
+    world_z: usize,
+    min_height: u16,
+    ground_elevation: usize,
+) -> bool {
+    let dist_from_floor = (y - min_height as usize) as f64;
+    let dist_from_surface = (ground_elevation - y) as f64;
+    let taper = (dist_from_floor.min(dist_from_surface) / CAVE_TAPER_DISTANCE).clamp(0.0, 1.0);
+    if taper <= 0.0 {
+        return false;
     }
+    let threshold = params.cave_threshold * taper;
 
-    let mut elevation_map_out: ChunkElevationMap = [[0_u16; CHUNK_XZ_SIZE]; CHUNK_XZ_SIZE];
+    let sample_field_3d = |(ox, oy, oz): (f64, f64, f64)| -> f64 {
+        noise.get([
+            (world_x as f64) * params.cave_frequency + ox,
+            (y as f64) * params.cave_vertical_frequency + oy,
+            (world_z as f64) * params.cave_frequency + oz,
+        ])
+    };
+
+    sample_field_3d(CAVE_OFFSET_A).abs() < threshold
+        && sample_field_3d(CAVE_OFFSET_B).abs() < threshold
+}
+
+/// Chunk-local `(x, y, z)` coordinates `NoiseCaves` should carve to air,
+/// below `elevation_map`'s surface and above `min_height`. Builds the cave
+/// noise field once for the whole chunk rather than once per voxel, the way
+/// calling `should_carve_cave` directly per block would.
+pub fn generate_chunk_cave_mask(
+    seed: u64,
+    params: TerrainParams,
+    [chunk_x, chunk_z]: [usize; 2],
+    elevation_map: &ChunkElevationMap,
+    min_height: u16,
+) -> Vec<(usize, usize, usize)> {
+    let noise = terrain_noise(seed);
+    let base_x = chunk_x * CHUNK_XZ_SIZE;
+    let base_z = chunk_z * CHUNK_XZ_SIZE;
+
+    let mut carved = Vec::new();
     for (x, z) in iproduct!(0..CHUNK_XZ_SIZE, 0..CHUNK_XZ_SIZE) {
-        elevation_map_out[x][z] =
-            (elevation_map_f64[x][z] * max_height as f64).floor() as u16 - min_elevation;
+        let ground_elevation = elevation_map[x][z] as usize;
+        for y in (min_height as usize)..ground_elevation {
+            if should_carve_cave(
+                noise,
+                params,
+                base_x + x,
+                y,
+                base_z + z,
+                min_height,
+                ground_elevation,
+            ) {
+                carved.push((x, y, z));
+            }
+        }
     }
-
-    elevation_map_out
+    carved
 }
 
 pub fn save_elevation_to_file(elevation_map: ChunkElevationMap, filepath: &str) {
@@ -78,3 +278,127 @@ pub fn save_elevation_to_file(elevation_map: ChunkElevationMap, filepath: &str)
 
     let _ = img.save(filepath);
 }
+
+/// A much lower frequency than `BASE_FREQUENCY`, so biomes span many chunks
+/// rather than flickering column to column.
+const BIOME_FREQUENCY: f64 = 1.0 / 48.0;
+/// Coordinate offset the moisture channel samples `terrain_noise` at, far
+/// enough from the elevation channel's own coordinates that the two come out
+/// decorrelated despite being the same underlying OpenSimplex noise.
+const MOISTURE_OFFSET: (f64, f64) = (0.0, 1000.0);
+/// A finer, lower-amplitude noise channel added to elevation/moisture
+/// before `classify_biome` buckets them, so a biome boundary meanders
+/// instead of falling on a razor-straight iso-line -- this is what keeps
+/// adjacent columns from flipping biome outright across a hard
+/// single-column seam.
+const JITTER_FREQUENCY: f64 = 1.0 / 6.0;
+const JITTER_OFFSET: (f64, f64) = (500.0, 500.0);
+const JITTER_AMPLITUDE: f64 = 0.08;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Biome {
+    Ocean,
+    Beach,
+    Desert,
+    Grassland,
+    Forest,
+    Tundra,
+    Snow,
+}
+
+/// Every `Biome` variant, for code (`smoothed_biome`) that needs to count
+/// or iterate over all of them rather than matching a specific one.
+pub const ALL_BIOMES: [Biome; 7] = [
+    Biome::Ocean,
+    Biome::Beach,
+    Biome::Desert,
+    Biome::Grassland,
+    Biome::Forest,
+    Biome::Tundra,
+    Biome::Snow,
+];
+
+/// Whittaker-style elevation/moisture -> biome lookup: rows are increasing
+/// elevation bands (sea, coast, mid-elevation, high-elevation), columns are
+/// increasing moisture bands (dry, medium, wet). Tune biome placement by
+/// editing this table rather than the classification logic.
+const BIOME_TABLE: [[Biome; 3]; 4] = [
+    [Biome::Ocean, Biome::Ocean, Biome::Ocean],
+    [Biome::Beach, Biome::Beach, Biome::Beach],
+    [Biome::Desert, Biome::Grassland, Biome::Forest],
+    [Biome::Tundra, Biome::Tundra, Biome::Snow],
+];
+/// Upper bound of each `BIOME_TABLE` row except the last, in normalized
+/// `[0.0, 1.0]` elevation.
+const ELEVATION_THRESHOLDS: [f64; 3] = [0.32, 0.45, 0.78];
+/// Upper bound of each `BIOME_TABLE` column except the last, in normalized
+/// `[0.0, 1.0]` moisture.
+const MOISTURE_THRESHOLDS: [f64; 2] = [0.33, 0.66];
+
+fn classify_biome(elevation: f64, moisture: f64) -> Biome {
+    let row = ELEVATION_THRESHOLDS
+        .iter()
+        .position(|&threshold| elevation < threshold)
+        .unwrap_or(ELEVATION_THRESHOLDS.len());
+    let col = MOISTURE_THRESHOLDS
+        .iter()
+        .position(|&threshold| moisture < threshold)
+        .unwrap_or(MOISTURE_THRESHOLDS.len());
+    BIOME_TABLE[row][col]
+}
+
+/// A world column's biome plus the continuous elevation/moisture it was
+/// classified from -- `WorldState::biome_at` hands back just the `Biome`,
+/// but `generate_chunk`'s decoration density and other continuous effects
+/// (lighting tint, water color) may want the raw fields instead of the
+/// bucketed category.
+pub struct BiomeSample {
+    pub biome: Biome,
+    pub elevation: f64,
+    pub moisture: f64,
+}
+
+fn sample_field(
+    noise: noise::OpenSimplex,
+    world_x: usize,
+    world_z: usize,
+    frequency: f64,
+    (offset_x, offset_z): (f64, f64),
+) -> f64 {
+    let nx = (world_x as f64) * frequency + offset_x;
+    let nz = (world_z as f64) * frequency + offset_z;
+    (noise.get([nx, nz]) + 1.0) / 2.0
+}
+
+pub fn sample_biome(seed: u64, world_x: usize, world_z: usize) -> BiomeSample {
+    let noise = terrain_noise(seed);
+    let elevation = sample_elevation_normalized(noise, TerrainParams::DEFAULT, world_x, world_z);
+    let moisture = sample_field(noise, world_x, world_z, BIOME_FREQUENCY, MOISTURE_OFFSET);
+    let jitter = (sample_field(noise, world_x, world_z, JITTER_FREQUENCY, JITTER_OFFSET) * 2.0
+        - 1.0)
+        * JITTER_AMPLITUDE;
+
+    let biome = classify_biome(elevation + jitter, moisture + jitter);
+
+    BiomeSample {
+        biome,
+        elevation,
+        moisture,
+    }
+}
+
+type ChunkBiomeMap = [[Biome; CHUNK_XZ_SIZE]; CHUNK_XZ_SIZE];
+
+/// Parallel to `generate_chunk_elevation_map`: classifies every column in
+/// the chunk into a `Biome` from the same world-space elevation/moisture
+/// sampling `sample_biome` uses for a single column.
+pub fn generate_chunk_biome_map(seed: u64, [chunk_x, chunk_z]: [usize; 2]) -> ChunkBiomeMap {
+    let base_x = chunk_x * CHUNK_XZ_SIZE;
+    let base_z = chunk_z * CHUNK_XZ_SIZE;
+
+    let mut biome_map = [[Biome::Ocean; CHUNK_XZ_SIZE]; CHUNK_XZ_SIZE];
+    for (x, z) in iproduct!(0..CHUNK_XZ_SIZE, 0..CHUNK_XZ_SIZE) {
+        biome_map[x][z] = sample_biome(seed, base_x + x, base_z + z).biome;
+    }
+    biome_map
+}
@@ -0,0 +1,277 @@
+//! Greedy-mesh surface extraction for `ZArray3D` voxel volumes.
+//!
+//! This turns a dense 3D grid of cell values into a small set of merged
+//! rectangular quads describing its visible surface, suitable as input to a
+//! renderer's vertex buffer (see `vertex::Vertex::generate_quad_data_for_cuboid`
+//! for the unmerged, one-quad-per-face equivalent used by the cuboid pipeline).
+//!
+//! Not yet wired up to `world::mesh_chunk`'s per-block-face `InstanceRaw`
+//! terrain renderer -- see that function's doc comment for why.
+
+use crate::zarray::z3d::ZArray3D;
+
+/// Which of the six axis-aligned cuboid faces a quad represents, using the
+/// same face names as `CuboidCoords` (x: left/right, y: bottom/top, z: near/far).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Normal {
+    Left,
+    Right,
+    Bottom,
+    Top,
+    Near,
+    Far,
+}
+
+/// A merged rectangle of identical exposed faces produced by `greedy_mesh`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quad<T> {
+    /// World-space coordinate of the quad's minimum corner.
+    pub corner: (usize, usize, usize),
+    /// Extent along the mesh's first in-plane axis (see `Axis::in_plane_axes`).
+    pub width: usize,
+    /// Extent along the mesh's second in-plane axis.
+    pub height: usize,
+    /// Which face of a cell this quad covers.
+    pub normal: Normal,
+    /// The source cell value shared by every cell this quad covers.
+    pub value: T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    const ALL: [Axis; 3] = [Axis::X, Axis::Y, Axis::Z];
+
+    /// Returns (size along this axis, size along the first in-plane axis,
+    /// size along the second in-plane axis).
+    fn dims(self, xsize: usize, ysize: usize, zsize: usize) -> (usize, usize, usize) {
+        match self {
+            Axis::X => (xsize, ysize, zsize),
+            Axis::Y => (ysize, xsize, zsize),
+            Axis::Z => (zsize, xsize, ysize),
+        }
+    }
+
+    /// Maps a (slice index along this axis, position along the two in-plane
+    /// axes) triple back to world-space (x, y, z).
+    fn world_coord(self, d: usize, u: usize, v: usize) -> (usize, usize, usize) {
+        match self {
+            Axis::X => (d, u, v),
+            Axis::Y => (u, d, v),
+            Axis::Z => (u, v, d),
+        }
+    }
+
+    /// Normal of a face exposed by a solid cell at slice `d - 1` facing the
+    /// empty cell at slice `d` (i.e. facing in the direction of increasing
+    /// axis coordinate).
+    fn positive_normal(self) -> Normal {
+        match self {
+            Axis::X => Normal::Right,
+            Axis::Y => Normal::Top,
+            Axis::Z => Normal::Far,
+        }
+    }
+
+    /// Normal of a face exposed by a solid cell at slice `d` facing the empty
+    /// cell at slice `d - 1` (i.e. facing in the direction of decreasing axis
+    /// coordinate).
+    fn negative_normal(self) -> Normal {
+        match self {
+            Axis::X => Normal::Left,
+            Axis::Y => Normal::Bottom,
+            Axis::Z => Normal::Near,
+        }
+    }
+}
+
+/// Extracts the visible surface of `map` as a list of merged quads.
+///
+/// For each of the three axes and both facing directions, this sweeps every
+/// slice perpendicular to that axis, builds a 2D mask of exposed faces (a
+/// face exists where an `is_solid` cell is adjacent to a non-solid cell or
+/// the edge of the volume), then greedily merges each mask into the fewest
+/// rectangles of identical `(normal, value)` pairs. A solid cell on the
+/// boundary of the volume is always considered exposed on its outward side.
+pub fn greedy_mesh<T: Clone + PartialEq>(
+    map: &ZArray3D<T>,
+    is_solid: impl Fn(&T) -> bool,
+) -> Vec<Quad<T>> {
+    let (xsize, ysize, zsize) = map.dimensions();
+    let mut quads = Vec::new();
+
+    for axis in Axis::ALL {
+        let (dim_axis, dim_u, dim_v) = axis.dims(xsize, ysize, zsize);
+        for d in 0..=dim_axis {
+            let mut mask: Vec<Option<(Normal, T)>> = vec![None; dim_u * dim_v];
+            for v in 0..dim_v {
+                for u in 0..dim_u {
+                    let below = if d == 0 {
+                        None
+                    } else {
+                        let (x, y, z) = axis.world_coord(d - 1, u, v);
+                        map.bounded_get(x as isize, y as isize, z as isize)
+                    };
+                    let above = if d == dim_axis {
+                        None
+                    } else {
+                        let (x, y, z) = axis.world_coord(d, u, v);
+                        map.bounded_get(x as isize, y as isize, z as isize)
+                    };
+
+                    let below_solid = below.map(&is_solid).unwrap_or(false);
+                    let above_solid = above.map(&is_solid).unwrap_or(false);
+
+                    mask[u + v * dim_u] = match (below_solid, above_solid) {
+                        (true, false) => Some((axis.positive_normal(), below.unwrap().clone())),
+                        (false, true) => Some((axis.negative_normal(), above.unwrap().clone())),
+                        _ => None,
+                    };
+                }
+            }
+            greedy_merge_mask(&mut mask, dim_u, dim_v, axis, d, &mut quads);
+        }
+    }
+
+    quads
+}
+
+/// Scans `mask` (a `dim_u` x `dim_v` grid stored row-major as `u + v * dim_u`)
+/// for the largest axis-aligned rectangle of identical `(normal, value)`
+/// entries starting at each unvisited cell, emitting one quad per rectangle
+/// and clearing its cells so runs merge into as few quads as possible.
+fn greedy_merge_mask<T: Clone + PartialEq>(
+    mask: &mut [Option<(Normal, T)>],
+    dim_u: usize,
+    dim_v: usize,
+    axis: Axis,
+    d: usize,
+    quads_out: &mut Vec<Quad<T>>,
+) {
+    for v in 0..dim_v {
+        let mut u = 0;
+        while u < dim_u {
+            let (normal, value) = match mask[u + v * dim_u].clone() {
+                Some(entry) => entry,
+                None => {
+                    u += 1;
+                    continue;
+                }
+            };
+
+            let mut width = 1;
+            while u + width < dim_u && mask[(u + width) + v * dim_u] == Some((normal, value.clone())) {
+                width += 1;
+            }
+
+            let mut height = 1;
+            'grow_height: while v + height < dim_v {
+                for w in 0..width {
+                    if mask[(u + w) + (v + height) * dim_u] != Some((normal, value.clone())) {
+                        break 'grow_height;
+                    }
+                }
+                height += 1;
+            }
+
+            for hh in 0..height {
+                for ww in 0..width {
+                    mask[(u + ww) + (v + hh) * dim_u] = None;
+                }
+            }
+
+            let corner = axis.world_coord(d, u, v);
+            quads_out.push(Quad { corner, width, height, normal, value });
+
+            u += width;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_volume(xsize: usize, ysize: usize, zsize: usize) -> ZArray3D<u8> {
+        ZArray3D::new(xsize, ysize, zsize, 0)
+    }
+
+    #[test]
+    fn single_solid_cell_produces_six_unit_quads() {
+        let mut map = empty_volume(3, 3, 3);
+        map.set_unchecked(1, 1, 1, 1);
+
+        let quads = greedy_mesh(&map, |v| *v != 0);
+        assert_eq!(quads.len(), 6);
+        for q in &quads {
+            assert_eq!((q.width, q.height), (1, 1));
+            assert_eq!(q.value, 1);
+        }
+    }
+
+    #[test]
+    fn flat_solid_slab_merges_top_face_into_one_quad() {
+        let mut map = empty_volume(4, 1, 4);
+        for x in 0..4 {
+            for z in 0..4 {
+                map.set_unchecked(x, 0, z, 1);
+            }
+        }
+
+        let quads = greedy_mesh(&map, |v| *v != 0);
+        let top_quads: Vec<_> = quads.iter().filter(|q| q.normal == Normal::Top).collect();
+        assert_eq!(top_quads.len(), 1);
+        assert_eq!((top_quads[0].width, top_quads[0].height), (4, 4));
+
+        let bottom_quads: Vec<_> = quads.iter().filter(|q| q.normal == Normal::Bottom).collect();
+        assert_eq!(bottom_quads.len(), 1);
+        assert_eq!((bottom_quads[0].width, bottom_quads[0].height), (4, 4));
+    }
+
+    #[test]
+    fn differing_values_prevent_merging_across_the_boundary() {
+        let mut map = empty_volume(4, 1, 1);
+        map.set_unchecked(0, 0, 0, 1);
+        map.set_unchecked(1, 0, 0, 1);
+        map.set_unchecked(2, 0, 0, 2);
+        map.set_unchecked(3, 0, 0, 2);
+
+        let quads = greedy_mesh(&map, |v| *v != 0);
+        let top_quads: Vec<_> = quads.iter().filter(|q| q.normal == Normal::Top).collect();
+        assert_eq!(top_quads.len(), 2);
+        assert_eq!(top_quads[0].width, 2);
+        assert_eq!(top_quads[1].width, 2);
+    }
+
+    #[test]
+    fn empty_volume_produces_no_quads() {
+        let map = empty_volume(4, 4, 4);
+        let quads = greedy_mesh(&map, |v| *v != 0);
+        assert!(quads.is_empty());
+    }
+
+    #[test]
+    fn fully_solid_volume_only_exposes_its_outer_boundary() {
+        let mut map = empty_volume(2, 2, 2);
+        for x in 0..2 {
+            for y in 0..2 {
+                for z in 0..2 {
+                    map.set_unchecked(x, y, z, 1);
+                }
+            }
+        }
+
+        let quads = greedy_mesh(&map, |v| *v != 0);
+        // A fully solid 2x2x2 cube has no interior faces: each of the six
+        // sides is covered by exactly one 2x2 quad.
+        assert_eq!(quads.len(), 6);
+        for q in &quads {
+            assert_eq!((q.width, q.height), (2, 2));
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Complementary filter fusing a phone's gyroscope and accelerometer into a
+//! stable orientation quaternion for gyro-based camera look on mobile web.
+//!
+//! Gyro integration alone drifts over time; accelerometer alone is noisy and
+//! unusable under linear acceleration. Blending a small accelerometer
+//! correction into the fast gyro-integrated estimate each frame cancels the
+//! drift while keeping the gyro's responsiveness.
+
+use cgmath::{InnerSpace, One, Quaternion, Rad, Rotation, Vector3};
+
+/// How strongly each frame's accelerometer reading pulls the integrated
+/// orientation back toward gravity. Small so gyro drift is cancelled over
+/// many frames without the noisy accelerometer estimate dominating any
+/// single frame.
+const ACCEL_CORRECTION_ALPHA: f64 = 0.02;
+
+/// Accelerometer readings are only trusted when `|accel|` is within this
+/// fraction of 1g; further away means the device is under linear
+/// acceleration and the reading no longer points at gravity.
+const GRAVITY_MAGNITUDE_TOLERANCE: f64 = 0.1;
+
+/// World-space direction gravity points away from, i.e. the direction a
+/// stationary accelerometer reads. Matches this engine's Y-up convention.
+fn world_up() -> Vector3<f64> {
+    Vector3::unit_y()
+}
+
+pub struct OrientationFilter {
+    orientation: Quaternion<f64>,
+}
+
+impl OrientationFilter {
+    pub fn new() -> Self {
+        Self { orientation: Quaternion::one() }
+    }
+
+    pub fn orientation(&self) -> Quaternion<f64> {
+        self.orientation
+    }
+
+    /// Integrates `gyro` (rad/s, device frame) over `dt` seconds, then nudges
+    /// the result toward the orientation implied by `accel` (device frame,
+    /// in g) when `accel` is close enough to 1g to trust.
+    pub fn update(&mut self, accel: Vector3<f64>, gyro: Vector3<f64>, dt: f64) -> Quaternion<f64> {
+        let angle = gyro.magnitude() * dt;
+        let dq = if angle > 1e-9 {
+            Quaternion::from_axis_angle(gyro.normalize(), Rad(angle))
+        } else {
+            Quaternion::one()
+        };
+        self.orientation = (self.orientation * dq).normalize();
+
+        let accel_magnitude = accel.magnitude();
+        if accel_magnitude > 1e-9
+            && (accel_magnitude - 1.0).abs() < GRAVITY_MAGNITUDE_TOLERANCE
+        {
+            let measured_gravity = accel / accel_magnitude;
+            let predicted_gravity = self.orientation.invert().rotate_vector(world_up());
+            let correction = rotation_between(predicted_gravity, measured_gravity);
+            let corrected = (correction * self.orientation).normalize();
+            self.orientation = self.orientation.slerp(corrected, ACCEL_CORRECTION_ALPHA);
+        }
+
+        self.orientation
+    }
+}
+
+/// Shortest-arc rotation taking unit vector `from` to unit vector `to`.
+/// Returns the identity if the vectors are already aligned (or exactly
+/// opposed, since the correction nudge is small enough that an arbitrary
+/// perpendicular axis in that degenerate case would do more harm than good).
+fn rotation_between(from: Vector3<f64>, to: Vector3<f64>) -> Quaternion<f64> {
+    let axis = from.cross(to);
+    let sin_angle = axis.magnitude();
+    let cos_angle = from.dot(to);
+    if sin_angle < 1e-9 {
+        return Quaternion::one();
+    }
+    Quaternion::from_axis_angle(axis / sin_angle, Rad(sin_angle.atan2(cos_angle)))
+}
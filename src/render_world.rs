@@ -0,0 +1,82 @@
+//! A small entity-component render scheduler for the handful of debug/
+//! auxiliary draws (the character model, the light-volume wireframe, ...)
+//! that used to be gated behind `const` flags like `RENDER_CHARACTER_ENTITY`
+//! in `lib.rs`, with pipeline switches spelled out inline per flag. Entities
+//! here can be spawned, despawned, and toggled at runtime instead.
+//!
+//! This is a hand-rolled stand-in for a real ECS (e.g. `bevy_ecs`) rather
+//! than a new dependency, since this snapshot has no Cargo manifest to pin
+//! one in. `Game::render_system` (in `lib.rs`, where `Scene`'s buffers and
+//! pipelines live) does the actual `wgpu` dispatch, batching draws by
+//! `RenderPipelineKind` so each pipeline is bound at most once per pass.
+
+pub type EntityId = usize;
+
+/// Which mesh (vertex/index buffer pair, already uploaded to `Scene`) an
+/// entity draws.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshKind {
+    CharacterEntity,
+    LightVolume,
+    SelectionOutline,
+}
+
+/// Which `Scene` pipeline (and its accompanying bind groups) an entity
+/// draws with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderPipelineKind {
+    /// The unlit solid-color variant of the main instanced pipeline.
+    SolidColor,
+    /// The non-instanced wireframe pipeline, used for debug volumes.
+    WireNoInstancing,
+}
+
+pub struct RenderEntity {
+    pub mesh: MeshKind,
+    pub pipeline: RenderPipelineKind,
+    pub visible: bool,
+}
+
+/// Holds the auxiliary render entities for one `Scene`. Chunks aren't
+/// modeled here -- they're bulk instanced data driven by `WorldState`, not
+/// individually spawned/despawned objects -- so this only covers the small,
+/// one-off draws that used to be `const`-gated.
+#[derive(Default)]
+pub struct RenderWorld {
+    entities: Vec<Option<RenderEntity>>,
+}
+
+impl RenderWorld {
+    pub fn new() -> Self {
+        Self { entities: vec![] }
+    }
+
+    pub fn spawn(&mut self, entity: RenderEntity) -> EntityId {
+        self.entities.push(Some(entity));
+        self.entities.len() - 1
+    }
+
+    pub fn despawn(&mut self, entity: EntityId) {
+        self.entities[entity] = None;
+    }
+
+    pub fn set_visible(&mut self, entity: EntityId, visible: bool) {
+        if let Some(entity) = &mut self.entities[entity] {
+            entity.visible = visible;
+        }
+    }
+
+    /// Visible entities, grouped so all entities sharing a pipeline are
+    /// adjacent -- the renderer can then issue one `set_pipeline` per group
+    /// instead of one per entity.
+    pub fn visible_grouped_by_pipeline(&self) -> Vec<(RenderPipelineKind, Vec<&RenderEntity>)> {
+        let mut groups: Vec<(RenderPipelineKind, Vec<&RenderEntity>)> = vec![];
+        for entity in self.entities.iter().flatten().filter(|e| e.visible) {
+            match groups.iter_mut().find(|(kind, _)| *kind == entity.pipeline) {
+                Some((_, batch)) => batch.push(entity),
+                None => groups.push((entity.pipeline, vec![entity])),
+            }
+        }
+        groups
+    }
+}
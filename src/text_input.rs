@@ -0,0 +1,93 @@
+//! A line-buffered text-entry mode for chat and slash-commands, toggled on
+//! top of the normal `input::ActionHandler` gameplay bindings.
+//!
+//! Movement/block-breaking keys are physical `VirtualKeyCode`s, which only
+//! identify *which key* was pressed, not what character it produces under
+//! the active keyboard layout or shift state. Typing therefore has to
+//! consume `WindowEvent::ReceivedCharacter` (winit's composed/symbolic
+//! character event) instead -- capturing the key's *meaning* rather than
+//! reverse-mapping its `VirtualKeyCode`, which is what makes this correct
+//! for shifted symbols and non-QWERTY layouts.
+
+use winit::event::{ElementState, VirtualKeyCode, WindowEvent};
+
+/// A line submitted from `TextInputBuffer`, parsed by `parse_line`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChatMessage {
+    /// A `/`-prefixed line, split into its command word and the rest of the
+    /// line as a single argument string.
+    Command { name: String, args: String },
+    /// Anything else, broadcast as-is.
+    Chat(String),
+}
+
+/// Splits a submitted line into a `ChatMessage` -- `/`-prefixed lines become
+/// `Command`s, everything else is `Chat`.
+pub fn parse_line(line: &str) -> ChatMessage {
+    match line.strip_prefix('/') {
+        Some(rest) => {
+            let mut parts = rest.splitn(2, ' ');
+            let name = parts.next().unwrap_or("").to_string();
+            let args = parts.next().unwrap_or("").to_string();
+            ChatMessage::Command { name, args }
+        }
+        None => ChatMessage::Chat(line.to_string()),
+    }
+}
+
+/// Accumulates `ReceivedCharacter` events into a line while active, handling
+/// backspace/enter/escape via their `VirtualKeyCode` (control keys, unlike
+/// typed symbols, are reliably identified by keycode across layouts).
+#[derive(Default)]
+pub struct TextInputBuffer {
+    active: bool,
+    line: String,
+}
+
+impl TextInputBuffer {
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn activate(&mut self) {
+        self.active = true;
+        self.line.clear();
+    }
+
+    pub fn line(&self) -> &str {
+        &self.line
+    }
+
+    /// Feeds a raw window event while text-entry mode is active -- a no-op
+    /// if it isn't (call `activate` first). Returns the submitted line on
+    /// Enter, after which text-entry mode is no longer active; also no
+    /// longer active (but with no returned line) after Escape cancels it.
+    pub fn process_window_event(&mut self, event: &WindowEvent) -> Option<String> {
+        if !self.active {
+            return None;
+        }
+        match event {
+            WindowEvent::KeyboardInput { input, .. } if input.state == ElementState::Pressed => {
+                match input.virtual_keycode {
+                    Some(VirtualKeyCode::Return) | Some(VirtualKeyCode::NumpadEnter) => {
+                        self.active = false;
+                        return Some(std::mem::take(&mut self.line));
+                    }
+                    Some(VirtualKeyCode::Back) => {
+                        self.line.pop();
+                    }
+                    Some(VirtualKeyCode::Escape) => {
+                        self.active = false;
+                        self.line.clear();
+                    }
+                    _ => (),
+                }
+            }
+            WindowEvent::ReceivedCharacter(c) if !c.is_control() => {
+                self.line.push(*c);
+            }
+            _ => (),
+        }
+        None
+    }
+}
@@ -0,0 +1,208 @@
+//! GPU-side frame timing via `wgpu::QuerySet` timestamp queries, to correlate
+//! real GPU occupancy with the CPU-side stats in `game_loop::GameLoop`.
+//!
+//! Timestamp queries are an optional device feature, so everything here
+//! degrades gracefully: when the adapter doesn't support
+//! `wgpu::Features::TIMESTAMP_QUERY`, `Timings` is still produced every
+//! frame, just derived from CPU `Instant` deltas around the same passes
+//! instead of GPU timestamps.
+
+use std::time::{Duration, Instant};
+
+/// The major passes we bracket with timestamp queries, in begin/end pairs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Pass {
+    Geometry,
+    Ui,
+    Tonemap,
+}
+
+const NUM_PASSES: usize = 3;
+// One begin + one end timestamp per pass.
+const NUM_QUERIES: usize = NUM_PASSES * 2;
+
+fn pass_index(pass: Pass) -> usize {
+    match pass {
+        Pass::Geometry => 0,
+        Pass::Ui => 1,
+        Pass::Tonemap => 2,
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Timings {
+    pub geometry: Duration,
+    pub ui: Duration,
+    pub tonemap: Duration,
+}
+
+enum Backend {
+    GpuTimestamps {
+        query_set: wgpu::QuerySet,
+        resolve_buffer: wgpu::Buffer,
+        readback_buffer: wgpu::Buffer,
+        timestamp_period: f32,
+    },
+    CpuFallback {
+        pass_start: [Option<Instant>; NUM_PASSES],
+        last_durations: [Duration; NUM_PASSES],
+    },
+}
+
+/// GPU (or CPU-fallback) timing subsystem for the render passes that consume
+/// `InstanceRaw` each frame.
+pub struct TimingQueries {
+    backend: Backend,
+}
+
+impl TimingQueries {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let has_timestamp_query = device.features().contains(wgpu::Features::TIMESTAMP_QUERY);
+
+        let backend = if has_timestamp_query {
+            let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("Timestamp Query Set"),
+                ty: wgpu::QueryType::Timestamp,
+                count: NUM_QUERIES as u32,
+            });
+            let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Timestamp Resolve Buffer"),
+                size: (NUM_QUERIES * std::mem::size_of::<u64>()) as u64,
+                usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            });
+            let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Timestamp Readback Buffer"),
+                size: (NUM_QUERIES * std::mem::size_of::<u64>()) as u64,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+
+            Backend::GpuTimestamps {
+                query_set,
+                resolve_buffer,
+                readback_buffer,
+                timestamp_period: queue.get_timestamp_period(),
+            }
+        } else {
+            log::warn!("TIMESTAMP_QUERY unsupported, falling back to CPU Instant timings");
+            Backend::CpuFallback {
+                pass_start: [None; NUM_PASSES],
+                last_durations: [Duration::ZERO; NUM_PASSES],
+            }
+        };
+
+        Self { backend }
+    }
+
+    pub fn has_gpu_timestamps(&self) -> bool {
+        matches!(self.backend, Backend::GpuTimestamps { .. })
+    }
+
+    /// Mark the start of `pass` within `encoder`. Must be paired with `end`.
+    pub fn begin(&mut self, encoder: &mut wgpu::CommandEncoder, pass: Pass) {
+        let idx = pass_index(pass) * 2;
+        match &mut self.backend {
+            Backend::GpuTimestamps { query_set, .. } => {
+                encoder.write_timestamp(query_set, idx as u32);
+            }
+            Backend::CpuFallback { pass_start, .. } => {
+                pass_start[pass_index(pass)] = Some(Instant::now());
+            }
+        }
+    }
+
+    pub fn end(&mut self, encoder: &mut wgpu::CommandEncoder, pass: Pass) {
+        let idx = pass_index(pass) * 2 + 1;
+        match &mut self.backend {
+            Backend::GpuTimestamps { query_set, .. } => {
+                encoder.write_timestamp(query_set, idx as u32);
+            }
+            Backend::CpuFallback {
+                pass_start,
+                last_durations,
+            } => {
+                if let Some(start) = pass_start[pass_index(pass)].take() {
+                    last_durations[pass_index(pass)] = start.elapsed();
+                }
+            }
+        }
+    }
+
+    /// Resolves the query set into a readable buffer. No-op under the CPU
+    /// fallback. Call once per frame, after all `begin`/`end` pairs, before
+    /// submitting `encoder`.
+    pub fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+        if let Backend::GpuTimestamps {
+            query_set,
+            resolve_buffer,
+            ..
+        } = &self.backend
+        {
+            encoder.resolve_query_set(query_set, 0..NUM_QUERIES as u32, resolve_buffer, 0);
+        }
+    }
+
+    /// Copies the resolved queries into the mappable readback buffer. Call
+    /// after `resolve`, against the same encoder, before submission.
+    pub fn copy_to_readback(&self, encoder: &mut wgpu::CommandEncoder) {
+        if let Backend::GpuTimestamps {
+            resolve_buffer,
+            readback_buffer,
+            ..
+        } = &self.backend
+        {
+            encoder.copy_buffer_to_buffer(
+                resolve_buffer,
+                0,
+                readback_buffer,
+                0,
+                (NUM_QUERIES * std::mem::size_of::<u64>()) as u64,
+            );
+        }
+    }
+
+    /// Maps the readback buffer and produces this frame's `Timings`. Must be
+    /// called after the encoder that queued `resolve`/`copy_to_readback` has
+    /// been submitted. Blocks on `device.poll` while the map future settles,
+    /// matching the synchronous style of the rest of this crate's device
+    /// setup.
+    pub fn read_timings(&self, device: &wgpu::Device) -> Timings {
+        match &self.backend {
+            Backend::GpuTimestamps {
+                readback_buffer,
+                timestamp_period,
+                ..
+            } => {
+                let slice = readback_buffer.slice(..);
+                slice.map_async(wgpu::MapMode::Read, |_| {});
+                device.poll(wgpu::Maintain::Wait);
+
+                let raw = slice.get_mapped_range();
+                let ticks: &[u64] = bytemuck::cast_slice(&raw);
+                let tick_to_duration = |delta_ticks: u64| {
+                    Duration::from_nanos((delta_ticks as f32 * timestamp_period) as u64)
+                };
+
+                let geometry =
+                    tick_to_duration(ticks[1].saturating_sub(ticks[0]));
+                let ui = tick_to_duration(ticks[3].saturating_sub(ticks[2]));
+                let tonemap = tick_to_duration(ticks[5].saturating_sub(ticks[4]));
+
+                drop(raw);
+                readback_buffer.unmap();
+
+                Timings {
+                    geometry,
+                    ui,
+                    tonemap,
+                }
+            }
+            Backend::CpuFallback { last_durations, .. } => Timings {
+                geometry: last_durations[pass_index(Pass::Geometry)],
+                ui: last_durations[pass_index(Pass::Ui)],
+                tonemap: last_durations[pass_index(Pass::Tonemap)],
+            },
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Two-finger pan/zoom/twist recognition for the web build's touch controls,
+//! modeled on the grab/pan gesture found in most map and modeling UIs: track
+//! the centroid, span, and bearing of the two active touch points across
+//! frames and report how each changed as a single `Pan` event.
+//!
+//! This mainly recognizes the two-finger gesture -- the existing
+//! single-finger virtual joysticks (`DomControlsUserEvent::
+//! PitchYawJoystickMoved`/`TranslationJoystickMoved`) cover one-finger input
+//! that starts out as one finger. The one exception is a finger that
+//! survives a two-finger gesture: it keeps being tracked (translation-only)
+//! until it lifts, so releasing the second finger doesn't jump the camera
+//! back to whatever the joystick was last doing.
+
+/// Selects which components of a two-finger gesture are reported, so e.g. a
+/// plain two-finger drag doesn't spuriously roll or zoom the camera from
+/// hand tremor in the other components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GestureMode {
+    PanOnly,
+    PanScale,
+    PanRotate,
+    PanFull,
+}
+
+impl GestureMode {
+    /// Zeroes out (to the identity value) whichever components this mode
+    /// doesn't report.
+    fn mask(self, pan: Pan) -> Pan {
+        match self {
+            GestureMode::PanOnly => Pan {
+                translation: pan.translation,
+                scale: 1.0,
+                rotation: 0.0,
+            },
+            GestureMode::PanScale => Pan {
+                translation: pan.translation,
+                scale: pan.scale,
+                rotation: 0.0,
+            },
+            GestureMode::PanRotate => Pan {
+                translation: pan.translation,
+                scale: 1.0,
+                rotation: pan.rotation,
+            },
+            GestureMode::PanFull => pan,
+        }
+    }
+}
+
+/// One frame's worth of two-finger gesture delta. `scale` is the ratio of
+/// this frame's finger-to-finger distance to the previous frame's (>1.0
+/// means the fingers spread apart); `rotation` is the signed angle (radians)
+/// the finger-to-finger vector turned by.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pan {
+    pub translation: (f64, f64),
+    pub scale: f64,
+    pub rotation: f64,
+}
+
+/// The previous frame's two-touch snapshot, used to diff against the
+/// current frame.
+struct TwoTouchState {
+    centroid: (f64, f64),
+    distance: f64,
+    angle: f64,
+}
+
+fn two_touch_state(a: (f64, f64), b: (f64, f64)) -> TwoTouchState {
+    let dx = b.0 - a.0;
+    let dy = b.1 - a.1;
+    TwoTouchState {
+        centroid: ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0),
+        distance: (dx * dx + dy * dy).sqrt(),
+        angle: dy.atan2(dx),
+    }
+}
+
+pub struct TouchGestureRecognizer {
+    mode: GestureMode,
+    two_touch: Option<TwoTouchState>,
+    /// The surviving finger's position, tracked only after a two-finger
+    /// gesture drops to one (see `update`) -- a single touch that never had
+    /// a partner is left untracked, for the single-finger virtual
+    /// joysticks to handle instead.
+    single_touch: Option<(f64, f64)>,
+}
+
+impl TouchGestureRecognizer {
+    pub fn new(mode: GestureMode) -> Self {
+        Self {
+            mode,
+            two_touch: None,
+            single_touch: None,
+        }
+    }
+
+    pub fn set_mode(&mut self, mode: GestureMode) {
+        self.mode = mode;
+    }
+
+    /// Feeds every touch point currently down (not just the ones that
+    /// moved) and returns this frame's `Pan`, if two fingers are down.
+    ///
+    /// Dropping from two fingers to one is a named edge case: rather than
+    /// just forgetting the gesture, the surviving finger is re-seeded as a
+    /// fresh single-touch origin (`single_touch`) so the *next* frame's
+    /// delta is measured from where that finger actually is instead of
+    /// jumping from the old two-finger centroid. The frame of the drop
+    /// itself reports a neutral `Pan` since there's nothing yet to diff
+    /// against; `single_touch` then keeps following that one finger,
+    /// translation-only, until it lifts.
+    pub fn update(&mut self, touches: &[(f64, f64)]) -> Option<Pan> {
+        if touches.len() < 2 {
+            if self.two_touch.take().is_some() {
+                self.single_touch = touches.first().copied();
+                return Some(self.mode.mask(Pan {
+                    translation: (0.0, 0.0),
+                    scale: 1.0,
+                    rotation: 0.0,
+                }));
+            }
+
+            return match (self.single_touch, touches.first()) {
+                (Some(prev), Some(&touch)) => {
+                    self.single_touch = Some(touch);
+                    Some(self.mode.mask(Pan {
+                        translation: (touch.0 - prev.0, touch.1 - prev.1),
+                        scale: 1.0,
+                        rotation: 0.0,
+                    }))
+                }
+                (Some(_), None) => {
+                    self.single_touch = None;
+                    None
+                }
+                (None, _) => None,
+            };
+        }
+
+        self.single_touch = None;
+        let current = two_touch_state(touches[0], touches[1]);
+
+        let pan = match &self.two_touch {
+            // First frame the second finger appeared -- there's no previous
+            // distance/angle to diff against, so report a neutral
+            // scale/rotation instead of a spurious jump.
+            None => Pan {
+                translation: (0.0, 0.0),
+                scale: 1.0,
+                rotation: 0.0,
+            },
+            Some(prev) => Pan {
+                translation: (
+                    current.centroid.0 - prev.centroid.0,
+                    current.centroid.1 - prev.centroid.1,
+                ),
+                scale: if prev.distance > 0.0 {
+                    current.distance / prev.distance
+                } else {
+                    1.0
+                },
+                // Wrapped into [-PI, PI) so crossing atan2's +-PI seam
+                // reports the small turn, not a near-full revolution.
+                rotation: (current.angle - prev.angle + std::f64::consts::PI)
+                    .rem_euclid(std::f64::consts::TAU)
+                    - std::f64::consts::PI,
+            },
+        };
+
+        self.two_touch = Some(current);
+
+        Some(self.mode.mask(pan))
+    }
+}
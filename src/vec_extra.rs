@@ -1,6 +1,8 @@
 use std::ops::{Index, IndexMut};
 
+#[derive(Clone, Copy)]
 pub struct YXZ<const XSIZE: usize, const YSIZE: usize, const ZSIZE: usize> {}
+#[derive(Clone, Copy)]
 pub struct XYZ<const XSIZE: usize, const YSIZE: usize, const ZSIZE: usize> {}
 pub trait DimOrder: Sized {
     fn new() -> Self;
@@ -35,7 +37,7 @@ impl<const XSIZE: usize, const YSIZE: usize, const ZSIZE: usize> DimOrder
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Vec3d<T, DO: DimOrder> {
     vec: Vec<T>,
     dim_order: DO,
@@ -67,6 +69,100 @@ impl<T, DO: DimOrder> Vec3d<T, DO> {
     pub fn dims(&self) -> &[usize; 3] {
         DO::dims()
     }
+
+    /// Walks the grid from `origin` along `direction` (Amanatides-Woo DDA),
+    /// stepping one cell at a time along whichever axis reaches its next
+    /// voxel boundary first, until `is_solid` reports a hit, `max_distance`
+    /// is exceeded, or the walk leaves `dims()`. The core primitive behind
+    /// block selection/placement and line-of-sight checks -- callers that
+    /// need those should build on this rather than re-deriving grid
+    /// traversal themselves.
+    pub fn raycast<F>(
+        &self,
+        origin: [f32; 3],
+        direction: [f32; 3],
+        max_distance: f32,
+        is_solid: F,
+    ) -> Option<RaycastHit>
+    where
+        F: Fn(&T) -> bool,
+    {
+        let dims = *self.dims();
+        let in_bounds = |cell: [isize; 3]| (0..3).all(|axis| cell[axis] >= 0 && (cell[axis] as usize) < dims[axis]);
+
+        let mut cell = [
+            origin[0].floor() as isize,
+            origin[1].floor() as isize,
+            origin[2].floor() as isize,
+        ];
+        let mut normal = [0_i32; 3];
+
+        if in_bounds(cell) && is_solid(&self[[cell[0] as usize, cell[1] as usize, cell[2] as usize]]) {
+            return Some(RaycastHit {
+                cell: [cell[0] as usize, cell[1] as usize, cell[2] as usize],
+                normal,
+                t: 0.0,
+            });
+        }
+
+        let mut step = [0_i32; 3];
+        let mut t_max = [f32::INFINITY; 3];
+        let mut t_delta = [f32::INFINITY; 3];
+        for axis in 0..3 {
+            if direction[axis] > 0.0 {
+                step[axis] = 1;
+                t_max[axis] = (cell[axis] as f32 + 1.0 - origin[axis]) / direction[axis];
+                t_delta[axis] = 1.0 / direction[axis];
+            } else if direction[axis] < 0.0 {
+                step[axis] = -1;
+                t_max[axis] = (cell[axis] as f32 - origin[axis]) / direction[axis];
+                t_delta[axis] = -1.0 / direction[axis];
+            }
+        }
+
+        loop {
+            let axis = if t_max[0] < t_max[1] {
+                if t_max[0] < t_max[2] { 0 } else { 2 }
+            } else if t_max[1] < t_max[2] {
+                1
+            } else {
+                2
+            };
+
+            let t = t_max[axis];
+            if t > max_distance {
+                return None;
+            }
+
+            cell[axis] += step[axis] as isize;
+            t_max[axis] += t_delta[axis];
+            normal = [0, 0, 0];
+            normal[axis] = -step[axis];
+
+            if !in_bounds(cell) {
+                return None;
+            }
+
+            let [x, y, z] = cell;
+            if is_solid(&self[[x as usize, y as usize, z as usize]]) {
+                return Some(RaycastHit {
+                    cell: [x as usize, y as usize, z as usize],
+                    normal,
+                    t,
+                });
+            }
+        }
+    }
+}
+
+/// The result of `Vec3d::raycast`: the first solid cell the ray hit, the
+/// face normal it crossed to enter that cell, and the ray parameter `t` of
+/// that crossing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RaycastHit {
+    pub cell: [usize; 3],
+    pub normal: [i32; 3],
+    pub t: f32,
 }
 
 impl<T, DO: DimOrder> Index<[usize; 3]> for Vec3d<T, DO> {
@@ -85,6 +181,189 @@ impl<T, DO: DimOrder> IndexMut<[usize; 3]> for Vec3d<T, DO> {
     }
 }
 
+/// Number of bits needed to index `len` distinct palette entries (minimum
+/// 1, so an all-one-value chunk still has an addressable index).
+fn bits_for_palette_len(len: usize) -> u32 {
+    if len <= 1 {
+        1
+    } else {
+        usize::BITS - (len - 1).leading_zeros()
+    }
+}
+
+fn read_packed(packed: &[u32], bits: u32, cell: usize) -> u32 {
+    let bit_pos = cell * bits as usize;
+    let word = bit_pos / 32;
+    let offset = bit_pos % 32;
+    let mask = if bits >= 32 { u32::MAX } else { (1u32 << bits) - 1 };
+
+    let mut value = (packed[word] >> offset) & mask;
+    let overflow = offset as i64 + bits as i64 - 32;
+    if overflow > 0 {
+        value |= (packed[word + 1] << (bits as i64 - overflow)) & mask;
+    }
+    value
+}
+
+fn write_packed(packed: &mut [u32], bits: u32, cell: usize, value: u32) {
+    let bit_pos = cell * bits as usize;
+    let word = bit_pos / 32;
+    let offset = bit_pos % 32;
+
+    packed[word] |= value << offset;
+    let overflow = offset as i64 + bits as i64 - 32;
+    if overflow > 0 {
+        packed[word + 1] |= value >> (bits as i64 - overflow);
+    }
+}
+
+fn pack_indices(indices: &[u32], bits: u32) -> Vec<u32> {
+    let mut packed = vec![0_u32; (indices.len() * bits as usize + 31) / 32];
+    for (cell, &index) in indices.iter().enumerate() {
+        write_packed(&mut packed, bits, cell, index);
+    }
+    packed
+}
+
+/// Alternate `Vec3d` backing for chunk-sized grids that are mostly long runs
+/// of a handful of distinct values (air/stone and friends) -- a flat
+/// `Vec3d<Block, _>` spends a full `size_of::<Block>()` per cell regardless.
+/// Cells are stored as indices into `palette`, packed to the smallest bit
+/// width that fits `palette.len()` (1 bit up to 2 entries, 2 bits up to 4,
+/// ...), which both of the packing functions above operate on a cell at a
+/// time.
+///
+/// `Index`/`IndexMut` return `&T`/`&mut T` like `Vec3d`, so callers don't
+/// need to change. Reads are free (`&palette[index]`), but a caller holding
+/// `&mut T` could write any value at all, which a shared palette entry
+/// can't represent -- so the first `index_mut` call fully decompresses into
+/// `expanded` and subsequent reads/writes go through that until `optimize`
+/// re-palettes and re-packs it back down.
+pub struct PalettedVec3d<T, DO: DimOrder> {
+    palette: Vec<T>,
+    bits_per_index: u32,
+    packed_indices: Vec<u32>,
+    expanded: Option<Vec<T>>,
+    dim_order: DO,
+}
+
+impl<T: Clone + PartialEq, DO: DimOrder> PalettedVec3d<T, DO> {
+    pub fn new(vec: Vec<T>) -> Self {
+        let dims = DO::dims();
+        assert!(vec.len() == dims[0] * dims[1] * dims[2]);
+
+        let (palette, indices) = Self::palettize(&vec);
+        let bits_per_index = bits_for_palette_len(palette.len());
+        let packed_indices = pack_indices(&indices, bits_per_index);
+
+        Self {
+            palette,
+            bits_per_index,
+            packed_indices,
+            expanded: None,
+            dim_order: DO::new(),
+        }
+    }
+
+    pub fn dims(&self) -> &[usize; 3] {
+        DO::dims()
+    }
+
+    /// Builds a deduplicated palette and the per-cell index into it. Used
+    /// by both `new` and `optimize`.
+    fn palettize(values: &[T]) -> (Vec<T>, Vec<u32>) {
+        let mut palette: Vec<T> = Vec::new();
+        let mut indices = Vec::with_capacity(values.len());
+        for value in values {
+            let index = match palette.iter().position(|entry| entry == value) {
+                Some(index) => index,
+                None => {
+                    palette.push(value.clone());
+                    palette.len() - 1
+                }
+            };
+            indices.push(index as u32);
+        }
+        (palette, indices)
+    }
+
+    fn decode_index(&self, cell: usize) -> usize {
+        read_packed(&self.packed_indices, self.bits_per_index, cell) as usize
+    }
+
+    fn cell_value(&self, cell: usize) -> &T {
+        match &self.expanded {
+            Some(expanded) => &expanded[cell],
+            None => &self.palette[self.decode_index(cell)],
+        }
+    }
+
+    fn ensure_expanded(&mut self) {
+        if self.expanded.is_some() {
+            return;
+        }
+        let dims = DO::dims();
+        let total = dims[0] * dims[1] * dims[2];
+        self.expanded = Some((0..total).map(|cell| self.cell_value(cell).clone()).collect());
+    }
+
+    /// Upper bound on the bytes this grid would need stored as a palette
+    /// plus a run-length pass over the packed indices: one run per maximal
+    /// stretch of repeated values, each costing a `u32` run length plus a
+    /// packed index.
+    pub fn compressed_len(&self) -> usize {
+        let dims = DO::dims();
+        let total = dims[0] * dims[1] * dims[2];
+
+        let mut runs = 0_usize;
+        let mut prev: Option<&T> = None;
+        for cell in 0..total {
+            let value = self.cell_value(cell);
+            if prev != Some(value) {
+                runs += 1;
+                prev = Some(value);
+            }
+        }
+
+        let index_bytes = ((self.bits_per_index as usize + 7) / 8).max(1);
+        std::mem::size_of::<T>() * self.palette.len() + runs * (std::mem::size_of::<u32>() + index_bytes)
+    }
+
+    /// Re-palettes and re-packs from the current values (dropping any
+    /// palette entries no edits left in use), and drops `expanded` if
+    /// `index_mut` had forced a decompression. Call after a batch of edits,
+    /// e.g. once a chunk modification settles down.
+    pub fn optimize(&mut self) {
+        let dims = DO::dims();
+        let total = dims[0] * dims[1] * dims[2];
+        let values: Vec<T> = (0..total).map(|cell| self.cell_value(cell).clone()).collect();
+
+        let (palette, indices) = Self::palettize(&values);
+        self.bits_per_index = bits_for_palette_len(palette.len());
+        self.packed_indices = pack_indices(&indices, self.bits_per_index);
+        self.palette = palette;
+        self.expanded = None;
+    }
+}
+
+impl<T: Clone + PartialEq, DO: DimOrder> Index<[usize; 3]> for PalettedVec3d<T, DO> {
+    type Output = T;
+
+    fn index(&self, index: [usize; 3]) -> &T {
+        let [x, y, z] = index;
+        self.cell_value(self.dim_order.array_index(x, y, z))
+    }
+}
+
+impl<T: Clone + PartialEq, DO: DimOrder> IndexMut<[usize; 3]> for PalettedVec3d<T, DO> {
+    fn index_mut(&mut self, index: [usize; 3]) -> &mut T {
+        let [x, y, z] = index;
+        let cell = self.dim_order.array_index(x, y, z);
+        self.ensure_expanded();
+        &mut self.expanded.as_mut().unwrap()[cell]
+    }
+}
+
 #[derive(Debug)]
 pub struct Vec2d<T> {
     pub vec: Vec<T>,
@@ -22,6 +22,40 @@ pub struct QuadListRenderData {
     pub index_data: Vec<u16>,
 }
 
+/// Per-instance data for the instanced cuboid rendering path: a model matrix
+/// that maps the shared unit-cube mesh (see `Vertex::unit_cube_data`) onto
+/// one cuboid's world-space extent. Kept separate from `instance::InstanceRaw`
+/// (which carries per-block texture/lighting attributes for the voxel world)
+/// since debug cuboids like the light volume or character entity bounds only
+/// need a transform.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable, Debug)]
+pub struct CuboidInstanceRaw {
+    model: [[f32; 4]; 4],
+}
+
+impl CuboidInstanceRaw {
+    pub fn new(cc: &CuboidCoords) -> Self {
+        let translation = glam::Mat4::from_translation(Vec3::new(cc.left, cc.bottom, cc.near));
+        let scale = glam::Mat4::from_scale(Vec3::new(
+            cc.right - cc.left,
+            cc.top - cc.bottom,
+            cc.far - cc.near,
+        ));
+        Self {
+            model: (translation * scale).to_cols_array_2d(),
+        }
+    }
+}
+
+/// Builds one `CuboidInstanceRaw` per cuboid, encoding each cuboid's
+/// translation/scale as a model matrix. The projection and view stay in the
+/// shader's view-proj uniform, so this buffer only needs to be re-uploaded
+/// when a cuboid's extent changes, not every time the camera moves.
+pub fn build_instance_buffer(cuboids: &[CuboidCoords]) -> Vec<CuboidInstanceRaw> {
+    cuboids.iter().map(CuboidInstanceRaw::new).collect()
+}
+
 impl Vertex {
     pub fn new(pos: [i8; 3], tc: [i8; 2]) -> Self {
         Self {
@@ -62,6 +96,65 @@ impl Vertex {
         }
     }
 
+    /// Vertex buffer layout for a `CuboidInstanceRaw` instance buffer: the
+    /// 4x4 model matrix as four `Float32x4` columns at shader locations 2-5,
+    /// stepped once per instance rather than once per vertex.
+    pub fn instance_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        use std::mem;
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<CuboidInstanceRaw>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: 0,
+                    shader_location: 2,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 3,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    shader_location: 4,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: mem::size_of::<[f32; 12]>() as wgpu::BufferAddress,
+                    shader_location: 5,
+                },
+            ],
+        }
+    }
+
+    /// Builds the unit cube's 24 vertices and 36 indices ONCE, unprojected
+    /// (spanning `[0, 1]` on every axis). Every instanced cuboid reuses this
+    /// same `QuadListRenderData` as its vertex/index buffer; per-instance
+    /// placement comes from the `CuboidInstanceRaw` model matrix instead of
+    /// re-baking the geometry, so the vertex buffer stays constant as the
+    /// camera moves.
+    pub fn unit_cube_data() -> QuadListRenderData {
+        let mut quad_data = QuadListRenderData {
+            vertex_data: Vec::new(),
+            index_data: Vec::new(),
+        };
+        Vertex::generate_quad_data_for_cuboid(
+            &CuboidCoords {
+                left: 0.0,
+                right: 1.0,
+                bottom: 0.0,
+                top: 1.0,
+                near: 0.0,
+                far: 1.0,
+            },
+            None,
+            &mut quad_data,
+        );
+        quad_data
+    }
+
     pub fn generate_quad_data(
         quads: &Vec<[glam::Vec3; 4]>,
         maybe_projection: Option<glam::Mat4>,
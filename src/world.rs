@@ -1,5 +1,9 @@
 use crate::camera::Camera;
-use crate::game_loop::GameLoop;
+use crate::color;
+use crate::events::Events;
+use crate::frustum;
+use crate::input_helper::InputHelper;
+use crate::light;
 use crate::map_generation::{self};
 use crate::vec_extra::{self, Vec2d, Vec3d};
 use crate::vertex::{CuboidCoords, QuadListRenderData, Vertex};
@@ -10,17 +14,23 @@ use winit::event::{ElementState, VirtualKeyCode, WindowEvent};
 
 use nalgebra as na;
 use parry3d::shape::{Cuboid, Cylinder};
+use rayon::prelude::*;
 
-use super::instance::InstanceRaw;
+use super::instance::{InstanceRaw, Transform};
 #[cfg(target_arch = "wasm32")]
 use crate::dom_controls;
+use crate::game_loop::Interpolate;
 use cgmath::{prelude::*, MetricSpace, Point3, Vector3};
 use collision::Continuous;
-use rand::Rng;
-use std::collections::HashSet;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::convert::Into;
 use std::fmt;
 #[cfg(not(target_arch = "wasm32"))]
+use std::sync::{mpsc, Arc, Mutex};
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread;
+#[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
 
 const VERBOSE_LOGS: bool = false;
@@ -50,6 +60,9 @@ pub enum BlockType {
     TreeLeaves4,
     RedFlower,
     OakPlank,
+    Glowstone,
+    Snow,
+    Sapling,
 }
 
 impl fmt::Display for BlockType {
@@ -70,6 +83,9 @@ impl fmt::Display for BlockType {
             BlockType::TreeLeaves4 => write!(f, "TreeLeaves4"),
             BlockType::RedFlower => write!(f, "RedFlower"),
             BlockType::OakPlank => write!(f, "OakPlank"),
+            BlockType::Glowstone => write!(f, "Glowstone"),
+            BlockType::Snow => write!(f, "Snow"),
+            BlockType::Sapling => write!(f, "Sapling"),
         }
     }
 }
@@ -77,37 +93,85 @@ impl fmt::Display for BlockType {
 impl BlockType {
     pub const DEFAULT_PLACE_BLOCK_TYPE: BlockType = BlockType::Stone;
 
+    /// Case-insensitive inverse of `Display`, for parsing block names out of
+    /// user input (e.g. the `/give` chat command).
+    pub fn from_name(name: &str) -> Option<BlockType> {
+        match name.to_lowercase().as_str() {
+            "empty" => Some(BlockType::Empty),
+            "debug" => Some(BlockType::Debug),
+            "dirt" => Some(BlockType::Dirt),
+            "grass" => Some(BlockType::Grass),
+            "sand" => Some(BlockType::Sand),
+            "stone" => Some(BlockType::Stone),
+            "water" => Some(BlockType::Water),
+            "glass" => Some(BlockType::Glass),
+            "tree" => Some(BlockType::Tree),
+            "treeleaves1" => Some(BlockType::TreeLeaves1),
+            "treeleaves2" => Some(BlockType::TreeLeaves2),
+            "treeleaves3" => Some(BlockType::TreeLeaves3),
+            "treeleaves4" => Some(BlockType::TreeLeaves4),
+            "redflower" => Some(BlockType::RedFlower),
+            "oakplank" => Some(BlockType::OakPlank),
+            "glowstone" => Some(BlockType::Glowstone),
+            "snow" => Some(BlockType::Snow),
+            "sapling" => Some(BlockType::Sapling),
+            _ => None,
+        }
+    }
+
     pub fn is_semi_translucent(&self) -> bool {
+        match *self {
+            BlockType::RedFlower => true,
+            BlockType::Sapling => true,
+            _ => false,
+        }
+    }
+
+    /// Binary-transparency blocks: every texel is either fully opaque or
+    /// fully cut out (no partial alpha), so unlike `is_semi_translucent`
+    /// they render with an alpha-test discard rather than a blended,
+    /// distance-sorted draw. `occludes_neighbor` gives them solid-like face
+    /// culling against same-type neighbors while still counting as
+    /// `is_translucent` for lighting.
+    pub fn is_binary_transparent(&self) -> bool {
         match *self {
             BlockType::TreeLeaves1 => true,
             BlockType::TreeLeaves2 => true,
             BlockType::TreeLeaves3 => true,
             BlockType::TreeLeaves4 => true,
-            BlockType::RedFlower => true,
             _ => false,
         }
     }
 
     pub fn is_translucent(&self) -> bool {
         match *self {
-            // BlockType::TreeLeaves1 => true,
-            // BlockType::TreeLeaves2 => true,
-            // BlockType::TreeLeaves3 => true,
-            // BlockType::TreeLeaves4 => true,
-            // BlockType::RedFlower => true,
             BlockType::Empty => true,
             BlockType::Water => true,
             BlockType::Glass => true,
             x if x.is_semi_translucent() => true,
+            x if x.is_binary_transparent() => true,
             _ => false,
         }
     }
 
+    /// Whether placing `self` here hides the shared face on `neighbor`'s
+    /// side of it. Ordinary solids always occlude; binary-transparency
+    /// blocks (leaves) only occlude a same-type neighbor, so a leaf-to-leaf
+    /// face of the same variant is culled like solid geometry while a
+    /// leaf-to-`Empty` or leaf-to-different-leaf-type face still renders.
+    pub fn occludes_neighbor(&self, neighbor: BlockType) -> bool {
+        if !self.is_translucent() {
+            return true;
+        }
+        self.is_binary_transparent() && neighbor.is_binary_transparent() && *self == neighbor
+    }
+
     pub fn is_collidable(&self) -> bool {
         match *self {
             BlockType::Empty => false,
             BlockType::Water => false,
             BlockType::RedFlower => false,
+            BlockType::Sapling => false,
             _ => true,
         }
     }
@@ -115,10 +179,32 @@ impl BlockType {
     pub fn is_sprite(&self) -> bool {
         match *self {
             BlockType::RedFlower => true,
+            BlockType::Sapling => true,
             _ => false,
         }
     }
 
+    /// Color and falloff radius this block emits as a `light::PointLight`
+    /// when placed, or `None` for blocks that don't light up the world.
+    pub fn emitted_light(&self) -> Option<(glam::Vec3, f32)> {
+        match *self {
+            BlockType::Glowstone => Some((glam::Vec3::new(1.0, 0.85, 0.6), 8.0)),
+            _ => None,
+        }
+    }
+
+    /// Block-light value (0-15) this block type seeds into
+    /// `WorldState`'s light propagation BFS. Distinct from `emitted_light`,
+    /// which drives the separate dynamic `light::PointLight` rendering path
+    /// -- this feeds the per-block voxel light channel the mesher reads for
+    /// face brightness (see `Block::light_level`).
+    pub fn light_emission(&self) -> u8 {
+        match *self {
+            BlockType::Glowstone => 15,
+            _ => 0,
+        }
+    }
+
     pub fn random_tree_leaf() -> BlockType {
         *[
             Self::TreeLeaves1,
@@ -147,9 +233,53 @@ impl BlockType {
             BlockType::TreeLeaves3 => [[0.0, 4.0], [0.0, 4.0], [0.0, 4.0]],
             BlockType::TreeLeaves4 => [[1.0, 3.0], [1.0, 3.0], [1.0, 3.0]],
             BlockType::RedFlower => [[2.0, 2.0], [2.0, 2.0], [2.0, 2.0]],
+            BlockType::Glowstone => [[3.0, 1.0], [3.0, 1.0], [3.0, 1.0]],
+            BlockType::Snow => [[3.0, 2.0], [2.0, 0.0], [3.0, 2.0]],
+            BlockType::Sapling => [[3.0, 3.0], [3.0, 3.0], [3.0, 3.0]],
             _ => [[0.0, 0.0], [0.0, 0.0], [0.0, 0.0]],
         }
     }
+
+    // top, bottom, sides -- mirrors `texture_atlas_offsets`, since a tint
+    // multiplies whichever tile that offset points at.
+    fn tint_types(&self) -> [TintType; 3] {
+        match self {
+            BlockType::Grass => [TintType::Grass, TintType::Default, TintType::Default],
+            BlockType::TreeLeaves1
+            | BlockType::TreeLeaves2
+            | BlockType::TreeLeaves3
+            | BlockType::TreeLeaves4 => [TintType::Foliage; 3],
+            _ => [TintType::Default; 3],
+        }
+    }
+}
+
+/// How a block face's sampled texel gets recolored before it's written to
+/// the vertex color -- `Default` leaves the atlas texture untouched, while
+/// `Grass`/`Foliage` pull a per-biome, per-elevation multiplier out of
+/// `tint_multiplier` (grass and leaf textures are authored grayscale in the
+/// atlas specifically so they can be recolored this way, the same trick
+/// Minecraft's own biome coloring uses). `Fixed` is an escape hatch for a
+/// future block that wants one constant tint regardless of biome.
+///
+/// `mesh_chunk` bakes the resolved multiplier into the face's existing
+/// `InstanceRaw.color_adjust` rather than a dedicated `Vertex.color`
+/// attribute -- block terrain is instanced unit geometry (see `mesh_chunk`'s
+/// doc comment), not the per-vertex `vertex::Vertex` buffer greedy-meshed
+/// quads would use, and `color_adjust` already is this renderer's per-face
+/// color-multiply channel (it's what the existing ambient shading constants
+/// below multiply through).
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum TintType {
+    Default,
+    Grass,
+    Foliage,
+    #[allow(dead_code)]
+    Fixed {
+        r: f32,
+        g: f32,
+        b: f32,
+    },
 }
 
 #[derive(Clone, Copy, PartialEq)]
@@ -163,10 +293,17 @@ enum Face {
     Back = 5,
 }
 
+/// Result of `WorldState::raycast_voxel` -- the first collidable block a ray
+/// pierces, the point it pierced it at, and which face it came in through.
+/// `face_normal` is derived straight from the DDA traversal (whichever axis
+/// was stepped to enter this voxel, negated), not inferred after the fact
+/// from `collision_point`'s coordinates, so it's exact even when the ray
+/// grazes an edge or corner.
 pub struct BlockCollision {
-    distance: f32,
-    block_pos: cgmath::Point3<usize>,
-    collision_point: cgmath::Point3<f32>,
+    pub distance: f32,
+    pub block_pos: cgmath::Point3<usize>,
+    pub collision_point: cgmath::Point3<f32>,
+    pub face_normal: Vector3<f32>,
 }
 
 #[derive(Copy, Clone)]
@@ -194,12 +331,24 @@ impl NeighborBitmap {
 struct Block {
     block_type: BlockType,
     neighbors: NeighborBitmap, // top (+y), bottom (-y), left (+x), right (-x), front (+z), back (-z)
+    /// Light channels maintained by `WorldState`'s light-propagation BFS
+    /// (`seed_world_light`/`relight_block`), each a 0-15 level: `sky_light`
+    /// reaches down from open sky, `block_light` spreads from emissive
+    /// blocks (see `BlockType::light_emission`).
+    sky_light: u8,
+    block_light: u8,
 }
 
 impl Block {
     pub fn is_empty(&self) -> bool {
         self.block_type == BlockType::Empty
     }
+
+    /// Combined light level (0-15) the mesher shades this block's faces
+    /// with -- the brighter of its two light channels.
+    pub fn light_level(&self) -> u8 {
+        self.sky_light.max(self.block_light)
+    }
 }
 
 pub const CHUNK_XZ_SIZE: usize = 16;
@@ -250,6 +399,8 @@ impl Default for Block {
         Block {
             block_type: BlockType::Empty,
             neighbors: NeighborBitmap::new(),
+            sky_light: 0,
+            block_light: 0,
         }
     }
 }
@@ -269,6 +420,10 @@ pub enum ChunkDataType {
     // Still generates a shadow
     SemiTranslucent,
     TranslucentAndSemiTranslucent,
+    // Alpha-tested like `Opaque` (depth writes, no blend sorting), but kept
+    // in its own bucket since it culls same-type faces instead of always
+    // culling against solids -- see `BlockType::occludes_neighbor`.
+    BinaryTransparency,
 }
 
 #[derive(Clone)]
@@ -286,35 +441,101 @@ pub struct ChunkData {
     pub typed_instances_vec: Vec<TypedInstances>,
 }
 
+type ChunkBlocks = Vec3d<Block, vec_extra::XYZ<CHUNK_XZ_SIZE, CHUNK_Y_SIZE, CHUNK_XZ_SIZE>>;
+
 pub struct Chunk {
     is_generated: bool,
-    blocks: Vec3d<Block, vec_extra::XYZ<CHUNK_XZ_SIZE, CHUNK_Y_SIZE, CHUNK_XZ_SIZE>>,
+    blocks: ChunkBlocks,
     // Index into RenderDescriptor array for rendering this chunk
     pub render_descriptor_idx: usize,
 }
 
-pub struct CharacterEntity {
+/// The position/velocity/acceleration state and collider extents shared by anything
+/// `physics_tick` moves under gravity and block collision -- modeled on the
+/// Position/Velocity/Gravity/Bounds components in stevenarella's ECS. `CharacterEntity` wraps
+/// one of these for the player; mobs or items dropped by `break_block` can carry their own once
+/// something populates `WorldState::dynamic_entities`. Input-driven acceleration (WASD,
+/// jumping) stays out of here -- see `CharacterEntity`'s own fields and `physics_tick`.
+pub struct DynamicEntity {
     pub position: glam::Vec3, // center of the cylinder
     velocity: glam::Vec3,
     acceleration: glam::Vec3,
     pub prev_position: glam::Vec3,
     pub is_underwater: bool,
+    collider_half_extent: f32,
+    collider_half_height: f32,
+}
+
+impl DynamicEntity {
+    fn new(position: glam::Vec3, collider_half_extent: f32, collider_half_height: f32) -> Self {
+        Self {
+            position,
+            velocity: glam::Vec3::ZERO,
+            acceleration: glam::Vec3::ZERO,
+            prev_position: position,
+            is_underwater: false,
+            collider_half_extent,
+            collider_half_height,
+        }
+    }
+
+    fn collider(&self) -> Cylinder {
+        Cylinder::new(self.collider_half_height, self.collider_half_extent)
+    }
+}
+
+pub struct CharacterEntity {
+    pub dynamics: DynamicEntity,
+    /// Ticks since `physics_tick` last saw the character grounded -- drives coyote time (a
+    /// jump is still allowed for a few ticks after walking off a ledge).
+    ticks_since_grounded: u32,
+    /// Ticks remaining in which a jump-button press is still considered "buffered" -- so a
+    /// press slightly before landing still triggers a jump the moment the character grounds.
+    buffered_jump_ticks_remaining: u32,
+    /// Whether the character still has its one air double-jump. Reset to `true` on landing,
+    /// mirroring the `on_floor`/`double_jump` fields in the lyrix character controller.
+    double_jump_available: bool,
 }
 
 impl CharacterEntity {
-    pub fn vertex_data(&self) -> QuadListRenderData {
+    /// `alpha` is `game_loop::GameLoop::blending_factor()` -- the character
+    /// is rendered at `prev_position` blended towards `position` rather than
+    /// snapped straight to `position`, so its motion stays smooth when the
+    /// render rate doesn't line up with the fixed physics step. Blends via
+    /// `instance::Transform`/`Interpolate` rather than a bare `Vec3::lerp`,
+    /// with rotation held at the identity quaternion since the character
+    /// model doesn't yet rotate -- `Transform::lerp` already does the right
+    /// thing (`slerp`) the day it does.
+    pub fn vertex_data(&self, alpha: f64) -> QuadListRenderData {
+        let previous = Transform {
+            position: Vector3::new(
+                self.dynamics.prev_position.x,
+                self.dynamics.prev_position.y,
+                self.dynamics.prev_position.z,
+            ),
+            rotation: cgmath::Quaternion::one(),
+        };
+        let current = Transform {
+            position: Vector3::new(
+                self.dynamics.position.x,
+                self.dynamics.position.y,
+                self.dynamics.position.z,
+            ),
+            rotation: cgmath::Quaternion::one(),
+        };
+        let render_position = previous.lerp(&current, alpha).position;
         let mut result_vertex_data = QuadListRenderData {
             vertex_data: vec![],
             index_data: vec![],
         };
         Vertex::generate_quad_data_for_cuboid(
             &CuboidCoords {
-                left: self.position.x - 0.5,
-                right: self.position.x + 0.5,
-                bottom: self.position.y - 1.0,
-                top: self.position.y + 1.0,
-                near: self.position.z - 0.5,
-                far: self.position.z + 0.5,
+                left: render_position.x - 0.5,
+                right: render_position.x + 0.5,
+                bottom: render_position.y - 1.0,
+                top: render_position.y + 1.0,
+                near: render_position.z - 0.5,
+                far: render_position.z + 0.5,
             },
             None,
             &mut result_vertex_data,
@@ -323,170 +544,1363 @@ impl CharacterEntity {
     }
 
     pub fn did_move(&self) -> bool {
-        self.position != self.prev_position
+        self.dynamics.position != self.dynamics.prev_position
     }
 }
 
-#[derive(PartialEq, Debug)]
-enum ButtonState {
-    Pressed,
-    Held,
-    Released,
-    Idle,
+/// The semantic movement actions `InputState::movement` tracks, decoupled
+/// from whichever physical key currently drives them -- `apply_queued_input_events`
+/// maps WASD or IJKL onto the same four directions depending on `is_flying`.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+enum MovementKey {
+    Forward,
+    Backward,
+    Left,
+    Right,
+    Jump,
+    /// The crouch/descend key -- only meaningful while `WorldState::is_flying`, where it
+    /// drives downward motion the way `Jump` drives upward motion.
+    Descend,
 }
 
 struct InputState {
-    is_forward_pressed: bool,
-    is_backward_pressed: bool,
-    is_left_pressed: bool,
-    is_right_pressed: bool,
-    jump_button_state: ButtonState,
+    movement: InputHelper<MovementKey>,
     last_joystick_vector: (f64, f64),
     last_translation_joystick_vector: (f64, f64),
 }
 
+/// An input intent translated from a raw `WindowEvent`/`DomControlsUserEvent`
+/// by `process_window_event`/`process_web_dom_button_event`. Queued onto
+/// `WorldState::input_events` rather than applied inline, so native keyboard
+/// events and web DOM events -- which used to race through two separate
+/// entry points mutating `input_state`/`place_block_type`/`is_flying`
+/// directly -- resolve in one deterministic pass. See
+/// `apply_queued_input_events`.
+enum InputEvent {
+    Key {
+        keycode: VirtualKeyCode,
+        pressed: bool,
+    },
+    PitchYawJoystickMoved {
+        vector: (f64, f64),
+    },
+    PitchYawJoystickReleased,
+    TranslationJoystickMoved {
+        vector: (f64, f64),
+    },
+    TranslationJoystickReleased,
+    YButtonPressed,
+    YButtonReleased,
+    BlockPreviewPressed,
+}
+
 const DEFAULT_IS_FLYING: bool = false;
 
-pub struct WorldState {
-    pub chunk_indices: Vec2d<u32>,
-    chunks: Vec<Chunk>,
-    highlighted_chunk: Option<[usize; 2]>,
-    highlighted_block: Option<[usize; 3]>,
+/// The two independent light channels `WorldState`'s propagation BFS
+/// maintains per block -- see `Block::sky_light`/`Block::block_light`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum LightChannel {
+    Sky,
+    Block,
+}
 
-    pub character_entity: CharacterEntity,
-    pub place_block_type: BlockType,
-    input_state: InputState,
+/// Brightest a light channel can be -- the sun, or a full-strength emissive
+/// block.
+const MAX_LIGHT_LEVEL: u8 = 15;
+/// How much a light level drops crossing one translucent block. Crossing a
+/// non-translucent block drops it straight to zero instead (see
+/// `WorldState::propagate_light_increase`).
+const LIGHT_ATTENUATION: u8 = 1;
+
+/// How a `QueuedBlock` should treat whatever block is already at its
+/// position when it's finally applied.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OverwriteMode {
+    /// Set the block regardless of what's there.
+    Always,
+    /// Only set the block if the existing one is `BlockType::Empty` --
+    /// e.g. leaves shouldn't clobber a trunk that grew into the same cell.
+    IfEmpty,
+}
 
-    pub is_flying: bool,
+/// A block placement deferred because it targets a chunk that doesn't exist
+/// yet -- see `WorldState::queue_or_set_block`.
+struct QueuedBlock {
+    world_pos: [usize; 3],
+    block_type: BlockType,
+    overwrite_mode: OverwriteMode,
 }
 
-macro_rules! set_block {
-    ($self:ident, $x:expr, $y:expr, $z:expr, $block_type:expr) => {
-        $self.set_block($x, $y, $z, $block_type, false)
-    };
-    ($self:ident, $x:expr, $y:expr, $z:expr, $block_type:expr, $verbose:expr) => {
-        $self.set_block($x, $y, $z, $block_type, $verbose)
-    };
+/// A chunk `ChunkGenPool` has been asked to generate.
+struct GenRequest([usize; 2]);
+
+/// The result of running `generate_chunk_blocks` for `chunk_idx`: an
+/// isolated block grid with no neighbor-chunk or lighting data baked in yet
+/// (see `WorldState::install_generated_chunk`), plus any tree/flower
+/// placements that spilled past `chunk_idx`'s own borders into a chunk the
+/// worker had no access to.
+struct GeneratedChunk {
+    chunk_idx: [usize; 2],
+    blocks: ChunkBlocks,
+    overflow: Vec<QueuedBlock>,
 }
 
-impl WorldState {
-    pub fn new() -> Self {
-        let world_center = get_world_center();
+/// How many threads pull `GenRequest`s off the shared queue. Generation is
+/// CPU-bound and embarrassingly parallel across chunks, so this is sized
+/// for throughput rather than tied to any particular hardware; four keeps a
+/// few workers busy without the pool itself becoming a bottleneck.
+#[cfg(not(target_arch = "wasm32"))]
+const CHUNK_GEN_WORKER_COUNT: usize = 4;
+
+/// Runs chunk terrain generation (`generate_chunk_blocks`) off the main
+/// thread so crossing into new terrain doesn't stall a frame. On wasm32,
+/// where `std::thread` isn't available, `tick` instead runs one queued
+/// request synchronously per call -- same eventual result, spread across
+/// frames instead of across cores.
+///
+/// `generate_chunk_blocks` is safe to run from any of this pool's worker
+/// threads unsynchronized: it builds its own `GenContext::rng` from
+/// `chunk_seed` and its own `map_generation::terrain_noise` from
+/// `WORLD_SEED`, so there's no shared mutable state for two workers to race
+/// on, and no dependence on which thread or order a chunk generates in.
+struct ChunkGenPool {
+    #[cfg(not(target_arch = "wasm32"))]
+    request_tx: mpsc::Sender<GenRequest>,
+    #[cfg(not(target_arch = "wasm32"))]
+    result_rx: mpsc::Receiver<GeneratedChunk>,
+    #[cfg(not(target_arch = "wasm32"))]
+    _workers: Vec<thread::JoinHandle<()>>,
+
+    #[cfg(target_arch = "wasm32")]
+    pending: VecDeque<GenRequest>,
+}
 
-        // let GRAVITY_ACCELERATION = glam::Vec3::new(0.0, -0.0005, 0.0);
+impl ChunkGenPool {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn new() -> Self {
+        let (request_tx, request_rx) = mpsc::channel::<GenRequest>();
+        let request_rx = Arc::new(Mutex::new(request_rx));
+        let (result_tx, result_rx) = mpsc::channel::<GeneratedChunk>();
+
+        let workers = (0..CHUNK_GEN_WORKER_COUNT)
+            .map(|_| {
+                let request_rx = Arc::clone(&request_rx);
+                let result_tx = result_tx.clone();
+                thread::spawn(move || {
+                    // Exits once every `request_tx`/`result_tx` clone is
+                    // dropped, i.e. when `WorldState` itself goes away.
+                    while let Ok(GenRequest(chunk_idx)) = request_rx.lock().unwrap().recv() {
+                        if result_tx.send(generate_chunk_blocks(chunk_idx)).is_err() {
+                            break;
+                        }
+                    }
+                })
+            })
+            .collect();
 
-        let initial_pos = glam::Vec3::new(
-            world_center.x as f32 - 20.0,
-            world_center.y as f32 + 10.0,
-            world_center.z as f32 - 20.0,
-        );
+        ChunkGenPool {
+            request_tx,
+            result_rx,
+            _workers: workers,
+        }
+    }
 
-        let character_entity = CharacterEntity {
-            position: initial_pos,
-            velocity: glam::Vec3::new(0.0, 0.0, 0.0),
-            acceleration: glam::Vec3::new(0.0, 0.0, 0.0),
-            prev_position: initial_pos,
-            is_underwater: false,
-        };
+    #[cfg(target_arch = "wasm32")]
+    fn new() -> Self {
+        ChunkGenPool {
+            pending: VecDeque::new(),
+        }
+    }
 
-        Self {
-            chunk_indices: Vec2d::new(
-                vec![CHUNK_DOES_NOT_EXIST_VALUE; MAX_CHUNK_WORLD_WIDTH * MAX_CHUNK_WORLD_WIDTH],
-                [MAX_CHUNK_WORLD_WIDTH, MAX_CHUNK_WORLD_WIDTH],
-            ),
-            chunks: vec![],
-            highlighted_chunk: None,
-            highlighted_block: None,
-            character_entity,
-            place_block_type: BlockType::DEFAULT_PLACE_BLOCK_TYPE,
-            input_state: InputState {
-                is_forward_pressed: false,
-                is_backward_pressed: false,
-                is_left_pressed: false,
-                is_right_pressed: false,
-                jump_button_state: ButtonState::Idle,
-                last_joystick_vector: (0.0, 0.0),
-                last_translation_joystick_vector: (0.0, 0.0),
-            },
-            is_flying: DEFAULT_IS_FLYING,
+    fn request(&mut self, chunk_idx: [usize; 2]) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            // A worker only hangs up if it's panicked; surface that instead
+            // of silently dropping the generation request.
+            self.request_tx.send(GenRequest(chunk_idx)).unwrap();
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            self.pending.push_back(GenRequest(chunk_idx));
         }
     }
 
-    fn get_chunk_mut(&mut self, chunk_idx: [usize; 2]) -> &mut Chunk {
-        let chunk_idx = self.chunk_indices[chunk_idx];
-        &mut self.chunks[chunk_idx as usize]
+    /// Drains whatever's finished generating since the last call.
+    fn tick(&mut self) -> Vec<GeneratedChunk> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.result_rx.try_iter().collect()
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            match self.pending.pop_front() {
+                Some(GenRequest(chunk_idx)) => vec![generate_chunk_blocks(chunk_idx)],
+                None => vec![],
+            }
+        }
     }
+}
 
-    fn get_chunk(&self, chunk_idx: [usize; 2]) -> &Chunk {
-        let chunk_idx = self.chunk_indices[chunk_idx];
-        &self.chunks[chunk_idx as usize]
+/// An owned snapshot of everything `mesh_chunk` needs for `chunk_idx`, so a
+/// `ChunkMeshPool` worker can mesh it without borrowing `WorldState` across
+/// the thread boundary -- see `WorldState::dispatch_chunk_mesh`.
+struct MeshRequest {
+    chunk_idx: [usize; 2],
+    blocks: ChunkBlocks,
+    camera_eye: Point3<f32>,
+}
+
+/// How many threads pull `MeshRequest`s off the shared queue. Meshing is
+/// more CPU-bound per chunk than generation (it walks every block and sorts
+/// several instance lists), so it gets double `CHUNK_GEN_WORKER_COUNT`'s
+/// worker count to keep up with dirty chunks arriving from camera movement.
+#[cfg(not(target_arch = "wasm32"))]
+const CHUNK_MESH_WORKER_COUNT: usize = 8;
+
+/// Runs `mesh_chunk` off the main thread so scrolling into new chunks
+/// doesn't stall a frame -- the `update_tick` counterpart to `ChunkGenPool`.
+/// On wasm32, `tick` instead meshes one queued request synchronously per
+/// call, same as `ChunkGenPool` falls back to there.
+struct ChunkMeshPool {
+    #[cfg(not(target_arch = "wasm32"))]
+    request_tx: mpsc::Sender<MeshRequest>,
+    #[cfg(not(target_arch = "wasm32"))]
+    result_rx: mpsc::Receiver<ChunkData>,
+    #[cfg(not(target_arch = "wasm32"))]
+    _workers: Vec<thread::JoinHandle<()>>,
+
+    #[cfg(target_arch = "wasm32")]
+    pending: VecDeque<MeshRequest>,
+}
+
+impl ChunkMeshPool {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn new() -> Self {
+        let (request_tx, request_rx) = mpsc::channel::<MeshRequest>();
+        let request_rx = Arc::new(Mutex::new(request_rx));
+        let (result_tx, result_rx) = mpsc::channel::<ChunkData>();
+
+        let workers = (0..CHUNK_MESH_WORKER_COUNT)
+            .map(|_| {
+                let request_rx = Arc::clone(&request_rx);
+                let result_tx = result_tx.clone();
+                thread::spawn(move || {
+                    // Exits once every `request_tx`/`result_tx` clone is
+                    // dropped, i.e. when `WorldState` itself goes away.
+                    while let Ok(request) = request_rx.lock().unwrap().recv() {
+                        let chunk_data =
+                            mesh_chunk(request.chunk_idx, &request.blocks, request.camera_eye);
+                        if result_tx.send(chunk_data).is_err() {
+                            break;
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        ChunkMeshPool {
+            request_tx,
+            result_rx,
+            _workers: workers,
+        }
     }
 
-    fn get_block(&self, x: usize, y: usize, z: usize) -> &Block {
-        let chunk_idx = self.chunk_indices[[x / CHUNK_XZ_SIZE, z / CHUNK_XZ_SIZE]];
-        let chunk = &self.chunks[chunk_idx as usize];
-        chunk
-            .blocks
-            .get_unchecked(x % CHUNK_XZ_SIZE, y, z % CHUNK_XZ_SIZE)
+    #[cfg(target_arch = "wasm32")]
+    fn new() -> Self {
+        ChunkMeshPool {
+            pending: VecDeque::new(),
+        }
     }
 
-    fn set_block(
+    fn request(&mut self, request: MeshRequest) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            // A worker only hangs up if it's panicked; surface that instead
+            // of silently dropping the mesh request.
+            self.request_tx.send(request).unwrap();
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            self.pending.push_back(request);
+        }
+    }
+
+    /// Drains whatever's finished meshing since the last call.
+    fn tick(&mut self) -> Vec<ChunkData> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.result_rx.try_iter().collect()
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            match self.pending.pop_front() {
+                Some(request) => vec![mesh_chunk(
+                    request.chunk_idx,
+                    &request.blocks,
+                    request.camera_eye,
+                )],
+                None => vec![],
+            }
+        }
+    }
+}
+
+/// A fixed root seed combined with a chunk's own coordinates to derive that
+/// chunk's `GenContext::rng` (see `chunk_seed`), and passed straight through
+/// to `map_generation::generate_chunk_elevation_map`/`generate_chunk_biome_map`
+/// for the terrain/biome noise field. The same constant driving both is what
+/// makes the whole pipeline -- RNG decoration and noise-based terrain alike
+/// -- reproduce identically across runs and across whichever `ChunkGenPool`
+/// worker thread happens to generate a given chunk. Bumping this reshuffles
+/// the whole world; it isn't meant to be player-configurable (yet).
+const WORLD_SEED: u64 = 0x6D_69_6E_65_63_72_75_73;
+
+/// Combines `WORLD_SEED` with a chunk's coordinates into the seed for that
+/// chunk's `GenContext::rng`, so every `GenStage` a chunk runs draws from
+/// the same deterministic stream regardless of generation order -- a chunk
+/// generated before or after its neighbors, or on a different thread,
+/// always comes out identical.
+fn chunk_seed([chunk_x, chunk_z]: [usize; 2]) -> u64 {
+    WORLD_SEED
+        .wrapping_mul(0x9E3779B97F4A7C15)
+        .wrapping_add((chunk_x as u64) << 32 | chunk_z as u64)
+}
+
+/// Per-chunk state threaded through the `GenStage` pipeline: an isolated
+/// block grid (no access to neighboring chunks or `WorldState`, so the
+/// pipeline can run on a worker thread -- see `ChunkGenPool`), the overflow
+/// queue for writes that spill past this chunk's own borders, and the
+/// elevation map and RNG stages need to stay consistent with each other.
+struct GenContext {
+    chunk_idx: [usize; 2],
+    base_x: usize,
+    base_z: usize,
+    elevation_map: [[u16; CHUNK_XZ_SIZE]; CHUNK_XZ_SIZE],
+    biome_map: [[map_generation::Biome; CHUNK_XZ_SIZE]; CHUNK_XZ_SIZE],
+    blocks: ChunkBlocks,
+    overflow: Vec<QueuedBlock>,
+    rng: rand::rngs::StdRng,
+}
+
+impl GenContext {
+    /// The "set block if empty" primitive generation stages need: if
+    /// `world_pos` falls inside this chunk, apply `overwrite_mode` and set
+    /// it directly; otherwise it's spilled past this chunk's own borders
+    /// (tree leaves, a cave mouth opening into a neighbor), so queue it in
+    /// `overflow` to be applied once that neighbor chunk is installed -- see
+    /// `WorldState::install_generated_chunk`.
+    fn set(
         &mut self,
         world_x: usize,
         y: usize,
         world_z: usize,
-        mut block_type: BlockType,
-        verbose: bool,
+        block_type: BlockType,
+        overwrite_mode: OverwriteMode,
     ) {
-        unsafe {
-            let [chunk_x, chunk_z] = [world_x / CHUNK_XZ_SIZE, world_z / CHUNK_XZ_SIZE];
-            let (x, z) = (world_x % CHUNK_XZ_SIZE, world_z % CHUNK_XZ_SIZE);
-
-            let this_block = self
-                .get_chunk_mut([chunk_x, chunk_z])
+        if (self.base_x..self.base_x + CHUNK_XZ_SIZE).contains(&world_x)
+            && (self.base_z..self.base_z + CHUNK_XZ_SIZE).contains(&world_z)
+        {
+            let block = self
                 .blocks
-                .get_raw_ptr_mut(x, y, z);
-
-            #[derive(Clone, Copy)]
-            struct Neighbor {
-                block: *mut Block,
-                this_shared_face: Face,
-                other_shared_face: Face,
+                .get_unchecked_mut(world_x - self.base_x, y, world_z - self.base_z);
+            if overwrite_mode == OverwriteMode::IfEmpty && !block.is_empty() {
+                return;
             }
+            block.block_type = block_type;
+        } else {
+            self.overflow.push(QueuedBlock {
+                world_pos: [world_x, y, world_z],
+                block_type,
+                overwrite_mode,
+            });
+        }
+    }
 
-            let mut neighbors: [Option<Neighbor>; 6] = [None; 6];
+    /// Local-only read counterpart of `set`: `None` outside this chunk's own
+    /// bounds, since a stage carving terrain (`Caves`, `Ravines`) has no way
+    /// to know what a neighbor chunk's blocks will be yet.
+    fn get_local(&self, world_x: usize, y: usize, world_z: usize) -> Option<&Block> {
+        if (self.base_x..self.base_x + CHUNK_XZ_SIZE).contains(&world_x)
+            && (self.base_z..self.base_z + CHUNK_XZ_SIZE).contains(&world_z)
+            && y < CHUNK_Y_SIZE
+        {
+            Some(
+                self.blocks
+                    .get_unchecked(world_x - self.base_x, y, world_z - self.base_z),
+            )
+        } else {
+            None
+        }
+    }
+}
 
-            if y < CHUNK_Y_SIZE - 1 {
-                neighbors[0] = Some(Neighbor {
-                    block: self
-                        .get_chunk_mut([chunk_x, chunk_z])
-                        .blocks
-                        .get_raw_ptr_mut(x, y + 1, z),
-                    this_shared_face: Face::Top,
-                    other_shared_face: Face::Bottom,
-                });
+/// One stage of the terrain generation pipeline `generate_chunk_blocks` runs
+/// in order. Every stage reads and writes only `ctx`'s isolated block grid
+/// and RNG, never `WorldState` directly, so the pipeline as a whole stays
+/// worker-thread-safe and deterministic from `(WORLD_SEED, chunk_x,
+/// chunk_z)` -- see `GenContext`, `chunk_seed`.
+trait GenStage {
+    fn apply(&self, ctx: &mut GenContext);
+}
+
+/// Computes this chunk's elevation map from world-space noise and stashes it
+/// in `ctx` for `Composition` (and anything later that cares where the
+/// ground is, like `Decoration`'s tree/flower placement).
+struct HeightGen;
+
+impl GenStage for HeightGen {
+    fn apply(&self, ctx: &mut GenContext) {
+        ctx.elevation_map = map_generation::generate_chunk_elevation_map(
+            WORLD_SEED,
+            map_generation::TerrainParams::DEFAULT,
+            ctx.chunk_idx,
+            MIN_HEIGHT,
+            MAX_HEIGHT,
+        );
+    }
+}
+
+/// Classifies each column's biome from low-frequency elevation/moisture
+/// noise (see `map_generation::sample_biome`) and stashes it in `ctx` for
+/// `Composition` (surface/filler block choice) and `Decoration` (tree/flower
+/// density and species).
+struct BiomeGen;
+
+impl GenStage for BiomeGen {
+    fn apply(&self, ctx: &mut GenContext) {
+        ctx.biome_map = map_generation::generate_chunk_biome_map(WORLD_SEED, ctx.chunk_idx);
+    }
+}
+
+/// Fills each column below `HeightGen`'s elevation map with sand up to
+/// `WATER_HEIGHT` (or the ground, if lower), stone above that, a
+/// biome-dependent surface/filler cap at the very top (see
+/// `surface_blocks_for_biome`; sand for a surface below `WATER_HEIGHT`), and
+/// water in any column still empty below `WATER_HEIGHT`.
+struct Composition;
+
+/// Depth below the surface at which a column's filler switches from dirt to
+/// stone -- shallow enough that `Caves`/`Ravines` mostly carve through
+/// stone rather than dirt.
+const STONE_DEPTH: usize = 4;
+
+/// The (surface, subsurface-filler) block pair a biome caps its terrain
+/// with above `WATER_HEIGHT` -- desert/beach/ocean are sand all the way
+/// down, tundra/snow keep a dirt filler under a snow cap, and
+/// grassland/forest are both grass-on-dirt (they only differ in decoration
+/// density/species).
+fn surface_blocks_for_biome(biome: map_generation::Biome) -> (BlockType, BlockType) {
+    use map_generation::Biome;
+    match biome {
+        Biome::Ocean | Biome::Beach | Biome::Desert => (BlockType::Sand, BlockType::Sand),
+        Biome::Tundra | Biome::Snow => (BlockType::Snow, BlockType::Dirt),
+        Biome::Grassland | Biome::Forest => (BlockType::Grass, BlockType::Dirt),
+    }
+}
+
+/// Majority-vote biome over the 3x3 neighborhood around `(x, z)` (clamped
+/// to `biome_map`'s own bounds, i.e. this chunk's columns), so the surface
+/// block choice doesn't flip outright across a single-column biome seam.
+/// Ties favor `(x, z)`'s own biome.
+fn smoothed_biome(
+    biome_map: &[[map_generation::Biome; CHUNK_XZ_SIZE]; CHUNK_XZ_SIZE],
+    x: usize,
+    z: usize,
+) -> map_generation::Biome {
+    use map_generation::ALL_BIOMES;
+    let mut counts = [0u8; ALL_BIOMES.len()];
+    for (dx, dz) in iproduct!(-1i64..=1, -1i64..=1) {
+        let (nx, nz) = (x as i64 + dx, z as i64 + dz);
+        if nx < 0 || nz < 0 || nx as usize >= CHUNK_XZ_SIZE || nz as usize >= CHUNK_XZ_SIZE {
+            continue;
+        }
+        let neighbor = biome_map[nx as usize][nz as usize];
+        counts[ALL_BIOMES.iter().position(|b| *b == neighbor).unwrap()] += 1;
+    }
+    let own_idx = ALL_BIOMES
+        .iter()
+        .position(|b| *b == biome_map[x][z])
+        .unwrap();
+    let (best_idx, _) = counts
+        .iter()
+        .enumerate()
+        .max_by_key(|(idx, count)| (**count, *idx == own_idx))
+        .unwrap();
+    ALL_BIOMES[best_idx]
+}
+
+impl GenStage for Composition {
+    fn apply(&self, ctx: &mut GenContext) {
+        for (z, x) in iproduct!(0..CHUNK_XZ_SIZE, 0..CHUNK_XZ_SIZE) {
+            let ground_elevation = ctx.elevation_map[x][z] as usize;
+            let (world_x, world_z) = (ctx.base_x + x, ctx.base_z + z);
+            let (surface, subsurface) = surface_blocks_for_biome(smoothed_biome(&ctx.biome_map, x, z));
+            let top_block_type = if ground_elevation < WATER_HEIGHT as usize {
+                BlockType::Sand
+            } else {
+                surface
+            };
+            ctx.set(
+                world_x,
+                ground_elevation,
+                world_z,
+                top_block_type,
+                OverwriteMode::Always,
+            );
+
+            let min_ground_or_water = core::cmp::min(ground_elevation, WATER_HEIGHT as usize);
+            for y in 0..min_ground_or_water {
+                ctx.set(world_x, y, world_z, BlockType::Sand, OverwriteMode::Always);
             }
-            if y > 0 {
-                neighbors[1] = Some(Neighbor {
-                    block: self
-                        .get_chunk_mut([chunk_x, chunk_z])
-                        .blocks
-                        .get_raw_ptr_mut(x, y - 1, z),
-                    this_shared_face: Face::Bottom,
-                    other_shared_face: Face::Top,
-                });
+            let stone_top = ground_elevation.saturating_sub(STONE_DEPTH).max(min_ground_or_water);
+            for y in min_ground_or_water..stone_top {
+                ctx.set(world_x, y, world_z, BlockType::Stone, OverwriteMode::Always);
             }
+            for y in stone_top..ground_elevation {
+                ctx.set(world_x, y, world_z, subsurface, OverwriteMode::Always);
+            }
+            for y in (MIN_HEIGHT as usize)..(WATER_HEIGHT as usize) {
+                ctx.set(world_x, y, world_z, BlockType::Water, OverwriteMode::IfEmpty);
+            }
+        }
+    }
+}
 
-            neighbors[2] = Some(Neighbor {
-                block: if x < CHUNK_XZ_SIZE - 1 {
-                    self.get_chunk_mut([chunk_x, chunk_z])
-                        .blocks
-                        .get_raw_ptr_mut(x + 1, y, z)
-                } else {
-                    self.get_chunk_mut([chunk_x + 1, chunk_z])
-                        .blocks
-                        .get_raw_ptr_mut(0, y, z)
-                },
+/// Carves narrow, noise-driven tunnels and overhangs through this chunk's
+/// solid terrain: unlike `Caves`/`Ravines` below, which walk a random path
+/// and bite out spheres/ellipsoids along it, this reads a pair of 3D
+/// `OpenSimplex` fields per voxel (see
+/// `map_generation::generate_chunk_cave_mask`) and carves wherever both
+/// fields sit near their zero isosurface. Shape and connectivity fall
+/// straight out of the noise instead of a stepped walk, which is what lets
+/// it produce arches and overhangs the other two rarely do.
+struct NoiseCaves;
+
+impl GenStage for NoiseCaves {
+    fn apply(&self, ctx: &mut GenContext) {
+        // No water guard needed here the way `carve_ellipsoid` has one:
+        // `generate_chunk_cave_mask` only ever covers `MIN_HEIGHT..ground_elevation`,
+        // and `Composition` (the stage immediately before this one) always
+        // fills that whole range with solid ground before its own water
+        // fill starts at `ground_elevation` -- there's no water block in
+        // range for this mask to carve through.
+        let carved = map_generation::generate_chunk_cave_mask(
+            WORLD_SEED,
+            map_generation::TerrainParams::DEFAULT,
+            ctx.chunk_idx,
+            &ctx.elevation_map,
+            MIN_HEIGHT,
+        );
+        for (x, y, z) in carved {
+            let (world_x, world_z) = (ctx.base_x + x, ctx.base_z + z);
+            ctx.set(world_x, y, world_z, BlockType::Empty, OverwriteMode::Always);
+        }
+    }
+}
+
+/// Carves a handful of tunnels through this chunk's solid terrain: each
+/// start point does a random walk of spherical "bites" (radius ~2-4),
+/// nudging its heading by a small random angular delta each step with a
+/// slight downward bias so tunnels trend toward caverns rather than
+/// resurfacing immediately.
+struct Caves;
+
+/// How many cave systems start per chunk. A start point only ever carves
+/// within its own chunk (see `GenContext::get_local`), so this is a density
+/// knob, not a count of tunnels that end up visible -- most wander out of
+/// chunk bounds within a few steps.
+const CAVE_START_COUNT: usize = 2;
+const CAVE_STEPS: usize = 40;
+const CAVE_STEP_LENGTH: f32 = 1.5;
+
+impl GenStage for Caves {
+    fn apply(&self, ctx: &mut GenContext) {
+        for _ in 0..CAVE_START_COUNT {
+            let start_x = ctx.base_x + ctx.rng.gen_range(0..CHUNK_XZ_SIZE);
+            let start_z = ctx.base_z + ctx.rng.gen_range(0..CHUNK_XZ_SIZE);
+            let start_y = ctx.rng.gen_range(MIN_HEIGHT as usize + 8..WATER_HEIGHT as usize);
+            let mut pos = [start_x as f32, start_y as f32, start_z as f32];
+
+            let mut yaw = ctx.rng.gen_range(0.0..std::f32::consts::TAU);
+            let mut pitch = ctx.rng.gen_range(-0.2..0.2);
+            let radius = ctx.rng.gen_range(2.0..4.0);
+
+            for _ in 0..CAVE_STEPS {
+                carve_sphere(ctx, pos, radius);
+
+                yaw += ctx.rng.gen_range(-0.5..0.5);
+                pitch = (pitch + ctx.rng.gen_range(-0.2..0.2) - 0.05).clamp(-0.6, 0.6);
+                pos[0] += yaw.cos() * pitch.cos() * CAVE_STEP_LENGTH;
+                pos[1] += pitch.sin() * CAVE_STEP_LENGTH;
+                pos[2] += yaw.sin() * pitch.cos() * CAVE_STEP_LENGTH;
+            }
+        }
+    }
+}
+
+/// Carves a long, thin, vertically-tall ellipsoid along a mostly-straight
+/// horizontal line -- rarer and more dramatic than `Caves`' wandering
+/// tunnels.
+struct Ravines;
+
+const RAVINE_CHANCE: f32 = 1.0 / 6.0;
+const RAVINE_STEPS: usize = 60;
+const RAVINE_STEP_LENGTH: f32 = 1.5;
+
+impl GenStage for Ravines {
+    fn apply(&self, ctx: &mut GenContext) {
+        if ctx.rng.gen::<f32>() > RAVINE_CHANCE {
+            return;
+        }
+
+        let start_x = ctx.base_x + ctx.rng.gen_range(0..CHUNK_XZ_SIZE);
+        let start_z = ctx.base_z + ctx.rng.gen_range(0..CHUNK_XZ_SIZE);
+        let mut pos = [start_x as f32, WATER_HEIGHT as f32, start_z as f32];
+
+        let yaw = ctx.rng.gen_range(0.0..std::f32::consts::TAU);
+        let (dx, dz) = (yaw.cos(), yaw.sin());
+
+        for _ in 0..RAVINE_STEPS {
+            carve_ellipsoid(ctx, pos, [1.5, 16.0, 4.0]);
+            pos[0] += dx * RAVINE_STEP_LENGTH + ctx.rng.gen_range(-0.3..0.3);
+            pos[2] += dz * RAVINE_STEP_LENGTH + ctx.rng.gen_range(-0.3..0.3);
+        }
+    }
+}
+
+/// Sets every block within `radius` of `center` (chunk-local only) to
+/// `Empty` -- the carving primitive `Caves` steps along its random walk.
+fn carve_sphere(ctx: &mut GenContext, center: [f32; 3], radius: f32) {
+    carve_ellipsoid(ctx, center, [radius, radius, radius]);
+}
+
+/// Sets every block within the axis-aligned ellipsoid of `radii` around
+/// `center` (chunk-local only) to `Empty` -- shared by `Caves` (radii all
+/// equal, i.e. a sphere) and `Ravines` (a tall, thin slot).
+fn carve_ellipsoid(ctx: &mut GenContext, center: [f32; 3], radii: [f32; 3]) {
+    let [cx, cy, cz] = center;
+    let [rx, ry, rz] = radii;
+    let (x_lo, x_hi) = ((cx - rx).floor() as i64, (cx + rx).ceil() as i64);
+    let (y_lo, y_hi) = ((cy - ry).floor() as i64, (cy + ry).ceil() as i64);
+    let (z_lo, z_hi) = ((cz - rz).floor() as i64, (cz + rz).ceil() as i64);
+
+    for (x, y, z) in iproduct!(x_lo..=x_hi, y_lo..=y_hi, z_lo..=z_hi) {
+        if x < 0 || y < MIN_HEIGHT as i64 || z < 0 {
+            continue;
+        }
+        let normalized = ((x as f32 - cx) / rx).powi(2)
+            + ((y as f32 - cy) / ry).powi(2)
+            + ((z as f32 - cz) / rz).powi(2);
+        if normalized > 1.0 {
+            continue;
+        }
+        if let Some(block) = ctx.get_local(x as usize, y as usize, z as usize) {
+            if block.block_type == BlockType::Water {
+                continue;
+            }
+        } else {
+            continue;
+        }
+        ctx.set(
+            x as usize,
+            y as usize,
+            z as usize,
+            BlockType::Empty,
+            OverwriteMode::Always,
+        );
+    }
+}
+
+/// Scatters trees and flowers across grass/snow columns -- the last stage, so it
+/// sees the finished terrain (including any cave mouths `NoiseCaves`/`Caves`/`Ravines`
+/// carved through a column's ground block).
+struct Decoration;
+
+impl GenStage for Decoration {
+    fn apply(&self, ctx: &mut GenContext) {
+        for (z, x) in iproduct!(0..CHUNK_XZ_SIZE, 0..CHUNK_XZ_SIZE) {
+            let ground_elevation = ctx.elevation_map[x][z] as usize;
+            let (world_x, world_z) = (ctx.base_x + x, ctx.base_z + z);
+            let can_decorate = ctx
+                .get_local(world_x, ground_elevation, world_z)
+                .map(|block| {
+                    block.block_type == BlockType::Grass || block.block_type == BlockType::Snow
+                })
+                .unwrap_or(false);
+            if !can_decorate {
+                continue;
+            }
+
+            let biome = ctx.biome_map[x][z];
+            let did_generate_tree =
+                generate_tree_blocks(ctx, [world_x, ground_elevation, world_z], biome);
+            if !did_generate_tree {
+                generate_flower_blocks(ctx, [world_x, ground_elevation, world_z], biome);
+            }
+        }
+    }
+}
+
+/// The generation pipeline every chunk runs, in order: height and biome
+/// classification, then bulk composition, then `NoiseCaves`/`Caves`/`Ravines`
+/// carve into that solid terrain, then decoration scatters trees/flowers
+/// across whatever grass/snow survived.
+const GEN_PIPELINE: &[&dyn GenStage] = &[
+    &HeightGen,
+    &BiomeGen,
+    &Composition,
+    &NoiseCaves,
+    &Caves,
+    &Ravines,
+    &Decoration,
+];
+
+/// Pure terrain generation for one chunk, run on a worker thread (or inline
+/// on wasm32): runs `GEN_PIPELINE` against an isolated `GenContext` with no
+/// access to neighboring chunks or `WorldState`, so it can run off the main
+/// thread. Writes that would have crossed into a neighbor chunk (tree
+/// leaves spilling over a border, a cave mouth opening past it) are
+/// collected into `overflow` instead of reaching for a chunk that may not
+/// even be allocated yet.
+fn generate_chunk_blocks(chunk_idx: [usize; 2]) -> GeneratedChunk {
+    let (base_x, base_z) = (chunk_idx[0] * CHUNK_XZ_SIZE, chunk_idx[1] * CHUNK_XZ_SIZE);
+
+    let mut ctx = GenContext {
+        chunk_idx,
+        base_x,
+        base_z,
+        elevation_map: [[0; CHUNK_XZ_SIZE]; CHUNK_XZ_SIZE],
+        biome_map: [[map_generation::Biome::Ocean; CHUNK_XZ_SIZE]; CHUNK_XZ_SIZE],
+        blocks: Vec3d::new(vec![
+            Block {
+                ..Default::default()
+            };
+            CHUNK_XZ_SIZE * CHUNK_Y_SIZE * CHUNK_XZ_SIZE
+        ]),
+        overflow: vec![],
+        rng: rand::rngs::StdRng::seed_from_u64(chunk_seed(chunk_idx)),
+    };
+
+    for stage in GEN_PIPELINE {
+        stage.apply(&mut ctx);
+    }
+
+    GeneratedChunk {
+        chunk_idx,
+        blocks: ctx.blocks,
+        overflow: ctx.overflow,
+    }
+}
+
+/// How often a grass/snow column sprouts a tree, by biome: forest is far
+/// denser than grassland/tundra, desert/beach/ocean are near-zero (they
+/// only decorate at all on the rare column a biome boundary smooths into
+/// grass-adjacent sand).
+fn tree_chance_for_biome(biome: map_generation::Biome) -> f32 {
+    use map_generation::Biome;
+    match biome {
+        Biome::Forest => 1.0 / 20.0,
+        Biome::Grassland => 1.0 / 200.0,
+        Biome::Tundra => 1.0 / 150.0,
+        Biome::Snow => 1.0 / 400.0,
+        Biome::Desert => 1.0 / 4000.0,
+        Biome::Beach | Biome::Ocean => 0.0,
+    }
+}
+
+/// How often a grass/snow column that didn't get a tree sprouts a flower,
+/// by biome -- desert/beach/ocean never do.
+fn flower_chance_for_biome(biome: map_generation::Biome) -> f32 {
+    use map_generation::Biome;
+    match biome {
+        Biome::Forest => 1.0 / 60.0,
+        Biome::Grassland => 1.0 / 100.0,
+        Biome::Tundra => 1.0 / 300.0,
+        Biome::Snow => 0.0,
+        Biome::Desert | Biome::Beach | Biome::Ocean => 0.0,
+    }
+}
+
+/// Base sRGB (0-255 per channel, like the swatches `lib.rs` builds its sky
+/// `LightUniform` colors from) grass tint per biome, before
+/// `scale_tint_for_elevation` fades it toward dry ground at altitude --
+/// lush and saturated in forest, paler through tundra/snow, and
+/// sun-bleached in desert/beach (grass itself barely generates there, but
+/// `smoothed_biome` can still paint an edge column this way).
+fn grass_tint_color_for_biome(biome: map_generation::Biome) -> [f64; 3] {
+    use map_generation::Biome;
+    match biome {
+        Biome::Forest => [62.0, 130.0, 48.0],
+        Biome::Grassland => [112.0, 165.0, 67.0],
+        Biome::Tundra => [130.0, 150.0, 110.0],
+        Biome::Snow => [150.0, 165.0, 150.0],
+        Biome::Desert => [165.0, 150.0, 80.0],
+        Biome::Beach | Biome::Ocean => [130.0, 160.0, 110.0],
+    }
+}
+
+/// Base sRGB foliage (leaf) tint per biome -- same idea as
+/// `grass_tint_color_for_biome` but darker and less saturated, matching how
+/// leaf canopy reads shadier than an open grass blade in every biome.
+fn foliage_tint_color_for_biome(biome: map_generation::Biome) -> [f64; 3] {
+    use map_generation::Biome;
+    match biome {
+        Biome::Forest => [45.0, 110.0, 40.0],
+        Biome::Grassland => [80.0, 140.0, 50.0],
+        Biome::Tundra => [90.0, 120.0, 90.0],
+        Biome::Snow => [110.0, 130.0, 120.0],
+        Biome::Desert => [120.0, 110.0, 60.0],
+        Biome::Beach | Biome::Ocean => [90.0, 130.0, 80.0],
+    }
+}
+
+/// The sRGB color `scale_tint_for_elevation` fades a biome's tint towards as
+/// elevation climbs from `MIN_HEIGHT` to `MAX_HEIGHT` -- a dry, desaturated
+/// brown, so a grassy peak reads as dry highland regardless of which biome
+/// it pokes up through.
+const HIGH_ELEVATION_TINT: [f64; 3] = [120.0, 100.0, 70.0];
+
+/// Linearly blends `base_srgb` towards `HIGH_ELEVATION_TINT` as `elevation`
+/// climbs from `MIN_HEIGHT` to `MAX_HEIGHT`, clamped at both ends.
+fn scale_tint_for_elevation(base_srgb: [f64; 3], elevation: usize) -> [f64; 3] {
+    let t = (elevation.saturating_sub(MIN_HEIGHT as usize) as f64
+        / (MAX_HEIGHT - MIN_HEIGHT) as f64)
+        .clamp(0.0, 1.0);
+    std::array::from_fn(|i| base_srgb[i] + (HIGH_ELEVATION_TINT[i] - base_srgb[i]) * t)
+}
+
+/// Resolves `tint_type` to a linear-space RGB multiplier for `biome` at
+/// `elevation`, for `mesh_chunk` to fold into a face's `color_adjust`.
+///
+/// The per-biome tables above are authored as ordinary sRGB swatches (the
+/// same convention `color::srgb_to_rgb` is already used for in `lib.rs`'s
+/// sky light color), but `color_adjust` multiplies the atlas texture in
+/// linear light -- the same space the existing per-face ambient constants
+/// (`0.7`, `0.8`, ...) and light-level `brightness` already operate in.
+/// Multiplying an sRGB tint straight into that linear multiplier would
+/// apply the gamma curve twice and wash the result out, so every swatch is
+/// converted through `color::srgb_to_rgb` before it's used here.
+fn tint_multiplier(
+    tint_type: TintType,
+    biome: map_generation::Biome,
+    elevation: usize,
+) -> [f32; 3] {
+    let srgb = match tint_type {
+        TintType::Default => return [1.0, 1.0, 1.0],
+        TintType::Grass => scale_tint_for_elevation(grass_tint_color_for_biome(biome), elevation),
+        TintType::Foliage => {
+            scale_tint_for_elevation(foliage_tint_color_for_biome(biome), elevation)
+        }
+        TintType::Fixed { r, g, b } => return [r, g, b],
+    };
+    std::array::from_fn(|i| color::srgb_to_rgb(srgb[i] / 255.0) as f32)
+}
+
+/// Per-biome tree silhouette: trunk height and the leaf-slice diameters
+/// stacked from `trunk_top - 3` upward. Forest keeps the original
+/// lollipop spruce; grassland/desert/beach/ocean get a smaller, rounder
+/// canopy; tundra/snow get a taller, slimmer conifer.
+fn tree_shape_for_biome(biome: map_generation::Biome) -> (usize, &'static [usize]) {
+    use map_generation::Biome;
+    match biome {
+        Biome::Forest => (6, &[7, 7, 5, 3]),
+        Biome::Tundra | Biome::Snow => (7, &[5, 5, 3, 3]),
+        Biome::Grassland | Biome::Desert | Biome::Beach | Biome::Ocean => (4, &[5, 5, 3]),
+    }
+}
+
+/// The world-space blocks (position, type, overwrite mode) a lollipop-spruce
+/// tree occupies when its trunk base sits at `trunk_base`, shaped per
+/// `biome` (see `tree_shape_for_biome`). Shared by `generate_tree_blocks`
+/// (bulk chunk generation, writes through `GenContext::set`) and
+/// `WorldState::grow_sapling` (random-tick growth, writes through
+/// `queue_or_set_block`) so the leaf-placement logic isn't duplicated
+/// between the two paths.
+fn tree_blocks(
+    trunk_base: [usize; 3],
+    biome: map_generation::Biome,
+) -> Vec<([usize; 3], BlockType, OverwriteMode)> {
+    let (trunk_height, leaf_slice_diameters) = tree_shape_for_biome(biome);
+    let [x, y_base, z] = trunk_base;
+    let trunk_top = y_base + trunk_height;
+
+    let mut blocks = Vec::new();
+    for y in y_base..trunk_top {
+        blocks.push(([x, y, z], BlockType::Tree, OverwriteMode::Always));
+    }
+
+    let mut leaf_y = trunk_top - 3;
+    for &diam in leaf_slice_diameters {
+        let radius = (diam - 1) / 2;
+        for (leaf_x, leaf_z) in iproduct!(x - radius + 1..x + radius, z - radius + 1..z + radius) {
+            blocks.push((
+                [leaf_x, leaf_y, leaf_z],
+                BlockType::random_tree_leaf(),
+                OverwriteMode::IfEmpty,
+            ));
+        }
+        leaf_y += 1;
+    }
+    blocks
+}
+
+/// Places a full-grown tree at world-gen time: trunk placements are always
+/// within `ctx`'s own chunk (a tree's base column is this chunk's own), but
+/// leaves can spill past its edges, so those go through `ctx.overflow` the
+/// same as every other stage. `WorldState::grow_sapling` is the random-tick
+/// counterpart that grows a `Sapling` into the same shape later.
+fn generate_tree_blocks(
+    ctx: &mut GenContext,
+    base_location: [usize; 3],
+    biome: map_generation::Biome,
+) -> bool {
+    if ctx.rng.gen::<f32>() > tree_chance_for_biome(biome) {
+        return false;
+    }
+
+    let mut trunk_base = base_location;
+    trunk_base[1] += 1;
+
+    for ([x, y, z], block_type, overwrite_mode) in tree_blocks(trunk_base, biome) {
+        ctx.set(x, y, z, block_type, overwrite_mode);
+    }
+    true
+}
+
+/// Places a flower at world-gen time -- always within `ctx`'s own chunk
+/// since a flower sits directly on its own column's ground block.
+fn generate_flower_blocks(
+    ctx: &mut GenContext,
+    ground_elevation: [usize; 3],
+    biome: map_generation::Biome,
+) -> bool {
+    if ctx.rng.gen::<f32>() > flower_chance_for_biome(biome) {
+        return false;
+    }
+
+    let [x, y_base, z] = ground_elevation;
+    ctx.set(
+        x,
+        y_base + 1,
+        z,
+        BlockType::RedFlower,
+        OverwriteMode::Always,
+    );
+    true
+}
+
+pub struct WorldState {
+    pub chunk_indices: Vec2d<u32>,
+    chunks: Vec<Chunk>,
+    highlighted_block: Option<[usize; 3]>,
+
+    pub character_entity: CharacterEntity,
+    /// Other entities driven by the same gravity/collision core as the player (mobs, items
+    /// dropped by `break_block`) -- ticked alongside `character_entity` in `physics_tick`.
+    /// Nothing pushes onto this yet.
+    dynamic_entities: Vec<DynamicEntity>,
+    pub place_block_type: BlockType,
+    input_state: InputState,
+    /// Input intents translated by `process_window_event`/
+    /// `process_web_dom_button_event`, applied in one ordered pass by
+    /// `apply_queued_input_events` at the top of `physics_tick`.
+    input_events: Events<InputEvent>,
+
+    pub is_flying: bool,
+
+    /// One `light::PointLight` per emissive block currently placed (see
+    /// `BlockType::emitted_light`), keyed by world block position so
+    /// `break_block` can look its entry up and remove it. Mirrored into
+    /// `light::LightUniform::point_lights` every frame in `Game::update_tick`.
+    point_lights: std::collections::HashMap<[usize; 3], light::PointLight>,
+
+    /// Chunks whose `sky_light`/`block_light` fields were touched by the most
+    /// recent `set_block`'s relighting BFS. A light change can ripple well
+    /// past the chunk the edited block lives in (up to `MAX_LIGHT_LEVEL`
+    /// blocks away), so `break_block`/`place_block` drain this to fold those
+    /// chunks into the set that gets remeshed, in addition to the ones
+    /// `get_affected_chunks` already flags from the edited block's position.
+    light_dirty_chunks: HashSet<[usize; 2]>,
+
+    /// Block placements generation wanted to make in a chunk that isn't
+    /// generated yet (e.g. tree leaves spilling over a chunk border), keyed
+    /// by that chunk's index. Drained and applied once that chunk finishes
+    /// generating -- see `queue_or_set_block`.
+    pending_blocks: HashMap<[usize; 2], Vec<QueuedBlock>>,
+
+    /// Chunk generation's worker-pool dispatcher -- see `ChunkGenPool`.
+    chunk_gen_pool: ChunkGenPool,
+    /// Chunks `maybe_allocate_chunk` has already requested generation for
+    /// but `tick` hasn't installed the result of yet, so repeat calls (the
+    /// player lingering near a chunk border) don't double-queue the same
+    /// chunk.
+    in_flight_chunks: HashSet<[usize; 2]>,
+
+    /// Chunks that have finished generating, i.e. `get_chunk(idx).is_generated`
+    /// -- the pool `random_tick` samples block positions from, since picking
+    /// from an unallocated or still-generating chunk would read garbage.
+    generated_chunk_idxs: HashSet<[usize; 2]>,
+
+    /// Parallel chunk meshing's worker-pool dispatcher -- see `ChunkMeshPool`.
+    chunk_mesh_pool: ChunkMeshPool,
+    /// Chunks `dispatch_chunk_mesh` has already requested a mesh for but
+    /// `drain_meshed_chunks` hasn't returned the result of yet, so repeat
+    /// dispatches for the same still-pending chunk don't double-queue it.
+    in_flight_mesh_chunks: HashSet<[usize; 2]>,
+}
+
+macro_rules! set_block {
+    ($self:ident, $x:expr, $y:expr, $z:expr, $block_type:expr) => {
+        $self.set_block($x, $y, $z, $block_type, false)
+    };
+    ($self:ident, $x:expr, $y:expr, $z:expr, $block_type:expr, $verbose:expr) => {
+        $self.set_block($x, $y, $z, $block_type, $verbose)
+    };
+}
+
+impl WorldState {
+    pub fn new() -> Self {
+        let world_center = get_world_center();
+
+        // let GRAVITY_ACCELERATION = glam::Vec3::new(0.0, -0.0005, 0.0);
+
+        let initial_pos = glam::Vec3::new(
+            world_center.x as f32 - 20.0,
+            world_center.y as f32 + 10.0,
+            world_center.z as f32 - 20.0,
+        );
+
+        let character_entity = CharacterEntity {
+            dynamics: DynamicEntity::new(initial_pos, 0.5, 1.0),
+            ticks_since_grounded: 0,
+            buffered_jump_ticks_remaining: 0,
+            double_jump_available: true,
+        };
+
+        Self {
+            chunk_indices: Vec2d::new(
+                vec![CHUNK_DOES_NOT_EXIST_VALUE; MAX_CHUNK_WORLD_WIDTH * MAX_CHUNK_WORLD_WIDTH],
+                [MAX_CHUNK_WORLD_WIDTH, MAX_CHUNK_WORLD_WIDTH],
+            ),
+            chunks: vec![],
+            highlighted_block: None,
+            character_entity,
+            dynamic_entities: vec![],
+            place_block_type: BlockType::DEFAULT_PLACE_BLOCK_TYPE,
+            input_state: InputState {
+                movement: InputHelper::new(),
+                last_joystick_vector: (0.0, 0.0),
+                last_translation_joystick_vector: (0.0, 0.0),
+            },
+            input_events: Events::new(),
+            is_flying: DEFAULT_IS_FLYING,
+            point_lights: std::collections::HashMap::new(),
+            light_dirty_chunks: HashSet::new(),
+            pending_blocks: HashMap::new(),
+            chunk_gen_pool: ChunkGenPool::new(),
+            in_flight_chunks: HashSet::new(),
+            generated_chunk_idxs: HashSet::new(),
+            chunk_mesh_pool: ChunkMeshPool::new(),
+            in_flight_mesh_chunks: HashSet::new(),
+        }
+    }
+
+    fn get_chunk_mut(&mut self, chunk_idx: [usize; 2]) -> &mut Chunk {
+        let chunk_idx = self.chunk_indices[chunk_idx];
+        &mut self.chunks[chunk_idx as usize]
+    }
+
+    fn get_chunk(&self, chunk_idx: [usize; 2]) -> &Chunk {
+        let chunk_idx = self.chunk_indices[chunk_idx];
+        &self.chunks[chunk_idx as usize]
+    }
+
+    fn get_block(&self, x: usize, y: usize, z: usize) -> &Block {
+        let chunk_idx = self.chunk_indices[[x / CHUNK_XZ_SIZE, z / CHUNK_XZ_SIZE]];
+        let chunk = &self.chunks[chunk_idx as usize];
+        chunk
+            .blocks
+            .get_unchecked(x % CHUNK_XZ_SIZE, y, z % CHUNK_XZ_SIZE)
+    }
+
+    /// This world column's biome, for systems outside the generation
+    /// pipeline that want it (lighting tint, water color) -- pure noise
+    /// lookup, so it doesn't need `self` to be generated there yet.
+    pub fn biome_at(&self, world_x: usize, world_z: usize) -> map_generation::Biome {
+        map_generation::sample_biome(WORLD_SEED, world_x, world_z).biome
+    }
+
+    fn light_channel(&self, channel: LightChannel, x: usize, y: usize, z: usize) -> u8 {
+        let block = self.get_block(x, y, z);
+        match channel {
+            LightChannel::Sky => block.sky_light,
+            LightChannel::Block => block.block_light,
+        }
+    }
+
+    fn set_light_channel(
+        &mut self,
+        channel: LightChannel,
+        x: usize,
+        y: usize,
+        z: usize,
+        value: u8,
+    ) {
+        let chunk_idx = [x / CHUNK_XZ_SIZE, z / CHUNK_XZ_SIZE];
+        let block = self.get_chunk_mut(chunk_idx).blocks.get_unchecked_mut(
+            x % CHUNK_XZ_SIZE,
+            y,
+            z % CHUNK_XZ_SIZE,
+        );
+        match channel {
+            LightChannel::Sky => block.sky_light = value,
+            LightChannel::Block => block.block_light = value,
+        }
+        self.light_dirty_chunks.insert(chunk_idx);
+    }
+
+    /// The up-to-6 neighbors of `(x, y, z)` that exist: in bounds, and in a
+    /// chunk that's already been allocated (`maybe_allocate_chunk`). Shared
+    /// by the light BFS below; unlike `set_block`'s neighbor lookup, this
+    /// doesn't need raw pointers since it never holds more than one
+    /// neighbor's reference at a time.
+    fn existing_light_neighbors(&self, x: usize, y: usize, z: usize) -> Vec<(usize, usize, usize)> {
+        let max_xz = MAX_CHUNK_WORLD_WIDTH * CHUNK_XZ_SIZE;
+        let candidates = [
+            (y + 1 < CHUNK_Y_SIZE).then_some((x, y + 1, z)),
+            (y > 0).then_some((x, y - 1, z)),
+            (x + 1 < max_xz).then_some((x + 1, y, z)),
+            (x > 0).then_some((x - 1, y, z)),
+            (z + 1 < max_xz).then_some((x, y, z + 1)),
+            (z > 0).then_some((x, y, z - 1)),
+        ];
+        candidates
+            .into_iter()
+            .flatten()
+            .filter(|&(nx, _, nz)| {
+                self.chunk_indices[[nx / CHUNK_XZ_SIZE, nz / CHUNK_XZ_SIZE]]
+                    != CHUNK_DOES_NOT_EXIST_VALUE
+            })
+            .collect()
+    }
+
+    /// Standard light-increase BFS: for each popped node, a neighbor's light
+    /// rises to this node's light minus attenuation (1 through a translucent
+    /// block, a full drop to 0 through anything else) if that's brighter
+    /// than what the neighbor already has, and the neighbor is enqueued in
+    /// turn. Shared by the initial per-chunk seeding in `seed_world_light`
+    /// and the incremental re-lighting `relight_block` does after
+    /// `set_block` changes a block.
+    fn propagate_light_increase(
+        &mut self,
+        channel: LightChannel,
+        mut queue: VecDeque<(usize, usize, usize)>,
+    ) {
+        while let Some((x, y, z)) = queue.pop_front() {
+            let light = self.light_channel(channel, x, y, z);
+            if light == 0 {
+                continue;
+            }
+            for (nx, ny, nz) in self.existing_light_neighbors(x, y, z) {
+                let attenuation = if self.get_block(nx, ny, nz).block_type.is_translucent() {
+                    LIGHT_ATTENUATION
+                } else {
+                    light
+                };
+                let propagated = light.saturating_sub(attenuation);
+                if propagated > self.light_channel(channel, nx, ny, nz) {
+                    self.set_light_channel(channel, nx, ny, nz, propagated);
+                    queue.push_back((nx, ny, nz));
+                }
+            }
+        }
+    }
+
+    /// Light-removal BFS, run before `relight_block` re-propagates: starting
+    /// from `origin`'s old light value, zero out every downstream cell whose
+    /// light could only have come from `origin` (strictly dimmer than the
+    /// node removing it), and collect any neighbor at least as bright as the
+    /// node it's sitting next to -- those kept their light from some other
+    /// source and become seeds for the re-increase pass that refills the
+    /// hole this left behind.
+    fn propagate_light_decrease(
+        &mut self,
+        channel: LightChannel,
+        origin: (usize, usize, usize),
+        old_light: u8,
+    ) {
+        let mut decrease_queue: VecDeque<(usize, usize, usize, u8)> = VecDeque::new();
+        let mut increase_seeds: VecDeque<(usize, usize, usize)> = VecDeque::new();
+
+        self.set_light_channel(channel, origin.0, origin.1, origin.2, 0);
+        decrease_queue.push_back((origin.0, origin.1, origin.2, old_light));
+
+        while let Some((x, y, z, light)) = decrease_queue.pop_front() {
+            for (nx, ny, nz) in self.existing_light_neighbors(x, y, z) {
+                let neighbor_light = self.light_channel(channel, nx, ny, nz);
+                if neighbor_light != 0 && neighbor_light < light {
+                    self.set_light_channel(channel, nx, ny, nz, 0);
+                    decrease_queue.push_back((nx, ny, nz, neighbor_light));
+                } else if neighbor_light >= light {
+                    increase_seeds.push_back((nx, ny, nz));
+                }
+            }
+        }
+
+        self.propagate_light_increase(channel, increase_seeds);
+    }
+
+    /// Initial light seeding for a freshly generated chunk: sky-light starts
+    /// at `MAX_LIGHT_LEVEL` at the top of every column and is seeded
+    /// straight down until the first non-translucent block (`is_translucent`)
+    /// shadows the rest of the column; block-light is seeded at every
+    /// emissive block (`BlockType::light_emission`). Both seed sets then
+    /// flood outward via `propagate_light_increase`. Called once generation
+    /// has placed every block in the chunk, so the seeding sees the chunk's
+    /// final terrain rather than a half-built column.
+    fn seed_world_light(&mut self, [chunk_x, chunk_z]: [usize; 2]) {
+        let (base_x, base_z) = (chunk_x * CHUNK_XZ_SIZE, chunk_z * CHUNK_XZ_SIZE);
+
+        let mut sky_queue = VecDeque::new();
+        let mut block_queue = VecDeque::new();
+
+        for (rel_z, rel_x) in iproduct!(0..CHUNK_XZ_SIZE, 0..CHUNK_XZ_SIZE) {
+            let (world_x, world_z) = (base_x + rel_x, base_z + rel_z);
+            for y in (0..CHUNK_Y_SIZE).rev() {
+                if !self
+                    .get_block(world_x, y, world_z)
+                    .block_type
+                    .is_translucent()
+                {
+                    break;
+                }
+                self.set_light_channel(LightChannel::Sky, world_x, y, world_z, MAX_LIGHT_LEVEL);
+                sky_queue.push_back((world_x, y, world_z));
+            }
+
+            for y in 0..CHUNK_Y_SIZE {
+                let emission = self
+                    .get_block(world_x, y, world_z)
+                    .block_type
+                    .light_emission();
+                if emission > 0 {
+                    self.set_light_channel(LightChannel::Block, world_x, y, world_z, emission);
+                    block_queue.push_back((world_x, y, world_z));
+                }
+            }
+        }
+
+        self.propagate_light_increase(LightChannel::Sky, sky_queue);
+        self.propagate_light_increase(LightChannel::Block, block_queue);
+    }
+
+    /// Re-lights `(x, y, z)` after `set_block` changes what's there:
+    /// unwinds whatever light it used to hold (`propagate_light_decrease`,
+    /// a no-op if it held none), re-seeds its own cell if the new block type
+    /// emits light, then pulls light back in from any neighbor that still
+    /// has some -- refilling the hole left by a removed block, or lighting a
+    /// newly-placed translucent block from its surroundings, via the same
+    /// `propagate_light_increase` used for initial seeding.
+    fn relight_block(
+        &mut self,
+        x: usize,
+        y: usize,
+        z: usize,
+        old_sky_light: u8,
+        old_block_light: u8,
+    ) {
+        if old_sky_light > 0 {
+            self.propagate_light_decrease(LightChannel::Sky, (x, y, z), old_sky_light);
+        }
+        if old_block_light > 0 {
+            self.propagate_light_decrease(LightChannel::Block, (x, y, z), old_block_light);
+        }
+
+        let emission = self.get_block(x, y, z).block_type.light_emission();
+        if emission > 0 {
+            self.set_light_channel(LightChannel::Block, x, y, z, emission);
+            self.propagate_light_increase(LightChannel::Block, VecDeque::from([(x, y, z)]));
+        }
+
+        for channel in [LightChannel::Sky, LightChannel::Block] {
+            let seeds: VecDeque<(usize, usize, usize)> = self
+                .existing_light_neighbors(x, y, z)
+                .into_iter()
+                .filter(|&(nx, ny, nz)| self.light_channel(channel, nx, ny, nz) > 0)
+                .collect();
+            self.propagate_light_increase(channel, seeds);
+        }
+    }
+
+    /// Returns the chunks whose light fields changed as a result of this
+    /// call, beyond the block's own chunk -- see `light_dirty_chunks`.
+    /// Callers that need to know which chunks to remesh after a
+    /// player-facing edit (`break_block`/`place_block`) should union this
+    /// with `get_affected_chunks`; `install_generated_chunk`'s queued-block
+    /// drains ignore it, since `seed_world_light` relights the whole chunk
+    /// anyway, and bulk terrain generation itself (`generate_chunk_blocks`)
+    /// never calls `set_block` at all -- it writes an isolated block grid
+    /// directly.
+    fn set_block(
+        &mut self,
+        world_x: usize,
+        y: usize,
+        world_z: usize,
+        mut block_type: BlockType,
+        verbose: bool,
+    ) -> Vec<[usize; 2]> {
+        self.light_dirty_chunks.clear();
+        let old_block = *self.get_block(world_x, y, world_z);
+
+        unsafe {
+            let [chunk_x, chunk_z] = [world_x / CHUNK_XZ_SIZE, world_z / CHUNK_XZ_SIZE];
+            let (x, z) = (world_x % CHUNK_XZ_SIZE, world_z % CHUNK_XZ_SIZE);
+
+            let this_block = self
+                .get_chunk_mut([chunk_x, chunk_z])
+                .blocks
+                .get_raw_ptr_mut(x, y, z);
+
+            #[derive(Clone, Copy)]
+            struct Neighbor {
+                block: *mut Block,
+                this_shared_face: Face,
+                other_shared_face: Face,
+            }
+
+            let mut neighbors: [Option<Neighbor>; 6] = [None; 6];
+
+            if y < CHUNK_Y_SIZE - 1 {
+                neighbors[0] = Some(Neighbor {
+                    block: self
+                        .get_chunk_mut([chunk_x, chunk_z])
+                        .blocks
+                        .get_raw_ptr_mut(x, y + 1, z),
+                    this_shared_face: Face::Top,
+                    other_shared_face: Face::Bottom,
+                });
+            }
+            if y > 0 {
+                neighbors[1] = Some(Neighbor {
+                    block: self
+                        .get_chunk_mut([chunk_x, chunk_z])
+                        .blocks
+                        .get_raw_ptr_mut(x, y - 1, z),
+                    this_shared_face: Face::Bottom,
+                    other_shared_face: Face::Top,
+                });
+            }
+
+            neighbors[2] = Some(Neighbor {
+                block: if x < CHUNK_XZ_SIZE - 1 {
+                    self.get_chunk_mut([chunk_x, chunk_z])
+                        .blocks
+                        .get_raw_ptr_mut(x + 1, y, z)
+                } else {
+                    self.get_chunk_mut([chunk_x + 1, chunk_z])
+                        .blocks
+                        .get_raw_ptr_mut(0, y, z)
+                },
                 this_shared_face: Face::Left,
                 other_shared_face: Face::Right,
             });
@@ -532,7 +1946,7 @@ impl WorldState {
 
             // Special cases:
             // 1. If we're breaking a block next to water, fill this block with water instead
-            // 2. If we're breaking a block with a flower above it, also remove the flower
+            // 2. If we're breaking a block with a flower/sapling above it, also remove it
             if block_type == BlockType::Empty {
                 for i in 0..6 {
                     if let Some(neighbor) = neighbors[i] {
@@ -541,7 +1955,8 @@ impl WorldState {
                         {
                             block_type = BlockType::Water;
                         }
-                        if (*neighbor.block).block_type == BlockType::RedFlower
+                        if ((*neighbor.block).block_type == BlockType::RedFlower
+                            || (*neighbor.block).block_type == BlockType::Sapling)
                             && neighbor.this_shared_face == Face::Top
                         {
                             (*neighbor.block).block_type = BlockType::Empty;
@@ -582,13 +1997,27 @@ impl WorldState {
                             .set(neighbor.other_shared_face, true);
                     }
                     (_, _) => {
+                        let neighbor_type = (*neighbor.block).block_type;
+                        (*this_block)
+                            .neighbors
+                            .set(neighbor.this_shared_face, neighbor_type.occludes_neighbor(block_type));
                         (*neighbor.block)
                             .neighbors
-                            .set(neighbor.other_shared_face, !block_type.is_translucent());
+                            .set(neighbor.other_shared_face, block_type.occludes_neighbor(neighbor_type));
                     }
                 }
             }
         }
+
+        self.relight_block(
+            world_x,
+            y,
+            world_z,
+            old_block.sky_light,
+            old_block.block_light,
+        );
+
+        self.light_dirty_chunks.drain().collect()
     }
 
     pub fn find_chunk_neighbors(
@@ -611,95 +2040,308 @@ impl WorldState {
             .collect::<Vec<_>>()
     }
 
-    pub fn maybe_generate_tree(&mut self, base_location: [usize; 3]) -> bool {
-        const TREE_CHANCE: f32 = 1.0 / 200.0;
-        if rand::thread_rng().gen::<f32>() > TREE_CHANCE {
-            return false;
+    /// Applies a `GeneratedChunk`'s `overflow` entry once its origin chunk
+    /// (`current_chunk_idx`) has installed: if `world_pos` falls in a chunk
+    /// that's already generated (or is `current_chunk_idx` itself), apply
+    /// `overwrite_mode` and set it right away; otherwise the target chunk
+    /// doesn't exist yet, so queue it in `pending_blocks` to be applied once
+    /// that chunk's own `install_generated_chunk` drains its queue.
+    fn queue_or_set_block(
+        &mut self,
+        current_chunk_idx: [usize; 2],
+        world_pos: [usize; 3],
+        block_type: BlockType,
+        overwrite_mode: OverwriteMode,
+    ) {
+        let [world_x, y, world_z] = world_pos;
+        let target_chunk_idx = [world_x / CHUNK_XZ_SIZE, world_z / CHUNK_XZ_SIZE];
+
+        if target_chunk_idx == current_chunk_idx || self.get_chunk(target_chunk_idx).is_generated {
+            if overwrite_mode == OverwriteMode::IfEmpty
+                && !self.get_block(world_x, y, world_z).is_empty()
+            {
+                return;
+            }
+            set_block!(self, world_x, y, world_z, block_type);
+        } else {
+            self.pending_blocks
+                .entry(target_chunk_idx)
+                .or_default()
+                .push(QueuedBlock {
+                    world_pos,
+                    block_type,
+                    overwrite_mode,
+                });
         }
+    }
 
-        let mut trunk_base = base_location;
-        trunk_base[1] += 1;
+    fn get_block_mut(&mut self, x: usize, y: usize, z: usize) -> &mut Block {
+        let chunk_idx = [x / CHUNK_XZ_SIZE, z / CHUNK_XZ_SIZE];
+        self.get_chunk_mut(chunk_idx).blocks.get_unchecked_mut(
+            x % CHUNK_XZ_SIZE,
+            y,
+            z % CHUNK_XZ_SIZE,
+        )
+    }
 
-        let [x, y_base, z] = trunk_base;
-        let trunk_top = y_base + 6;
-        for y in y_base..trunk_top {
-            set_block!(self, x, y, z, BlockType::Tree);
+    /// Derives every block's `NeighborBitmap` in `chunk_idx` from its
+    /// neighbors in one pass -- the bulk counterpart of the incremental
+    /// raw-pointer neighbor dance `set_block` does for a single edit, needed
+    /// here because a freshly generated chunk's blocks never went through
+    /// `set_block` at all. Also patches the mirrored bit on each bordering
+    /// neighbor block, whose bitmap was computed against this chunk back
+    /// when it was all-`Empty`.
+    fn stitch_neighbor_bitmaps(&mut self, [chunk_x, chunk_z]: [usize; 2]) {
+        let (base_x, base_z) = (chunk_x * CHUNK_XZ_SIZE, chunk_z * CHUNK_XZ_SIZE);
+        let max_xz = MAX_CHUNK_WORLD_WIDTH * CHUNK_XZ_SIZE;
+
+        for (rel_z, rel_x, y) in iproduct!(0..CHUNK_XZ_SIZE, 0..CHUNK_XZ_SIZE, 0..CHUNK_Y_SIZE) {
+            let (x, z) = (base_x + rel_x, base_z + rel_z);
+            let this_type = self.get_block(x, y, z).block_type;
+
+            let directions = [
+                (y + 1 < CHUNK_Y_SIZE).then_some((x, y + 1, z, Face::Top, Face::Bottom)),
+                (y > 0).then_some((x, y - 1, z, Face::Bottom, Face::Top)),
+                (x + 1 < max_xz).then_some((x + 1, y, z, Face::Left, Face::Right)),
+                (x > 0).then_some((x - 1, y, z, Face::Right, Face::Left)),
+                (z + 1 < max_xz).then_some((x, y, z + 1, Face::Front, Face::Back)),
+                (z > 0).then_some((x, y, z - 1, Face::Back, Face::Front)),
+            ];
+            for (nx, ny, nz, this_face, other_face) in directions.into_iter().flatten() {
+                let neighbor_type = self.get_block(nx, ny, nz).block_type;
+                self.get_block_mut(x, y, z)
+                    .neighbors
+                    .set(this_face, neighbor_type.occludes_neighbor(this_type));
+                self.get_block_mut(nx, ny, nz)
+                    .neighbors
+                    .set(other_face, this_type.occludes_neighbor(neighbor_type));
+            }
         }
+    }
 
-        // Minecraft Lollipop Spruce Tree
-        let leaf_slice_diameters = [7, 7, 5, 3];
-        let mut leaf_y = trunk_top - 3;
-        for diam in leaf_slice_diameters {
-            let radius = (diam - 1) / 2;
-            for (leaf_x, leaf_z) in
-                iproduct!(x - radius + 1..x + radius, z - radius + 1..z + radius)
+    /// Installs a `GeneratedChunk` a worker thread (or, on wasm32, `tick`
+    /// itself) produced: drops its block grid in, derives its neighbor
+    /// bitmaps and lighting now that its neighbors can actually be read,
+    /// then applies whatever this chunk's generation spilled into neighbors
+    /// (`overflow`) and whatever earlier-generated neighbors had queued for
+    /// this chunk while it didn't exist (`pending_blocks`).
+    fn install_generated_chunk(&mut self, generated: GeneratedChunk) {
+        let GeneratedChunk {
+            chunk_idx,
+            blocks,
+            overflow,
+        } = generated;
+
+        self.get_chunk_mut(chunk_idx).blocks = blocks;
+        self.stitch_neighbor_bitmaps(chunk_idx);
+        self.seed_world_light(chunk_idx);
+        self.get_chunk_mut(chunk_idx).is_generated = true;
+        self.in_flight_chunks.remove(&chunk_idx);
+        self.generated_chunk_idxs.insert(chunk_idx);
+
+        for queued in overflow {
+            self.queue_or_set_block(
+                chunk_idx,
+                queued.world_pos,
+                queued.block_type,
+                queued.overwrite_mode,
+            );
+        }
+
+        for queued in self.pending_blocks.remove(&chunk_idx).unwrap_or_default() {
+            let [world_x, y, world_z] = queued.world_pos;
+            if queued.overwrite_mode == OverwriteMode::IfEmpty
+                && !self.get_block(world_x, y, world_z).is_empty()
             {
-                // TODO(aleks): need a "set block if empty" primitive
-                if self.get_block(leaf_x, leaf_y, leaf_z).is_empty() {
-                    set_block!(self, leaf_x, leaf_y, leaf_z, BlockType::random_tree_leaf());
-                }
+                continue;
             }
-            leaf_y += 1;
+            set_block!(self, world_x, y, world_z, queued.block_type);
         }
-        true
     }
 
-    pub fn maybe_generate_flower(&mut self, ground_elevation: [usize; 3]) -> bool {
-        const FLOWER_CHANCE: f32 = 1.0 / 100.0;
-        if rand::thread_rng().gen::<f32>() > FLOWER_CHANCE {
-            return false;
+    /// Drains whatever chunks finished generating since the last call,
+    /// installs them, and returns their indices so the caller knows which
+    /// chunks need remeshing.
+    pub fn tick(&mut self) -> Vec<[usize; 2]> {
+        let generated = self.chunk_gen_pool.tick();
+        generated
+            .into_iter()
+            .map(|generated| {
+                let chunk_idx = generated.chunk_idx;
+                self.install_generated_chunk(generated);
+                chunk_idx
+            })
+            .collect()
+    }
+
+    /// Ticks the living world forward after generation: samples `budget`
+    /// random block positions from already-generated chunks and dispatches
+    /// each to its `BlockType`'s growth handler (`grow_block`) -- flowers
+    /// occasionally spread, grass creeps onto exposed dirt, saplings grow
+    /// into trees. Returns the chunks any handler modified, for the caller
+    /// to fold into its remesh set the same way `tick`'s result is.
+    pub fn random_tick(&mut self, budget: usize) -> Vec<[usize; 2]> {
+        if self.generated_chunk_idxs.is_empty() {
+            return vec![];
         }
+        let candidate_chunks: Vec<[usize; 2]> =
+            self.generated_chunk_idxs.iter().cloned().collect();
+        let mut rng = rand::thread_rng();
+
+        let mut dirty_chunks: HashSet<[usize; 2]> = HashSet::new();
+        for _ in 0..budget {
+            let [chunk_x, chunk_z] = *candidate_chunks.choose(&mut rng).unwrap();
+            let world_x = chunk_x * CHUNK_XZ_SIZE + rng.gen_range(0..CHUNK_XZ_SIZE);
+            let world_z = chunk_z * CHUNK_XZ_SIZE + rng.gen_range(0..CHUNK_XZ_SIZE);
+            let y = rng.gen_range(0..CHUNK_Y_SIZE);
+
+            let block_type = self.get_block(world_x, y, world_z).block_type;
+            for modified_pos in self.grow_block(block_type, [world_x, y, world_z], &mut rng) {
+                let point = cgmath::Point3::new(modified_pos[0], modified_pos[1], modified_pos[2]);
+                dirty_chunks.extend(self.get_affected_chunks(&point));
+            }
+        }
+        dirty_chunks.into_iter().collect()
+    }
 
-        let [x, y_base, z] = ground_elevation;
-        set_block!(self, x, y_base + 1, z, BlockType::RedFlower);
-        true
+    /// Dispatches a sampled block to its growth handler -- a `match` so
+    /// adding a new growable `BlockType` is just another arm, no new
+    /// call site in `random_tick`. Returns whichever block positions the
+    /// handler actually modified (possibly none, since every handler also
+    /// rolls its own chance to act this tick).
+    fn grow_block(
+        &mut self,
+        block_type: BlockType,
+        pos: [usize; 3],
+        rng: &mut impl Rng,
+    ) -> Vec<[usize; 3]> {
+        match block_type {
+            BlockType::RedFlower => self.grow_flower(pos, rng),
+            BlockType::Grass => self.grow_grass(pos, rng),
+            BlockType::Sapling => self.grow_sapling(pos, rng),
+            _ => vec![],
+        }
     }
 
-    pub fn generate_chunk(&mut self, [chunk_x, chunk_z]: [usize; 2]) {
-        let elevation_map = map_generation::generate_chunk_elevation_map(
-            [chunk_x, chunk_z],
-            MIN_HEIGHT,
-            MAX_HEIGHT,
+    /// How often a sampled flower spreads a copy of itself onto a nearby
+    /// empty, grass-topped column.
+    const FLOWER_SPREAD_CHANCE: f32 = 1.0 / 4.0;
+    /// How far (in either horizontal axis) a spread flower can land from
+    /// its parent -- keeps the spread bounded to a small patch rather than
+    /// letting one flower reseed the whole map.
+    const FLOWER_SPREAD_RADIUS: i64 = 2;
+
+    fn grow_flower(&mut self, pos: [usize; 3], rng: &mut impl Rng) -> Vec<[usize; 3]> {
+        if rng.gen::<f32>() > Self::FLOWER_SPREAD_CHANCE {
+            return vec![];
+        }
+        let [x, y, z] = pos;
+        if y == 0 {
+            return vec![];
+        }
+
+        let dx = rng.gen_range(-Self::FLOWER_SPREAD_RADIUS..=Self::FLOWER_SPREAD_RADIUS);
+        let dz = rng.gen_range(-Self::FLOWER_SPREAD_RADIUS..=Self::FLOWER_SPREAD_RADIUS);
+        if dx == 0 && dz == 0 {
+            return vec![];
+        }
+        let (Some(nx), Some(nz)) = (
+            (x as i64 + dx).try_into().ok(),
+            (z as i64 + dz).try_into().ok(),
+        ) else {
+            return vec![];
+        };
+        let max_xz = MAX_CHUNK_WORLD_WIDTH * CHUNK_XZ_SIZE;
+        if nx >= max_xz || nz >= max_xz {
+            return vec![];
+        }
+
+        let target_empty = self.get_block(nx, y, nz).block_type == BlockType::Empty;
+        let below_grass = self.get_block(nx, y - 1, nz).block_type == BlockType::Grass;
+        if !target_empty || !below_grass {
+            return vec![];
+        }
+
+        let current_chunk_idx = [x / CHUNK_XZ_SIZE, z / CHUNK_XZ_SIZE];
+        self.queue_or_set_block(
+            current_chunk_idx,
+            [nx, y, nz],
+            BlockType::RedFlower,
+            OverwriteMode::IfEmpty,
         );
-        let (base_x, base_z) = (chunk_x * CHUNK_XZ_SIZE, chunk_z * CHUNK_XZ_SIZE);
-        // vprintln!(
-        //     "Took {}ms to generate elevation map",
-        //     func_start.elapsed().as_millis()
-        // );
+        vec![[nx, y, nz]]
+    }
 
-        for (z, x) in iproduct!(0..CHUNK_XZ_SIZE, 0..CHUNK_XZ_SIZE) {
-            let ground_elevation = elevation_map[x][z] as usize;
-            let (world_x, world_z) = (base_x + x, base_z + z);
-            let top_block_type = if ground_elevation < WATER_HEIGHT as usize {
-                BlockType::Sand
-            } else {
-                BlockType::Grass
-            };
-            set_block!(self, world_x, ground_elevation, world_z, top_block_type);
+    /// How often a sampled grass block spreads onto a cardinal-adjacent
+    /// dirt block with `Empty` directly above it.
+    const GRASS_SPREAD_CHANCE: f32 = 1.0 / 8.0;
 
-            let min_ground_or_water = core::cmp::min(ground_elevation, WATER_HEIGHT as usize);
-            for y in 0..min_ground_or_water {
-                set_block!(self, world_x, y, world_z, BlockType::Sand);
-            }
-            for y in min_ground_or_water..ground_elevation {
-                set_block!(self, world_x, y, world_z, BlockType::Dirt);
-            }
-            for y in (MIN_HEIGHT as usize)..(WATER_HEIGHT as usize) {
-                if self.get_block(world_x, y, world_z).block_type == BlockType::Empty {
-                    set_block!(self, world_x, y, world_z, BlockType::Water);
-                }
-            }
+    fn grow_grass(&mut self, pos: [usize; 3], rng: &mut impl Rng) -> Vec<[usize; 3]> {
+        if rng.gen::<f32>() > Self::GRASS_SPREAD_CHANCE {
+            return vec![];
+        }
+        let [x, y, z] = pos;
+        let max_xz = MAX_CHUNK_WORLD_WIDTH * CHUNK_XZ_SIZE;
+
+        let candidates: Vec<(usize, usize)> = [
+            (x + 1 < max_xz).then_some((x + 1, z)),
+            (x > 0).then_some((x - 1, z)),
+            (z + 1 < max_xz).then_some((x, z + 1)),
+            (z > 0).then_some((x, z - 1)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let Some(&(nx, nz)) = candidates.choose(rng) else {
+            return vec![];
+        };
 
-            if top_block_type == BlockType::Grass {
-                let did_generate_tree =
-                    self.maybe_generate_tree([world_x, ground_elevation, world_z]);
-                if !did_generate_tree {
-                    self.maybe_generate_flower([world_x, ground_elevation, world_z]);
-                }
-            }
+        let neighbor_is_dirt = self.get_block(nx, y, nz).block_type == BlockType::Dirt;
+        let neighbor_exposed =
+            y + 1 < CHUNK_Y_SIZE && self.get_block(nx, y + 1, nz).block_type == BlockType::Empty;
+        if !neighbor_is_dirt || !neighbor_exposed {
+            return vec![];
         }
 
-        self.get_chunk_mut([chunk_x, chunk_z]).is_generated = true;
+        let current_chunk_idx = [x / CHUNK_XZ_SIZE, z / CHUNK_XZ_SIZE];
+        self.queue_or_set_block(
+            current_chunk_idx,
+            [nx, y, nz],
+            BlockType::Grass,
+            OverwriteMode::Always,
+        );
+        vec![[nx, y, nz]]
+    }
+
+    /// How often a sampled sapling with enough vertical clearance grows
+    /// into a full tree.
+    const SAPLING_GROWTH_CHANCE: f32 = 1.0 / 50.0;
+    /// Vertical clearance (all-`Empty`) a sapling needs above it to grow --
+    /// tall enough for the tallest shape `tree_shape_for_biome` produces
+    /// (`Tundra`/`Snow`: trunk height 7, top leaf slice 7 blocks above the
+    /// sapling), with room to spare.
+    const SAPLING_GROWTH_CLEARANCE: usize = 11;
+
+    fn grow_sapling(&mut self, pos: [usize; 3], rng: &mut impl Rng) -> Vec<[usize; 3]> {
+        if rng.gen::<f32>() > Self::SAPLING_GROWTH_CHANCE {
+            return vec![];
+        }
+        let [x, y, z] = pos;
+        let clearance_top = (y + 1 + Self::SAPLING_GROWTH_CLEARANCE).min(CHUNK_Y_SIZE);
+        let has_clearance = (y + 1..clearance_top)
+            .all(|clear_y| self.get_block(x, clear_y, z).block_type == BlockType::Empty);
+        if !has_clearance {
+            return vec![];
+        }
+
+        let biome = self.biome_at(x, z);
+        let current_chunk_idx = [x / CHUNK_XZ_SIZE, z / CHUNK_XZ_SIZE];
+        let mut modified = Vec::new();
+        for (block_pos, block_type, overwrite_mode) in tree_blocks(pos, biome) {
+            self.queue_or_set_block(current_chunk_idx, block_pos, block_type, overwrite_mode);
+            modified.push(block_pos);
+        }
+        modified
     }
 
     pub fn maybe_allocate_chunk(&mut self, outer_chunk_idx: [usize; 2]) {
@@ -741,8 +2383,10 @@ impl WorldState {
             func_start.elapsed().as_millis()
         );
 
-        if !self.get_chunk(outer_chunk_idx).is_generated {
-            self.generate_chunk(outer_chunk_idx)
+        if !self.get_chunk(outer_chunk_idx).is_generated
+            && self.in_flight_chunks.insert(outer_chunk_idx)
+        {
+            self.chunk_gen_pool.request(outer_chunk_idx);
         }
 
         #[cfg(not(target_arch = "wasm32"))]
@@ -773,6 +2417,13 @@ impl WorldState {
         chunk.render_descriptor_idx
     }
 
+    /// Every currently-placed emissive block's light, for
+    /// `light::LightUniform::point_lights`. Order isn't meaningful; callers
+    /// just upload the whole list each frame.
+    pub fn point_lights(&self) -> Vec<light::PointLight> {
+        self.point_lights.values().copied().collect()
+    }
+
     pub fn get_chunk_order_by_distance(&self, camera: &Camera) -> Vec<[usize; 2]> {
         let mut chunk_order = self.iter_visible_chunks(camera).collect::<Vec<_>>();
 
@@ -797,6 +2448,15 @@ impl WorldState {
         chunk_order
     }
 
+    /// Candidate chunk columns are still the fixed `VISIBLE_CHUNK_WIDTH`
+    /// square centered on the camera, but each candidate is then filtered
+    /// through a frustum test so chunks behind the player never reach
+    /// `generate_world_data`/`compute_chunk_mesh` in the first place --
+    /// roughly halving mesh work when the player isn't looking straight
+    /// down. The frustum is built once per call from `camera.view_proj`
+    /// (world-space, unlike `build_view_projection_matrix`'s camera-relative
+    /// matrix) via the same row-extraction `frustum::Frustum` that
+    /// `light::LightUniform::light_frustum` uses for shadow-map culling.
     fn iter_visible_chunks(&self, camera: &Camera) -> std::vec::IntoIter<[usize; 2]> {
         let (camera_chunk_x, camera_chunk_z) = (
             (camera.eye.x / CHUNK_XZ_SIZE as f32) as usize,
@@ -805,12 +2465,28 @@ impl WorldState {
         let first_chunk_x_index = camera_chunk_x - (VISIBLE_CHUNK_WIDTH / 2);
         let first_chunk_z_index = camera_chunk_z - (VISIBLE_CHUNK_WIDTH / 2);
 
+        let view_proj_cols: [[f32; 4]; 4] = camera.view_proj.into();
+        let camera_frustum = frustum::Frustum::from_matrix(glam::Mat4::from_cols_array_2d(&view_proj_cols));
+
         let mut chunk_idxs: Vec<[usize; 2]> = vec![];
         for (chunk_x, chunk_z) in iproduct!(
             first_chunk_x_index..first_chunk_x_index + VISIBLE_CHUNK_WIDTH,
             first_chunk_z_index..first_chunk_z_index + VISIBLE_CHUNK_WIDTH
         ) {
-            chunk_idxs.push([chunk_x, chunk_z]);
+            let min = glam::Vec3::new(
+                (chunk_x * CHUNK_XZ_SIZE) as f32,
+                0.0,
+                (chunk_z * CHUNK_XZ_SIZE) as f32,
+            );
+            let max = min
+                + glam::Vec3::new(
+                    CHUNK_XZ_SIZE as f32,
+                    CHUNK_Y_SIZE as f32,
+                    CHUNK_XZ_SIZE as f32,
+                );
+            if camera_frustum.intersects_aabb(min, max) {
+                chunk_idxs.push([chunk_x, chunk_z]);
+            }
         }
 
         chunk_idxs.into_iter()
@@ -821,18 +2497,7 @@ impl WorldState {
         chunk_idx: [usize; 2],
         camera: &Camera,
     ) -> [usize; 2] {
-        let [world_chunk_x, world_chunk_z] = chunk_idx;
-        let (camera_chunk_x, camera_chunk_z) = (
-            (camera.eye.x / CHUNK_XZ_SIZE as f32) as usize,
-            (camera.eye.z / CHUNK_XZ_SIZE as f32) as usize,
-        );
-        let first_chunk_x_index = camera_chunk_x - (VISIBLE_CHUNK_WIDTH / 2);
-        let first_chunk_z_index = camera_chunk_z - (VISIBLE_CHUNK_WIDTH / 2);
-
-        [
-            world_chunk_x - first_chunk_x_index,
-            world_chunk_z - first_chunk_z_index,
-        ]
+        camera_relative_chunk_position(chunk_idx, camera.eye)
     }
 
     pub fn generate_world_data(&mut self, camera: &Camera) -> (Vec2d<ChunkData>, Vec<[usize; 2]>) {
@@ -851,12 +2516,37 @@ impl WorldState {
             [VISIBLE_CHUNK_WIDTH, VISIBLE_CHUNK_WIDTH],
         );
 
-        let mut abs_chunk_iter = self.iter_visible_chunks(camera);
-        for (rel_chunk_x, rel_chunk_z) in iproduct!(0..VISIBLE_CHUNK_WIDTH, 0..VISIBLE_CHUNK_WIDTH)
-        {
-            let [abs_chunk_x, abs_chunk_z] = abs_chunk_iter.next().unwrap();
-            all_chunk_data[[rel_chunk_x, rel_chunk_z]] =
-                self.compute_chunk_mesh([abs_chunk_x, abs_chunk_z], camera);
+        // Only the frustum-surviving chunks are meshed; each lands in its
+        // camera-relative slot, and the rest keep the empty default above.
+        let rel_to_abs_chunk: Vec<([usize; 2], [usize; 2])> = self
+            .iter_visible_chunks(camera)
+            .map(|abs_chunk_idx| {
+                (
+                    camera_relative_chunk_position(abs_chunk_idx, camera.eye),
+                    abs_chunk_idx,
+                )
+            })
+            .collect();
+
+        // Chunk allocation mutates `self.chunks`/`self.chunk_indices`, so it
+        // has to run single-threaded before the parallel meshing pass below
+        // can borrow `self` read-only from multiple rayon threads at once.
+        for (_, abs_chunk_idx) in &rel_to_abs_chunk {
+            self.maybe_allocate_chunk(*abs_chunk_idx);
+        }
+
+        let computed_chunk_data: Vec<([usize; 2], ChunkData)> = rel_to_abs_chunk
+            .par_iter()
+            .map(|(rel_chunk_idx, abs_chunk_idx)| {
+                (
+                    *rel_chunk_idx,
+                    self.compute_chunk_mesh_readonly(*abs_chunk_idx, camera),
+                )
+            })
+            .collect();
+
+        for (rel_chunk_idx, chunk_data) in computed_chunk_data {
+            all_chunk_data[rel_chunk_idx] = chunk_data;
         }
 
         #[cfg(not(target_arch = "wasm32"))]
@@ -873,386 +2563,577 @@ impl WorldState {
 
     pub fn compute_chunk_mesh(&mut self, chunk_idx: [usize; 2], camera: &Camera) -> ChunkData {
         self.maybe_allocate_chunk(chunk_idx);
+        self.compute_chunk_mesh_readonly(chunk_idx, camera)
+    }
 
-        use cgmath::{Deg, Quaternion};
-
-        let no_rotation: Quaternion<f32> = Quaternion::from_axis_angle(Vector3::unit_y(), Deg(0.0));
-        let flip_to_top: Quaternion<f32> =
-            Quaternion::from_axis_angle(Vector3::unit_x(), Deg(180.0));
-        let flip_to_front: Quaternion<f32> =
-            Quaternion::from_axis_angle(Vector3::unit_x(), Deg(90.0));
-        let flip_to_back: Quaternion<f32> =
-            Quaternion::from_axis_angle(Vector3::unit_x(), Deg(-90.0))
-                * Quaternion::from_axis_angle(Vector3::unit_y(), Deg(180.0));
-        let flip_to_left: Quaternion<f32> =
-            Quaternion::from_axis_angle(Vector3::unit_z(), Deg(90.0))
-                * Quaternion::from_axis_angle(Vector3::unit_y(), Deg(-90.0));
-        let flip_to_right: Quaternion<f32> =
-            Quaternion::from_axis_angle(Vector3::unit_z(), Deg(-90.0))
-                * Quaternion::from_axis_angle(Vector3::unit_y(), Deg(90.0));
-
-        let flip_to_diagonal_right_front: Quaternion<f32> =
-            Quaternion::from_axis_angle(Vector3::unit_x(), Deg(90.0))
-                * Quaternion::from_axis_angle(Vector3::unit_z(), Deg(45.0));
-        let flip_to_diagonal_left_front: Quaternion<f32> =
-            Quaternion::from_axis_angle(Vector3::unit_x(), Deg(-90.0))
-                * Quaternion::from_axis_angle(Vector3::unit_y(), Deg(180.0))
-                * Quaternion::from_axis_angle(Vector3::unit_z(), Deg(-45.0));
-        let flip_to_diagonal_right_back: Quaternion<f32> =
-            Quaternion::from_axis_angle(Vector3::unit_x(), Deg(-90.0))
-                * Quaternion::from_axis_angle(Vector3::unit_y(), Deg(180.0))
-                * Quaternion::from_axis_angle(Vector3::unit_z(), Deg(45.0));
-        let flip_to_diagonal_left_back: Quaternion<f32> =
-            Quaternion::from_axis_angle(Vector3::unit_x(), Deg(90.0))
-                * Quaternion::from_axis_angle(Vector3::unit_z(), Deg(-45.0));
-
-        let mut opaque_instances = Vec::<InstanceRaw>::with_capacity(4096);
-        let mut opaque_instance_distances = Vec::<i32>::with_capacity(4096);
-
-        let mut translucent_instances = Vec::<InstanceRaw>::with_capacity(4096);
-        let mut translucent_instance_distances = Vec::<i32>::with_capacity(4096);
-
-        let mut semi_translucent_instances = Vec::<InstanceRaw>::with_capacity(4096);
-        let mut semi_translucent_instance_distances = Vec::<i32>::with_capacity(4096);
+    /// The meshing half of `compute_chunk_mesh`, split out so it only needs
+    /// `&self`: it reads `chunk_idx`'s blocks and never allocates, so callers
+    /// that have already run `maybe_allocate_chunk` for every chunk they
+    /// touch can call this from multiple rayon threads at once (see
+    /// `generate_world_data`). `ChunkMeshPool` workers can't borrow `&self`
+    /// across the thread boundary at all, so `dispatch_chunk_mesh` instead
+    /// snapshots what this delegates to `mesh_chunk` with into an owned
+    /// `MeshRequest`.
+    pub(crate) fn compute_chunk_mesh_readonly(
+        &self,
+        chunk_idx: [usize; 2],
+        camera: &Camera,
+    ) -> ChunkData {
+        mesh_chunk(chunk_idx, &self.get_chunk(chunk_idx).blocks, camera.eye)
+    }
 
-        let chunk = self.get_chunk(chunk_idx);
+    /// Queues `chunk_idx` onto `chunk_mesh_pool` instead of meshing it
+    /// inline, so `update_tick`'s per-frame remesh pass doesn't block on
+    /// it -- the dirty-chunk counterpart to `maybe_allocate_chunk` queueing
+    /// generation onto `chunk_gen_pool`. A no-op if `chunk_idx` already has
+    /// a mesh request in flight.
+    pub fn dispatch_chunk_mesh(&mut self, chunk_idx: [usize; 2], camera: &Camera) {
+        if !self.in_flight_mesh_chunks.insert(chunk_idx) {
+            return;
+        }
+        self.chunk_mesh_pool.request(MeshRequest {
+            chunk_idx,
+            blocks: self.get_chunk(chunk_idx).blocks.clone(),
+            camera_eye: camera.eye,
+        });
+    }
 
-        let [chunk_x, chunk_z] = chunk_idx;
+    /// Drains whatever `dispatch_chunk_mesh` requests have finished meshing
+    /// since the last call, clearing each from `in_flight_mesh_chunks` so it
+    /// can be re-dispatched the next time it goes dirty.
+    pub fn drain_meshed_chunks(&mut self) -> Vec<ChunkData> {
+        let chunk_datas = self.chunk_mesh_pool.tick();
+        for chunk_data in &chunk_datas {
+            self.in_flight_mesh_chunks.remove(&chunk_data.position);
+        }
+        chunk_datas
+    }
+}
 
-        // Don't use !iproduct here to squeeze out a tiny bit of perf
-        for chunk_rel_z in 0..CHUNK_XZ_SIZE {
-            for chunk_rel_x in 0..CHUNK_XZ_SIZE {
-                for y in 0..CHUNK_Y_SIZE {
-                    let world_x = (chunk_x * CHUNK_XZ_SIZE) + chunk_rel_x;
-                    let world_z = (chunk_z * CHUNK_XZ_SIZE) + chunk_rel_z;
+/// Shared by `WorldState::camera_relative_position_from_world_position` and
+/// `mesh_chunk`'s `ChunkData::camera_relative_position` field -- both need to
+/// place `chunk_idx` in the same camera-centered grid that
+/// `iter_visible_chunks` generates candidates from, just given `camera_eye`
+/// directly instead of a whole `&Camera` so `mesh_chunk` can call it with the
+/// `camera_eye` a `MeshRequest` snapshot carries.
+fn camera_relative_chunk_position(chunk_idx: [usize; 2], camera_eye: Point3<f32>) -> [usize; 2] {
+    let [world_chunk_x, world_chunk_z] = chunk_idx;
+    let (camera_chunk_x, camera_chunk_z) = (
+        (camera_eye.x / CHUNK_XZ_SIZE as f32) as usize,
+        (camera_eye.z / CHUNK_XZ_SIZE as f32) as usize,
+    );
+    let first_chunk_x_index = camera_chunk_x - (VISIBLE_CHUNK_WIDTH / 2);
+    let first_chunk_z_index = camera_chunk_z - (VISIBLE_CHUNK_WIDTH / 2);
+
+    [
+        world_chunk_x - first_chunk_x_index,
+        world_chunk_z - first_chunk_z_index,
+    ]
+}
 
-                    let position = cgmath::Vector3::new(world_x as f32, y as f32, world_z as f32);
-                    let block = chunk.blocks.get_unchecked(chunk_rel_x, y, chunk_rel_z);
-                    if block.block_type == BlockType::Empty {
-                        continue;
-                    }
+/// Pure meshing logic shared by `WorldState::compute_chunk_mesh_readonly`
+/// (borrows straight from `WorldState`) and `ChunkMeshPool`'s workers
+/// (given an owned `MeshRequest` snapshot instead, since they can't borrow
+/// `WorldState` across the thread boundary). Emits one `InstanceRaw` per
+/// exposed block face, one `TypedInstances` each for the `Opaque`,
+/// `Translucent`, `SemiTranslucent` and `BinaryTransparency` buckets, each
+/// back-to-front sorted by distance from `camera_eye`.
+///
+/// This is deliberately per-face, not per-merged-run: `mesh::greedy_mesh`
+/// already implements the mask-and-merge algorithm that would turn a run of
+/// coplanar exposed faces into one quad (see its doc comment), but that
+/// module's output is a vertex/index buffer of arbitrarily-sized quads,
+/// while every face here becomes a fixed-size `InstanceRaw` -- a rotated,
+/// translated copy of the single unit quad `Face::new` builds once and
+/// `shader.wgsl`/`shadow_map.wgsl` read atlas-relative `tex_coord`s out of.
+/// Collapsing a merged run into one instance needs a non-unit scale those
+/// two shaders don't currently read, and neither is part of this checkout to
+/// extend -- so for now each block face still costs its own instance here;
+/// hooking `mesh::greedy_mesh` up to this path is gated on that shader work.
+fn mesh_chunk(chunk_idx: [usize; 2], blocks: &ChunkBlocks, camera_eye: Point3<f32>) -> ChunkData {
+    use cgmath::{Deg, Quaternion};
+
+    let no_rotation: Quaternion<f32> = Quaternion::from_axis_angle(Vector3::unit_y(), Deg(0.0));
+    let flip_to_top: Quaternion<f32> =
+        Quaternion::from_axis_angle(Vector3::unit_x(), Deg(180.0));
+    let flip_to_front: Quaternion<f32> =
+        Quaternion::from_axis_angle(Vector3::unit_x(), Deg(90.0));
+    let flip_to_back: Quaternion<f32> =
+        Quaternion::from_axis_angle(Vector3::unit_x(), Deg(-90.0))
+            * Quaternion::from_axis_angle(Vector3::unit_y(), Deg(180.0));
+    let flip_to_left: Quaternion<f32> =
+        Quaternion::from_axis_angle(Vector3::unit_z(), Deg(90.0))
+            * Quaternion::from_axis_angle(Vector3::unit_y(), Deg(-90.0));
+    let flip_to_right: Quaternion<f32> =
+        Quaternion::from_axis_angle(Vector3::unit_z(), Deg(-90.0))
+            * Quaternion::from_axis_angle(Vector3::unit_y(), Deg(90.0));
+
+    let flip_to_diagonal_right_front: Quaternion<f32> =
+        Quaternion::from_axis_angle(Vector3::unit_x(), Deg(90.0))
+            * Quaternion::from_axis_angle(Vector3::unit_z(), Deg(45.0));
+    let flip_to_diagonal_left_front: Quaternion<f32> =
+        Quaternion::from_axis_angle(Vector3::unit_x(), Deg(-90.0))
+            * Quaternion::from_axis_angle(Vector3::unit_y(), Deg(180.0))
+            * Quaternion::from_axis_angle(Vector3::unit_z(), Deg(-45.0));
+    let flip_to_diagonal_right_back: Quaternion<f32> =
+        Quaternion::from_axis_angle(Vector3::unit_x(), Deg(-90.0))
+            * Quaternion::from_axis_angle(Vector3::unit_y(), Deg(180.0))
+            * Quaternion::from_axis_angle(Vector3::unit_z(), Deg(45.0));
+    let flip_to_diagonal_left_back: Quaternion<f32> =
+        Quaternion::from_axis_angle(Vector3::unit_x(), Deg(90.0))
+            * Quaternion::from_axis_angle(Vector3::unit_z(), Deg(-45.0));
+
+    let mut opaque_instances = Vec::<InstanceRaw>::with_capacity(4096);
+    let mut opaque_instance_distances = Vec::<i32>::with_capacity(4096);
+
+    let mut translucent_instances = Vec::<InstanceRaw>::with_capacity(4096);
+    let mut translucent_instance_distances = Vec::<i32>::with_capacity(4096);
+
+    let mut semi_translucent_instances = Vec::<InstanceRaw>::with_capacity(4096);
+    let mut semi_translucent_instance_distances = Vec::<i32>::with_capacity(4096);
+
+    let mut binary_transparent_instances = Vec::<InstanceRaw>::with_capacity(4096);
+    let mut binary_transparent_instance_distances = Vec::<i32>::with_capacity(4096);
+
+    let [chunk_x, chunk_z] = chunk_idx;
+
+    // Don't use !iproduct here to squeeze out a tiny bit of perf
+    for chunk_rel_z in 0..CHUNK_XZ_SIZE {
+        for chunk_rel_x in 0..CHUNK_XZ_SIZE {
+            let world_x = (chunk_x * CHUNK_XZ_SIZE) + chunk_rel_x;
+            let world_z = (chunk_z * CHUNK_XZ_SIZE) + chunk_rel_z;
+
+            // Sampled once per column, not per block/face: grass/foliage
+            // tint depends on biome and elevation, not on anything that
+            // varies within a single (x, z) column.
+            let biome = map_generation::sample_biome(WORLD_SEED, world_x, world_z).biome;
+
+            for y in 0..CHUNK_Y_SIZE {
+                let position = cgmath::Vector3::new(world_x as f32, y as f32, world_z as f32);
+                let block = blocks.get_unchecked(chunk_rel_x, y, chunk_rel_z);
+                if block.block_type == BlockType::Empty {
+                    continue;
+                }
 
-                    let mut highlight_adjust = 1.0;
-                    if let Some(highlighted_block) = self.highlighted_block {
-                        if highlighted_block == [world_x, y, world_z] {
-                            highlight_adjust = 1.8;
+                // Brightness from a light level (see `Block::light_level`),
+                // floored so fully dark blocks are still dimly visible
+                // rather than pure black.
+                let brightness_from_level =
+                    |level: u8| -> f32 { 0.2 + 0.8 * (level as f32 / 15.0) };
+                let brightness = brightness_from_level(block.light_level());
+
+                // Per-face brightness, sampled from the voxel just outside
+                // that face instead of this block's own level, so a dim
+                // block lit from one side (e.g. a cave wall) shades smoothly
+                // instead of uniformly -- see `WorldState`'s light
+                // propagation methods for how light levels get there. A
+                // `mesh_chunk` call only has this chunk's own `blocks` (see
+                // `MeshRequest`), so a face on this chunk's own xz border
+                // can't see across the seam and falls back to this block's
+                // own level there.
+                let face_brightness = |face: Face| -> f32 {
+                    let neighbor = match face {
+                        Face::Top if y + 1 < CHUNK_Y_SIZE => {
+                            Some((chunk_rel_x, y + 1, chunk_rel_z))
                         }
-                    }
-
-                    let [top_offset, bottom_offset, side_offset] =
-                        block.block_type.texture_atlas_offsets();
-                    let alpha_adjust = if block.block_type == BlockType::Water {
-                        0.7
-                    } else {
-                        1.0
+                        Face::Bottom if y > 0 => Some((chunk_rel_x, y - 1, chunk_rel_z)),
+                        Face::Left if chunk_rel_x + 1 < CHUNK_XZ_SIZE => {
+                            Some((chunk_rel_x + 1, y, chunk_rel_z))
+                        }
+                        Face::Right if chunk_rel_x > 0 => Some((chunk_rel_x - 1, y, chunk_rel_z)),
+                        Face::Front if chunk_rel_z + 1 < CHUNK_XZ_SIZE => {
+                            Some((chunk_rel_x, y, chunk_rel_z + 1))
+                        }
+                        Face::Back if chunk_rel_z > 0 => Some((chunk_rel_x, y, chunk_rel_z - 1)),
+                        _ => None,
                     };
+                    let level = neighbor
+                        .map(|(nx, ny, nz)| blocks.get_unchecked(nx, ny, nz).light_level())
+                        .unwrap_or_else(|| block.light_level());
+                    brightness_from_level(level)
+                };
 
-                    let (instance_vec, distance_vec) = if block.block_type.is_semi_translucent() {
-                        (
-                            &mut semi_translucent_instances,
-                            &mut semi_translucent_instance_distances,
-                        )
-                    } else if block.block_type.is_translucent() {
-                        (
-                            &mut translucent_instances,
-                            &mut translucent_instance_distances,
-                        )
-                    } else {
-                        (&mut opaque_instances, &mut opaque_instance_distances)
-                    };
+                let [top_offset, bottom_offset, side_offset] =
+                    block.block_type.texture_atlas_offsets();
+                let [top_tint_type, bottom_tint_type, side_tint_type] =
+                    block.block_type.tint_types();
+                let top_tint = tint_multiplier(top_tint_type, biome, y);
+                let bottom_tint = tint_multiplier(bottom_tint_type, biome, y);
+                let side_tint = tint_multiplier(side_tint_type, biome, y);
+                let alpha_adjust = if block.block_type == BlockType::Water {
+                    0.7
+                } else {
+                    1.0
+                };
 
-                    let distance_from_camera = (camera.eye - cgmath::Vector3::new(0.5, 0.5, 0.5))
-                        .distance((world_x as f32, y as f32, world_z as f32).into());
+                let (instance_vec, distance_vec) = if block.block_type.is_semi_translucent() {
+                    (
+                        &mut semi_translucent_instances,
+                        &mut semi_translucent_instance_distances,
+                    )
+                } else if block.block_type.is_binary_transparent() {
+                    (
+                        &mut binary_transparent_instances,
+                        &mut binary_transparent_instance_distances,
+                    )
+                } else if block.block_type.is_translucent() {
+                    (
+                        &mut translucent_instances,
+                        &mut translucent_instance_distances,
+                    )
+                } else {
+                    (&mut opaque_instances, &mut opaque_instance_distances)
+                };
 
-                    let half_diag_shift = (1.0 - (1.0 / 2.0_f32.sqrt())) / 2.0;
+                let distance_from_camera = (camera_eye - cgmath::Vector3::new(0.5, 0.5, 0.5))
+                    .distance((world_x as f32, y as f32, world_z as f32).into());
+
+                let half_diag_shift = (1.0 - (1.0 / 2.0_f32.sqrt())) / 2.0;
+
+                if block.block_type.is_sprite() {
+                    // left cross, front-face
+                    instance_vec.push(InstanceRaw::new(
+                        position
+                            + cgmath::Vector3::new(1.0 - half_diag_shift, 1.0, half_diag_shift),
+                        flip_to_diagonal_left_front,
+                        side_offset,
+                        (cgmath::Vector4::new(0.7, 0.7, 0.7, alpha_adjust) * brightness).into(),
+                    ));
+                    distance_vec.push(-distance_from_camera as i32);
+                    // right cross, front-face
+                    instance_vec.push(InstanceRaw::new(
+                        position + cgmath::Vector3::new(half_diag_shift, 1.0, half_diag_shift),
+                        flip_to_diagonal_right_front,
+                        side_offset,
+                        (cgmath::Vector4::new(0.7, 0.7, 0.7, alpha_adjust) * brightness).into(),
+                    ));
+                    distance_vec.push(-distance_from_camera as i32);
+                    // left cross, back-face
+                    instance_vec.push(InstanceRaw::new(
+                        position
+                            + cgmath::Vector3::new(half_diag_shift, 1.0, 1.0 - half_diag_shift),
+                        flip_to_diagonal_left_back,
+                        side_offset,
+                        (cgmath::Vector4::new(0.7, 0.7, 0.7, alpha_adjust) * brightness).into(),
+                    ));
+                    distance_vec.push(-distance_from_camera as i32);
+                    // right cross, back-face
+                    instance_vec.push(InstanceRaw::new(
+                        position
+                            + cgmath::Vector3::new(
+                                1.0 - half_diag_shift,
+                                1.0,
+                                1.0 - half_diag_shift,
+                            ),
+                        flip_to_diagonal_right_back,
+                        side_offset,
+                        (cgmath::Vector4::new(0.7, 0.7, 0.7, alpha_adjust) * brightness).into(),
+                    ));
+                    distance_vec.push(-distance_from_camera as i32);
+                } else {
+                    if !block.neighbors.get(Face::Top) {
+                        let y_offset = if block.block_type == BlockType::Water {
+                            WATER_BLOCK_Y_HEIGHT
+                        } else {
+                            1.0
+                        };
+                        instance_vec.push(InstanceRaw::new(
+                            position + cgmath::Vector3::new(0.0, y_offset, 1.0),
+                            flip_to_top,
+                            top_offset,
+                            (cgmath::Vector4::new(
+                                top_tint[0],
+                                top_tint[1],
+                                top_tint[2],
+                                alpha_adjust,
+                            ) * face_brightness(Face::Top))
+                            .into(),
+                        ));
 
-                    if block.block_type.is_sprite() {
-                        // left cross, front-face
+                        // N.B.
+                        // - store negative value because we want further instances to be drawn first
+                        // - lose float precision to gain speed in sorting (I did not benchmark this, could be useless)
+                        distance_vec.push(-distance_from_camera as i32);
+                    }
+                    if !block.neighbors.get(Face::Bottom) {
+                        instance_vec.push(InstanceRaw::new(
+                            position,
+                            no_rotation,
+                            bottom_offset,
+                            (cgmath::Vector4::new(
+                                bottom_tint[0],
+                                bottom_tint[1],
+                                bottom_tint[2],
+                                alpha_adjust,
+                            ) * face_brightness(Face::Bottom))
+                            .into(),
+                        ));
+                        distance_vec.push(-distance_from_camera as i32);
+                    }
+                    if !block.neighbors.get(Face::Left) {
                         instance_vec.push(InstanceRaw::new(
-                            position
-                                + cgmath::Vector3::new(1.0 - half_diag_shift, 1.0, half_diag_shift),
-                            flip_to_diagonal_left_front,
+                            position + cgmath::Vector3::new(1.0, 1.0, 0.0),
+                            flip_to_left,
                             side_offset,
-                            (cgmath::Vector4::new(0.7, 0.7, 0.7, alpha_adjust) * highlight_adjust)
-                                .into(),
+                            (cgmath::Vector4::new(
+                                0.7 * side_tint[0],
+                                0.7 * side_tint[1],
+                                0.7 * side_tint[2],
+                                alpha_adjust,
+                            ) * face_brightness(Face::Left))
+                            .into(),
                         ));
                         distance_vec.push(-distance_from_camera as i32);
-                        // right cross, front-face
+                    }
+                    if !block.neighbors.get(Face::Right) {
                         instance_vec.push(InstanceRaw::new(
-                            position + cgmath::Vector3::new(half_diag_shift, 1.0, half_diag_shift),
-                            flip_to_diagonal_right_front,
+                            position + cgmath::Vector3::new(0.0, 1.0, 1.0),
+                            flip_to_right,
                             side_offset,
-                            (cgmath::Vector4::new(0.7, 0.7, 0.7, alpha_adjust) * highlight_adjust)
-                                .into(),
+                            (cgmath::Vector4::new(
+                                0.7 * side_tint[0],
+                                0.7 * side_tint[1],
+                                0.7 * side_tint[2],
+                                alpha_adjust,
+                            ) * face_brightness(Face::Right))
+                            .into(),
                         ));
                         distance_vec.push(-distance_from_camera as i32);
-                        // left cross, back-face
+                    }
+                    if !block.neighbors.get(Face::Front) {
                         instance_vec.push(InstanceRaw::new(
-                            position
-                                + cgmath::Vector3::new(half_diag_shift, 1.0, 1.0 - half_diag_shift),
-                            flip_to_diagonal_left_back,
+                            position + cgmath::Vector3::new(1.0, 1.0, 1.0),
+                            flip_to_back,
                             side_offset,
-                            (cgmath::Vector4::new(0.7, 0.7, 0.7, alpha_adjust) * highlight_adjust)
-                                .into(),
+                            (cgmath::Vector4::new(
+                                0.8 * side_tint[0],
+                                0.8 * side_tint[1],
+                                0.8 * side_tint[2],
+                                alpha_adjust,
+                            ) * face_brightness(Face::Front))
+                            .into(),
                         ));
                         distance_vec.push(-distance_from_camera as i32);
-                        // right cross, back-face
+                    }
+                    if !block.neighbors.get(Face::Back) {
                         instance_vec.push(InstanceRaw::new(
-                            position
-                                + cgmath::Vector3::new(
-                                    1.0 - half_diag_shift,
-                                    1.0,
-                                    1.0 - half_diag_shift,
-                                ),
-                            flip_to_diagonal_right_back,
+                            position + cgmath::Vector3::new(0.0, 1.0, 0.0),
+                            flip_to_front,
                             side_offset,
-                            (cgmath::Vector4::new(0.7, 0.7, 0.7, alpha_adjust) * highlight_adjust)
-                                .into(),
+                            (cgmath::Vector4::new(
+                                0.8 * side_tint[0],
+                                0.8 * side_tint[1],
+                                0.8 * side_tint[2],
+                                alpha_adjust,
+                            ) * face_brightness(Face::Back))
+                            .into(),
                         ));
                         distance_vec.push(-distance_from_camera as i32);
-                    } else {
-                        if !block.neighbors.get(Face::Top) {
-                            let y_offset = if block.block_type == BlockType::Water {
-                                WATER_BLOCK_Y_HEIGHT
-                            } else {
-                                1.0
-                            };
-                            instance_vec.push(InstanceRaw::new(
-                                position + cgmath::Vector3::new(0.0, y_offset, 1.0),
-                                flip_to_top,
-                                top_offset,
-                                (cgmath::Vector4::new(1.0, 1.0, 1.0, alpha_adjust)
-                                    * highlight_adjust)
-                                    .into(),
-                            ));
-
-                            // N.B.
-                            // - store negative value because we want further instances to be drawn first
-                            // - lose float precision to gain speed in sorting (I did not benchmark this, could be useless)
-                            distance_vec.push(-distance_from_camera as i32);
-                        }
-                        if !block.neighbors.get(Face::Bottom) {
-                            instance_vec.push(InstanceRaw::new(
-                                position,
-                                no_rotation,
-                                bottom_offset,
-                                (cgmath::Vector4::new(1.0, 1.0, 1.0, alpha_adjust)
-                                    * highlight_adjust)
-                                    .into(),
-                            ));
-                            distance_vec.push(-distance_from_camera as i32);
-                        }
-                        if !block.neighbors.get(Face::Left) {
-                            instance_vec.push(InstanceRaw::new(
-                                position + cgmath::Vector3::new(1.0, 1.0, 0.0),
-                                flip_to_left,
-                                side_offset,
-                                (cgmath::Vector4::new(0.7, 0.7, 0.7, alpha_adjust)
-                                    * highlight_adjust)
-                                    .into(),
-                            ));
-                            distance_vec.push(-distance_from_camera as i32);
-                        }
-                        if !block.neighbors.get(Face::Right) {
-                            instance_vec.push(InstanceRaw::new(
-                                position + cgmath::Vector3::new(0.0, 1.0, 1.0),
-                                flip_to_right,
-                                side_offset,
-                                (cgmath::Vector4::new(0.7, 0.7, 0.7, alpha_adjust)
-                                    * highlight_adjust)
-                                    .into(),
-                            ));
-                            distance_vec.push(-distance_from_camera as i32);
-                        }
-                        if !block.neighbors.get(Face::Front) {
-                            instance_vec.push(InstanceRaw::new(
-                                position + cgmath::Vector3::new(1.0, 1.0, 1.0),
-                                flip_to_back,
-                                side_offset,
-                                (cgmath::Vector4::new(0.8, 0.8, 0.8, alpha_adjust)
-                                    * highlight_adjust)
-                                    .into(),
-                            ));
-                            distance_vec.push(-distance_from_camera as i32);
-                        }
-                        if !block.neighbors.get(Face::Back) {
-                            instance_vec.push(InstanceRaw::new(
-                                position + cgmath::Vector3::new(0.0, 1.0, 0.0),
-                                flip_to_front,
-                                side_offset,
-                                (cgmath::Vector4::new(0.8, 0.8, 0.8, alpha_adjust)
-                                    * highlight_adjust)
-                                    .into(),
-                            ));
-                            distance_vec.push(-distance_from_camera as i32);
-                        }
                     }
                 }
             }
         }
-
-        permutation::sort(&translucent_instance_distances)
-            .apply_slice_in_place(&mut translucent_instances);
-        permutation::sort(&semi_translucent_instance_distances)
-            .apply_slice_in_place(&mut semi_translucent_instances);
-        permutation::sort(&opaque_instance_distances).apply_slice_in_place(&mut opaque_instances);
-
-        ChunkData {
-            position: chunk_idx,
-            camera_relative_position: self
-                .camera_relative_position_from_world_position(chunk_idx, camera),
-            typed_instances_vec: vec![
-                TypedInstances {
-                    data_type: ChunkDataType::Opaque,
-                    instance_data: opaque_instances,
-                },
-                TypedInstances {
-                    data_type: ChunkDataType::Translucent,
-                    instance_data: translucent_instances,
-                },
-                TypedInstances {
-                    data_type: ChunkDataType::SemiTranslucent,
-                    instance_data: semi_translucent_instances,
-                },
-            ],
-        }
     }
 
-    pub fn highlight_colliding_block(&mut self, camera: &Camera) -> Vec<[usize; 2]> {
-        let mut modified_chunks: Vec<[usize; 2]> = vec![];
-
-        let prev_highlighted_chunk = self.highlighted_chunk;
-        if let Some(chunk_idx) = prev_highlighted_chunk {
-            modified_chunks.push(chunk_idx);
-        }
+    permutation::sort(&translucent_instance_distances)
+        .apply_slice_in_place(&mut translucent_instances);
+    permutation::sort(&semi_translucent_instance_distances)
+        .apply_slice_in_place(&mut semi_translucent_instances);
+    permutation::sort(&opaque_instance_distances).apply_slice_in_place(&mut opaque_instances);
+    permutation::sort(&binary_transparent_instance_distances)
+        .apply_slice_in_place(&mut binary_transparent_instances);
+
+    ChunkData {
+        position: chunk_idx,
+        camera_relative_position: camera_relative_chunk_position(chunk_idx, camera_eye),
+        typed_instances_vec: vec![
+            TypedInstances {
+                data_type: ChunkDataType::Opaque,
+                instance_data: opaque_instances,
+            },
+            TypedInstances {
+                data_type: ChunkDataType::Translucent,
+                instance_data: translucent_instances,
+            },
+            TypedInstances {
+                data_type: ChunkDataType::SemiTranslucent,
+                instance_data: semi_translucent_instances,
+            },
+            TypedInstances {
+                data_type: ChunkDataType::BinaryTransparency,
+                instance_data: binary_transparent_instances,
+            },
+        ],
+    }
+}
 
-        let collision = match self.get_colliding_block(camera, MAX_BREAK_DISTANCE) {
+impl WorldState {
+    /// Updates `highlighted_block` from whatever's under the crosshair. No
+    /// longer reports affected chunks to remesh -- the selection indicator
+    /// is a dedicated wireframe mesh now (see `selection_outline_vertex_data`)
+    /// rather than a brightness multiplier baked into the targeted block's
+    /// own face colors, so highlighting a block no longer needs to touch its
+    /// chunk's mesh at all.
+    pub fn highlight_colliding_block(&mut self, camera: &Camera) {
+        let collision = match self.raycast_voxel(camera, MAX_BREAK_DISTANCE) {
             Some(collision) => collision,
             None => {
-                self.highlighted_chunk = None;
                 self.highlighted_block = None;
-
-                return modified_chunks;
+                return;
             }
         };
 
-        let colliding_chunk = [
-            (collision.block_pos.x / CHUNK_XZ_SIZE) as usize,
-            (collision.block_pos.z / CHUNK_XZ_SIZE) as usize,
-        ];
-        modified_chunks.push(colliding_chunk);
-        self.highlighted_chunk = Some(colliding_chunk);
         self.highlighted_block = Some([
             collision.block_pos.x,
             collision.block_pos.y,
             collision.block_pos.z,
         ]);
+    }
 
-        modified_chunks
-    }
-
-    // Ray intersection algo pseudocode:
-    //   start at eye e
-    //   all_candidate_cubes = []
-    //   repeat for N steps  # N = 20ish
-    //     add unit vector in direction t  # t = target
-    //     for all possible intersecting cubes  # possible intersection means we added/subtracted 1 to an axis
-    //       add cube to all_candidate_cubes
-    //   colliding_cubes = []
-    //   for cube in all_candidate_cubes:
-    //     if cube doesn't exist, skip
-    //     if cube exists
-    //       check intersection using ray tracing linear algebra  # https://www.scratchapixel.com/lessons/3d-basic-rendering/minimal-ray-tracer-rendering-simple-shapes/ray-box-intersection
-    //       if intersection
-    //         add to colliding cubes
-    //         only iterate 6 more times  # optimization
-    //   pick closest colliding cube to camera eye
-    //
-    // Returns colliding cube and colliding point
-    fn get_colliding_block(&self, camera: &Camera, max_distance: usize) -> Option<BlockCollision> {
-        let mut all_candidate_cubes: Vec<Point3<f32>> = vec![];
+    /// Thin wireframe box hugging `highlighted_block`'s collision AABB, or
+    /// `None` when nothing's highlighted. Built through the same
+    /// `Vertex::generate_quad_data_for_cuboid` path as `CharacterEntity`'s
+    /// collision-box wireframe and `LightUniform::vertex_data_for_sunlight`,
+    /// so it renders via `RenderPipelineKind::WireNoInstancing` instead of
+    /// brightening the block's own instanced face colors -- that keeps the
+    /// indicator visible regardless of how translucent/semi-translucent
+    /// sorting orders the block underneath it.
+    pub fn selection_outline_vertex_data(&self) -> Option<QuadListRenderData> {
+        // Inflated slightly so the outline doesn't z-fight with the block's
+        // own faces.
+        const OUTLINE_INSET: f32 = 1.0 / 128.0;
+
+        let [x, y, z] = self.highlighted_block?;
+        // `Water`'s visual top sits below the unit cube (see
+        // `WATER_BLOCK_Y_HEIGHT`); every other collidable block type fills
+        // the full cube. Sprites (flowers, saplings) have a non-full
+        // collision bounds too, but `is_collidable` excludes them from
+        // `raycast_voxel` entirely, so they can never end up here.
+        let top = if self.get_block(x, y, z).block_type == BlockType::Water {
+            WATER_BLOCK_Y_HEIGHT
+        } else {
+            1.0
+        };
 
-        let camera_eye_cgmath17 = Point3::new(camera.eye.x, camera.eye.y, camera.eye.z);
-        all_candidate_cubes.push(Point3::new(
-            camera_eye_cgmath17.x.floor(),
-            camera_eye_cgmath17.y.floor(),
-            camera_eye_cgmath17.z.floor(),
-        ));
+        let mut result_vertex_data = QuadListRenderData {
+            vertex_data: vec![],
+            index_data: vec![],
+        };
+        Vertex::generate_quad_data_for_cuboid(
+            &CuboidCoords {
+                left: x as f32 - OUTLINE_INSET,
+                right: x as f32 + 1.0 + OUTLINE_INSET,
+                bottom: y as f32 - OUTLINE_INSET,
+                top: y as f32 + top + OUTLINE_INSET,
+                near: z as f32 - OUTLINE_INSET,
+                far: z as f32 + 1.0 + OUTLINE_INSET,
+            },
+            None,
+            &mut result_vertex_data,
+        );
+        Some(result_vertex_data)
+    }
 
+    /// Amanatides & Woo's "Fast Voxel Traversal" 3D-DDA: walk the voxel grid
+    /// along `forward_unit` one cell at a time, each step advancing whichever
+    /// axis reaches its cell boundary soonest (smallest `t_max`), so every
+    /// voxel the ray pierces gets visited exactly once in strict near-to-far
+    /// order -- unlike the old brute-force neighbor fan-out, there's no
+    /// redundant candidate cubes and no "stop after N more hits" heuristic:
+    /// the first collidable voxel visited is the answer.
+    ///
+    /// The selection/reach API used by `break_block`, `place_block`, and
+    /// `collision_normal_from_ray_2` -- modeled on the `raycube(origin, ray,
+    /// surface)` routine in AssaultCube's physics code, which likewise
+    /// reports the normal of the struck face alongside the hit point.
+    pub fn raycast_voxel(&self, camera: &Camera, max_distance: usize) -> Option<BlockCollision> {
+        let camera_eye_cgmath17 = Point3::new(camera.eye.x, camera.eye.y, camera.eye.z);
         let camera_target_cgmath17 = Point3::new(camera.target.x, camera.target.y, camera.target.z);
-
         let forward_unit = (camera_target_cgmath17 - camera_eye_cgmath17).normalize();
+        let collision_ray = collision::Ray::new(camera_eye_cgmath17, forward_unit);
 
-        let x_dir = forward_unit.x.signum();
-        let y_dir = forward_unit.y.signum();
-        let z_dir = forward_unit.z.signum();
-
-        let mut curr_pos = camera_eye_cgmath17;
-
-        for _ in 0..max_distance {
-            curr_pos += forward_unit;
-            let cube = Point3::new(curr_pos.x.floor(), curr_pos.y.floor(), curr_pos.z.floor());
+        let eye = [
+            camera_eye_cgmath17.x,
+            camera_eye_cgmath17.y,
+            camera_eye_cgmath17.z,
+        ];
+        let dir = [forward_unit.x, forward_unit.y, forward_unit.z];
+        let step = [
+            dir[0].signum() as isize,
+            dir[1].signum() as isize,
+            dir[2].signum() as isize,
+        ];
+        let mut voxel = [
+            eye[0].floor() as isize,
+            eye[1].floor() as isize,
+            eye[2].floor() as isize,
+        ];
 
-            // Add all possible intersecting neighbors as the ray moves forward
-            for (x_diff, y_diff, z_diff) in iproduct!([0.0, -x_dir], [0.0, -y_dir], [0.0, -z_dir]) {
-                all_candidate_cubes.push(Point3::new(
-                    cube.x + x_diff,
-                    cube.y + y_diff,
-                    cube.z + z_diff,
-                ));
+        let mut t_max = [0.0f32; 3];
+        let mut t_delta = [0.0f32; 3];
+        for axis in 0..3 {
+            if dir[axis] == 0.0 {
+                t_max[axis] = f32::INFINITY;
+                t_delta[axis] = f32::INFINITY;
+            } else {
+                let next_boundary = if step[axis] > 0 {
+                    voxel[axis] as f32 + 1.0
+                } else {
+                    voxel[axis] as f32
+                };
+                t_max[axis] = (next_boundary - eye[axis]) / dir[axis];
+                t_delta[axis] = step[axis] as f32 / dir[axis];
             }
-
-            all_candidate_cubes.push(cube);
         }
 
-        let collision_ray = collision::Ray::new(camera_eye_cgmath17, forward_unit);
-
-        let mut closest_collider = BlockCollision {
-            distance: std::f32::INFINITY,
-            block_pos: cgmath::Point3::new(0, 0, 0),
-            collision_point: cgmath::Point3::new(0.0, 0.0, 0.0),
-        };
-        let mut hit_first_collision = false;
-        let mut additional_checks = 0;
-
-        for cube in all_candidate_cubes.iter() {
-            let collision_cube =
-                collision::Aabb3::new(*cube, Point3::new(cube.x + 1.0, cube.y + 1.0, cube.z + 1.0));
+        loop {
+            // Advance to the next voxel boundary along whichever axis
+            // reaches it soonest; `t_max[axis]` doubles as the traversed
+            // distance once we get there, since `forward_unit` is unit length.
+            let axis = if t_max[0] < t_max[1] {
+                if t_max[0] < t_max[2] { 0 } else { 2 }
+            } else if t_max[1] < t_max[2] {
+                1
+            } else {
+                2
+            };
+            if t_max[axis] > max_distance as f32 {
+                return None;
+            }
+            voxel[axis] += step[axis];
+            t_max[axis] += t_delta[axis];
 
-            if self
-                .get_block(cube.x as usize, cube.y as usize, cube.z as usize)
-                .block_type
-                .is_collidable()
-            {
-                let maybe_collision = collision_ray.intersection(&collision_cube);
-
-                if let Some(ref collision_point) = maybe_collision {
-                    hit_first_collision = true;
-                    let collision_distance = collision_point.distance(camera_eye_cgmath17);
-                    if collision_distance < closest_collider.distance {
-                        closest_collider.distance = collision_distance;
-                        closest_collider.block_pos =
-                            cgmath::Point3::new(cube.x as usize, cube.y as usize, cube.z as usize);
-                        closest_collider.collision_point = cgmath::Point3::new(
-                            collision_point.x,
-                            collision_point.y,
-                            collision_point.z,
-                        );
-                    }
-                }
+            if voxel[0] < 0 || voxel[1] < 0 || voxel[2] < 0 {
+                continue;
             }
-            if hit_first_collision {
-                additional_checks += 1;
+            let (x, y, z) = (voxel[0] as usize, voxel[1] as usize, voxel[2] as usize);
+            if !self.get_block(x, y, z).block_type.is_collidable() {
+                continue;
             }
-            if additional_checks >= 7 {
-                break;
-            }
-        }
 
-        if hit_first_collision {
-            Some(closest_collider)
-        } else {
-            None
+            // We just stepped across `axis`'s boundary to enter this voxel,
+            // so that's the face the ray struck -- its outward normal points
+            // back the way the ray came, i.e. opposite `step[axis]`. No need
+            // to infer it from `collision_point`'s coordinates afterward.
+            let mut face_normal = Vector3::new(0.0, 0.0, 0.0);
+            face_normal[axis] = -(step[axis] as f32);
+
+            let collision_cube = collision::Aabb3::new(
+                Point3::new(x as f32, y as f32, z as f32),
+                Point3::new(x as f32 + 1.0, y as f32 + 1.0, z as f32 + 1.0),
+            );
+            if let Some(collision_point) = collision_ray.intersection(&collision_cube) {
+                return Some(BlockCollision {
+                    distance: collision_point.distance(camera_eye_cgmath17),
+                    block_pos: cgmath::Point3::new(x, y, z),
+                    collision_point: cgmath::Point3::new(
+                        collision_point.x,
+                        collision_point.y,
+                        collision_point.z,
+                    ),
+                    face_normal,
+                });
+            }
         }
     }
 
@@ -1267,38 +3148,8 @@ impl WorldState {
         next_eye: &cgmath::Point3<f32>,
     ) -> Option<Vector3<f32>> {
         let distance = (next_eye - camera.eye).magnitude().ceil() as usize;
-
-        return match self.get_colliding_block(camera, distance) {
-            Some(collision) => {
-                let collision_point = collision.collision_point;
-                let block_pos = collision.block_pos;
-                // Get the collision normal
-                let collision_normal = if collision_point.x - collision_point.x.floor() == 0.0 {
-                    if collision_point.x as usize == block_pos.x {
-                        Some(Vector3::new(-1.0, 0.0, 0.0))
-                    } else {
-                        Some(Vector3::new(1.0, 0.0, 0.0))
-                    }
-                } else if collision_point.y - collision_point.y.floor() == 0.0 {
-                    if collision_point.y as usize == block_pos.y {
-                        Some(Vector3::new(0.0, -1.0, 0.0))
-                    } else {
-                        Some(Vector3::new(0.0, 1.0, 0.0))
-                    }
-                } else if collision_point.z - collision_point.z.floor() == 0.0 {
-                    if collision_point.z as usize == block_pos.z {
-                        Some(Vector3::new(0.0, 0.0, -1.0))
-                    } else {
-                        Some(Vector3::new(0.0, 0.0, 1.0))
-                    }
-                } else {
-                    return None;
-                };
-                // println!("Normal is {:?}", collision_normal);
-                return collision_normal;
-            }
-            None => None,
-        };
+        self.raycast_voxel(camera, distance)
+            .map(|collision| collision.face_normal)
     }
 
     fn get_affected_chunks(&self, block_pos: &cgmath::Point3<usize>) -> Vec<[usize; 2]> {
@@ -1354,7 +3205,7 @@ impl WorldState {
 
     // Returns which chunks were modified
     pub fn break_block(&mut self, camera: &Camera) -> Vec<[usize; 2]> {
-        let maybe_collision = self.get_colliding_block(camera, MAX_BREAK_DISTANCE);
+        let maybe_collision = self.raycast_voxel(camera, MAX_BREAK_DISTANCE);
         if let Some(ref collision) = maybe_collision {
             let (collider_x, collider_y, collider_z) = (
                 collision.block_pos.x,
@@ -1367,9 +3218,15 @@ impl WorldState {
                 collision.collision_point
             );
             vprintln!("break_block collision block is {:?}", collision.block_pos);
-            set_block!(self, collider_x, collider_y, collider_z, BlockType::Empty);
-
-            self.get_affected_chunks(&collision.block_pos)
+            self.point_lights
+                .remove(&[collider_x, collider_y, collider_z]);
+            let light_dirty_chunks =
+                set_block!(self, collider_x, collider_y, collider_z, BlockType::Empty);
+
+            let mut affected_chunks = self.get_affected_chunks(&collision.block_pos);
+            affected_chunks.extend(light_dirty_chunks);
+            affected_chunks.dedup();
+            affected_chunks
         } else {
             vec![]
         }
@@ -1377,7 +3234,7 @@ impl WorldState {
 
     // Returns which chunks were modified
     pub fn place_block(&mut self, camera: &Camera, block_type: BlockType) -> Vec<[usize; 2]> {
-        let maybe_collision = self.get_colliding_block(camera, MAX_BREAK_DISTANCE + 1);
+        let maybe_collision = self.raycast_voxel(camera, MAX_BREAK_DISTANCE + 1);
         if let Some(ref collision) = maybe_collision {
             vprintln!(
                 "place_block collision point is {:?}",
@@ -1385,178 +3242,137 @@ impl WorldState {
             );
             vprintln!("place_block collision block is {:?}", collision.block_pos);
 
-            let mut new_block_pos = cgmath::Point3::<usize>::new(0, 0, 0);
-            if collision.collision_point.x - collision.collision_point.x.floor() == 0.0 {
-                new_block_pos = cgmath::Point3::new(
-                    if collision.collision_point.x as usize == collision.block_pos.x {
-                        collision.block_pos.x - 1
-                    } else {
-                        collision.block_pos.x + 1
-                    },
-                    collision.block_pos.y,
-                    collision.block_pos.z,
-                )
-            }
-            if collision.collision_point.y - collision.collision_point.y.floor() == 0.0 {
-                new_block_pos = cgmath::Point3::new(
-                    collision.block_pos.x,
-                    if collision.collision_point.y as usize == collision.block_pos.y {
-                        collision.block_pos.y - 1
-                    } else {
-                        collision.block_pos.y + 1
-                    },
-                    collision.block_pos.z,
-                )
-            }
-            if collision.collision_point.z - collision.collision_point.z.floor() == 0.0 {
-                new_block_pos = cgmath::Point3::new(
-                    collision.block_pos.x,
-                    collision.block_pos.y,
-                    if collision.collision_point.z as usize == collision.block_pos.z {
-                        collision.block_pos.z - 1
-                    } else {
-                        collision.block_pos.z + 1
-                    },
-                )
-            }
-            vprintln!("place_block new block pos is {:?}", collision.block_pos);
+            // The struck face's normal, straight from the voxel traversal, so
+            // this lands correctly even when the ray grazes an edge or
+            // corner -- unlike inferring the face from which `collision_point`
+            // coordinate happens to be integral, which has no match there.
+            let new_block_pos = cgmath::Point3::new(
+                (collision.block_pos.x as isize + collision.face_normal.x as isize) as usize,
+                (collision.block_pos.y as isize + collision.face_normal.y as isize) as usize,
+                (collision.block_pos.z as isize + collision.face_normal.z as isize) as usize,
+            );
+            vprintln!("place_block new block pos is {:?}", new_block_pos);
 
-            set_block!(
+            let light_dirty_chunks = set_block!(
                 self,
                 new_block_pos.x,
                 new_block_pos.y,
                 new_block_pos.z,
                 block_type
             );
+            if let Some((color, radius)) = block_type.emitted_light() {
+                self.point_lights.insert(
+                    [new_block_pos.x, new_block_pos.y, new_block_pos.z],
+                    light::PointLight {
+                        position: glam::Vec3::new(
+                            new_block_pos.x as f32 + 0.5,
+                            new_block_pos.y as f32 + 0.5,
+                            new_block_pos.z as f32 + 0.5,
+                        ),
+                        color,
+                        radius,
+                    },
+                );
+            }
 
-            self.get_affected_chunks(&new_block_pos)
+            let mut affected_chunks = self.get_affected_chunks(&new_block_pos);
+            affected_chunks.extend(light_dirty_chunks);
+            affected_chunks.dedup();
+            affected_chunks
         } else {
             vec![]
         }
     }
 
-    pub fn physics_tick(&mut self, game_loop: &mut GameLoop, camera: &Camera) {
-        let character_half_extent = 0.5; // Assuming the character is 1 voxel wide
-        let character_height = 2.0; // Assuming the character is 2 voxels tall
-        let character_half_height = character_height / 2.0;
-        let character_collider = Cylinder::new(character_half_height, character_half_extent);
-
-        const FLOOR_CONTACT_TOLERANCE: f32 = 0.001;
-        const WALL_CONTACT_TOLERANCE: f32 = 0.01;
-
-        // Define a helper function to check for collisions in a given direction
-        fn check_collision_in_direction(
-            character_pos: &na::Isometry3<f32>,
-            character_collider: &Cylinder,
-            direction: glam::Vec3,
-            blocks: &Vec<[usize; 3]>,
-            contact_tolerance: f32,
-        ) -> Option<parry3d::query::Contact> {
-            for block_pos in blocks {
-                let block_collider = Cuboid::new(na::vector![0.5, 0.5, 0.5]);
-                let block_pos = na::Isometry3::new(
-                    na::vector![
-                        block_pos[0] as f32 + 0.5,
-                        block_pos[1] as f32 + 0.5,
-                        block_pos[2] as f32 + 0.5
-                    ],
-                    na::zero(),
-                );
-
-                if let Some(contact) = parry3d::query::contact(
-                    character_pos,
-                    character_collider,
-                    &block_pos,
-                    &block_collider,
-                    0.01, // tolerance
-                )
-                .unwrap()
-                {
-                    let contact_normal =
-                        glam::Vec3::new(contact.normal1.x, contact.normal1.y, contact.normal1.z);
-
-                    // Project the normal onto the plane perpendicular to the direction
-                    let normal_on_plane =
-                        contact_normal - direction * contact_normal.dot(direction);
-
-                    // If true, the normal does not have significant components in directions other than `direction`
-                    let is_normal_mostly_parallel_to_direction = normal_on_plane.length() < 0.5;
+    /// `fixed_dt` is `game_loop::GameLoop::fixed_time_step()` -- the caller
+    /// runs this once per accumulator step rather than once per render, so
+    /// physics speed stays constant regardless of display refresh rate.
+    pub fn physics_tick(&mut self, fixed_dt: f64, camera: &Camera) {
+        self.apply_queued_input_events();
 
-                    if is_normal_mostly_parallel_to_direction
-                        && contact.dist.abs() > contact_tolerance
-                    {
-                        return Some(contact);
-                    }
-                }
-            }
-            None
+        if self.is_flying {
+            self.flying_physics_tick(camera);
+            return;
         }
 
+        // Closure form of `get_block` that only captures `chunk_indices`/`chunks` (not all of
+        // `self`), via Rust 2021's disjoint field capture -- so it can be held alongside a
+        // `&mut self.character_entity` borrow below.
+        let get_block_type = |x: usize, y: usize, z: usize| -> BlockType {
+            let chunk_idx = self.chunk_indices[[x / CHUNK_XZ_SIZE, z / CHUNK_XZ_SIZE]];
+            self.chunks[chunk_idx as usize]
+                .blocks
+                .get_unchecked(x % CHUNK_XZ_SIZE, y, z % CHUNK_XZ_SIZE)
+                .block_type
+        };
+
         // First, check if the character entity is touching the floor. This determines if we should apply gravity and whether the character can jump.
-        let curr_character_pos = na::Isometry3::new(
-            na::vector![
-                self.character_entity.position.x,
-                self.character_entity.position.y,
-                self.character_entity.position.z
-            ],
-            na::zero(),
-        );
+        let is_contacting_floor = is_grounded(get_block_type, &self.character_entity.dynamics);
 
-        // Feet of the character entity, a cynlinder. The middle of the cylinder is at the character's feet.
-        let chracter_feet_pos = (
-            self.character_entity.position.x,
-            self.character_entity.position.y - character_half_height,
-            self.character_entity.position.z,
-        );
+        let gravity_y_accel: f32 = (fixed_dt.powi(2) * -9.807) as f32;
 
-        let mut floor_blocks_to_check_collision: Vec<[usize; 3]> = vec![];
-        for (dx, dz) in iproduct!(-1..=1, -1..=1) {
-            let block_pos = [
-                (chracter_feet_pos.0 + (dx as f32)).floor() as usize,
-                (chracter_feet_pos.1).floor() as usize,
-                (chracter_feet_pos.2 + (dz as f32)).floor() as usize,
-            ];
-            if self
-                .get_block(block_pos[0], block_pos[1], block_pos[2])
-                .block_type
-                .is_collidable()
-            {
-                floor_blocks_to_check_collision.push(block_pos);
+        // Jump tuning.
+        const COYOTE_TIME_TICKS: u32 = 6;
+        const JUMP_BUFFER_TICKS: u32 = 6;
+        const JUMP_ACCEL: f32 = 0.05;
+
+        // Track grounded state across ticks for coyote time, and refill the double jump
+        // the moment we land.
+        if is_contacting_floor {
+            if self.character_entity.ticks_since_grounded > 0 {
+                self.character_entity.double_jump_available = true;
             }
+            self.character_entity.ticks_since_grounded = 0;
+        } else {
+            self.character_entity.ticks_since_grounded =
+                self.character_entity.ticks_since_grounded.saturating_add(1);
         }
 
-        let mut is_contacting_floor = false;
-        if let Some(_contact) = check_collision_in_direction(
-            &curr_character_pos,
-            &character_collider,
-            -glam::Vec3::Y,
-            &floor_blocks_to_check_collision,
-            FLOOR_CONTACT_TOLERANCE / 4.0, // lower tolerance
-        ) {
-            is_contacting_floor = true;
+        // Jump buffering: remember a jump press for a few ticks so it still fires if the
+        // button was hit slightly before landing, instead of requiring the exact tick.
+        if self.input_state.movement.key_pressed(MovementKey::Jump) {
+            self.character_entity.buffered_jump_ticks_remaining = JUMP_BUFFER_TICKS;
         }
 
-        let gravity_y_accel: f32 = (game_loop.fixed_time_step().powi(2) * -9.807) as f32;
-
         // Apply gravity if not contacting floor
-        self.character_entity.acceleration.y = 0.0;
-        if self.input_state.jump_button_state == ButtonState::Pressed {
-            // Jump button can only be "pressed" for one tick
-            self.input_state.jump_button_state = ButtonState::Held;
-            if is_contacting_floor || self.character_entity.is_underwater {
-                self.character_entity.acceleration.y = 0.05;
+        self.character_entity.dynamics.acceleration.y = 0.0;
+        if self.character_entity.buffered_jump_ticks_remaining > 0 {
+            let can_ground_or_coyote_jump = is_contacting_floor
+                || self.character_entity.ticks_since_grounded <= COYOTE_TIME_TICKS;
+            if self.character_entity.dynamics.is_underwater || can_ground_or_coyote_jump {
+                self.character_entity.dynamics.acceleration.y = JUMP_ACCEL;
+                self.character_entity.buffered_jump_ticks_remaining = 0;
+                // Don't let this grounded/coyote jump also be treated as having left the
+                // ground just now -- that would let a single jump refill and then
+                // immediately spend the air double-jump too.
+                self.character_entity.ticks_since_grounded = COYOTE_TIME_TICKS + 1;
+            } else if self.character_entity.double_jump_available {
+                self.character_entity.dynamics.acceleration.y = JUMP_ACCEL;
+                self.character_entity.buffered_jump_ticks_remaining = 0;
+                self.character_entity.double_jump_available = false;
             }
         }
-        if !is_contacting_floor && self.character_entity.acceleration.y == 0.0 {
-            self.character_entity.acceleration.y = if self.character_entity.is_underwater {
-                gravity_y_accel * 0.5
-            } else {
-                gravity_y_accel
-            };
+        if self.character_entity.buffered_jump_ticks_remaining > 0 {
+            self.character_entity.buffered_jump_ticks_remaining -= 1;
+        }
+
+        if !is_contacting_floor && self.character_entity.dynamics.acceleration.y == 0.0 {
+            self.character_entity.dynamics.acceleration.y =
+                if self.character_entity.dynamics.is_underwater {
+                    gravity_y_accel * 0.5
+                } else {
+                    gravity_y_accel
+                };
         }
 
         const MAX_XZ_VELOCITY: f32 = 0.1;
-        const XZ_ACCEL: f32 = 0.010;
+        const XZ_ACCEL: f32 = 6.0;
+        // Air movement is capped to a much lower wishspeed than ground
+        // movement, clamped *before* the `wishdir` dot product below -- this
+        // is what lets strafing while turning the view gain speed in the
+        // air (source/Quake-style "air-strafing"), a la Xonotic's
+        // `PM_AirAccelerate`, instead of topping out at ground speed.
+        const AIR_SPEED_LIMIT: f32 = 0.03;
         const XZ_FRICTION: f32 = 0.004;
 
         // Get the camera's forward normal and ignore the Y component for XZ plane movement
@@ -1564,46 +3380,64 @@ impl WorldState {
         let camera_forward_xz =
             glam::Vec3::new(camera_forward_normal.x, 0.0, camera_forward_normal.z).normalize();
 
-        // Reset acceleration
-        self.character_entity.acceleration.x = 0.0;
-        self.character_entity.acceleration.z = 0.0;
-
-        // Apply acceleration based on input
-        if self.input_state.is_forward_pressed {
-            self.character_entity.acceleration += camera_forward_xz * XZ_ACCEL;
-        }
-        if self.input_state.is_backward_pressed {
-            self.character_entity.acceleration -= camera_forward_xz * XZ_ACCEL;
-        }
-
         // For right and left movement, we need the rightward normal on the XZ plane
         let camera_right_xz = glam::Vec3::new(-camera_forward_xz.z, 0.0, camera_forward_xz.x); // Rotate 90 degrees on the Y axis
 
-        if self.input_state.is_right_pressed {
-            self.character_entity.acceleration += camera_right_xz * XZ_ACCEL;
+        // Reset acceleration
+        self.character_entity.dynamics.acceleration.x = 0.0;
+        self.character_entity.dynamics.acceleration.z = 0.0;
+
+        // Build a normalized wish direction from the held movement keys in
+        // the XZ plane (diagonal input isn't faster than single-axis input).
+        let mut wishdir = glam::Vec3::ZERO;
+        if self.input_state.movement.key_held(MovementKey::Forward) {
+            wishdir += camera_forward_xz;
+        }
+        if self.input_state.movement.key_held(MovementKey::Backward) {
+            wishdir -= camera_forward_xz;
+        }
+        if self.input_state.movement.key_held(MovementKey::Right) {
+            wishdir += camera_right_xz;
         }
-        if self.input_state.is_left_pressed {
-            self.character_entity.acceleration -= camera_right_xz * XZ_ACCEL;
+        if self.input_state.movement.key_held(MovementKey::Left) {
+            wishdir -= camera_right_xz;
         }
+        let is_no_input_given = wishdir == glam::Vec3::ZERO;
 
         let curr_velocity_xz = glam::Vec3::new(
-            self.character_entity.velocity.x,
+            self.character_entity.dynamics.velocity.x,
             0.0,
-            self.character_entity.velocity.z,
+            self.character_entity.dynamics.velocity.z,
         );
-        let is_no_input_given = self.character_entity.acceleration.x == 0.0
-            && self.character_entity.acceleration.z == 0.0;
 
-        // Apply friction to decelerate the character when no input is given
-        if curr_velocity_xz.length().abs() > 0.0 && is_no_input_given {
+        if !is_no_input_given {
+            wishdir = wishdir.normalize();
+            let wishspeed = if is_contacting_floor {
+                MAX_XZ_VELOCITY
+            } else {
+                MAX_XZ_VELOCITY.min(AIR_SPEED_LIMIT)
+            };
+
+            let currentspeed = curr_velocity_xz.dot(wishdir);
+            let addspeed = wishspeed - currentspeed;
+            if addspeed > 0.0 {
+                let accelspeed = (XZ_ACCEL * (fixed_dt as f32) * wishspeed).min(addspeed);
+                self.character_entity.dynamics.acceleration += accelspeed * wishdir;
+            }
+        }
+
+        // Ground friction only applies while grounded -- airborne players
+        // keep their momentum, which is the other half of what makes
+        // air-strafing work.
+        if is_contacting_floor && curr_velocity_xz.length().abs() > 0.0 && is_no_input_given {
             let friction_dir = curr_velocity_xz.normalize();
             let friction = friction_dir * XZ_FRICTION;
             // Apply friction but don't reverse the direction
-            self.character_entity.acceleration -= friction.min(curr_velocity_xz.abs());
+            self.character_entity.dynamics.acceleration -= friction.min(curr_velocity_xz.abs());
         }
 
         // Apply acceleration to velocity
-        self.character_entity.velocity += self.character_entity.acceleration;
+        self.character_entity.dynamics.velocity += self.character_entity.dynamics.acceleration;
 
         // Handle translation joystick. Apply it to velocity directly rather than acceleration, more responsive controls this way
         let (joystick_z, joystick_x) = self.input_state.last_translation_joystick_vector;
@@ -1616,303 +3450,676 @@ impl WorldState {
             joystick_velocity_xz += camera_right_xz * (joystick_z as f32) * MAX_XZ_VELOCITY * 0.75;
         }
         if joystick_velocity_xz != glam::Vec3::ZERO {
-            self.character_entity.velocity.x = joystick_velocity_xz.x;
-            self.character_entity.velocity.z = joystick_velocity_xz.z;
+            self.character_entity.dynamics.velocity.x = joystick_velocity_xz.x;
+            self.character_entity.dynamics.velocity.z = joystick_velocity_xz.z;
         }
 
         let next_velocity_xz = glam::Vec3::new(
-            self.character_entity.velocity.x,
+            self.character_entity.dynamics.velocity.x,
             0.0,
-            self.character_entity.velocity.z,
+            self.character_entity.dynamics.velocity.z,
         );
 
         // Clamp XZ velocity if it's to high
         if next_velocity_xz.length().abs() > MAX_XZ_VELOCITY {
             let clamped_next_velocity_xz = next_velocity_xz.normalize() * MAX_XZ_VELOCITY;
-            self.character_entity.velocity.x = clamped_next_velocity_xz.x;
-            self.character_entity.velocity.z = clamped_next_velocity_xz.z;
+            self.character_entity.dynamics.velocity.x = clamped_next_velocity_xz.x;
+            self.character_entity.dynamics.velocity.z = clamped_next_velocity_xz.z;
         } else if (-XZ_FRICTION..XZ_FRICTION).contains(&next_velocity_xz.length().abs()) {
-            self.character_entity.velocity.x = 0.0;
-            self.character_entity.velocity.z = 0.0;
+            self.character_entity.dynamics.velocity.x = 0.0;
+            self.character_entity.dynamics.velocity.z = 0.0;
         }
 
         // Clamp Y velocity
         const MAX_Y_VELOCITY: f32 = 0.15;
         const MAX_Y_VELOCITY_UNDERWATER: f32 = 0.05;
-        self.character_entity.velocity.y = self.character_entity.velocity.y.clamp(
-            -1000.0,
-            if self.character_entity.is_underwater {
-                MAX_Y_VELOCITY_UNDERWATER
-            } else {
-                MAX_Y_VELOCITY
-            },
-        );
-
-        let mut potential_new_pos = self.character_entity.position + self.character_entity.velocity;
+        self.character_entity.dynamics.velocity.y =
+            self.character_entity.dynamics.velocity.y.clamp(
+                -1000.0,
+                if self.character_entity.dynamics.is_underwater {
+                    MAX_Y_VELOCITY_UNDERWATER
+                } else {
+                    MAX_Y_VELOCITY
+                },
+            );
 
-        // Update character_pos with the potential new position for collision checks
-        let next_character_pos = na::Isometry3::new(
-            na::vector![
-                potential_new_pos.x,
-                potential_new_pos.y,
-                potential_new_pos.z
-            ],
-            na::zero(),
+        // Everything above this point is the player-only input/jump state machine deciding
+        // this tick's `acceleration` and `velocity`. From here on the block-collision sweep,
+        // step-up, position commit, and underwater state are the same generic routine any
+        // `DynamicEntity` runs -- see `tick_dynamic_entity`.
+        tick_dynamic_entity(
+            get_block_type,
+            &mut self.character_entity.dynamics,
+            is_contacting_floor,
         );
 
-        // Collect blocks to check for collision in all directions
-        let mut blocks_to_check_collision: Vec<[usize; 3]> = vec![];
-
-        // Calculate the bounds of the character's current and next position
-        let min_x = (potential_new_pos.x - character_half_extent).floor() as isize;
-        let max_x = (potential_new_pos.x + character_half_extent).ceil() as isize;
-        let min_y = (potential_new_pos.y - character_half_height).floor() as isize; // Adjusted for Y-axis
-        let max_y = (potential_new_pos.y + character_half_height).ceil() as isize; // Adjusted for Y-axis
-        let min_z = (potential_new_pos.z - character_half_extent).floor() as isize;
-        let max_z = (potential_new_pos.z + character_half_extent).ceil() as isize;
-
-        // Iterate over the blocks in the range and collect the ones that are collidable
-        for x in min_x..=max_x {
-            for y in min_y..=max_y {
-                for z in min_z..=max_z {
-                    if x < 0 || y < 0 || z < 0 {
-                        // Skip blocks with negative indices, if your world has no blocks at negative coordinates
-                        continue;
-                    }
-                    let block_pos = [x as usize, y as usize, z as usize];
-                    if self
-                        .get_block(block_pos[0], block_pos[1], block_pos[2])
-                        .block_type
-                        .is_collidable()
-                    {
-                        blocks_to_check_collision.push(block_pos);
-                    }
-                }
-            }
+        // Tick every other dynamic entity (mobs, items dropped by `break_block`) through the
+        // same gravity/collision core the player uses above. Nothing pushes onto
+        // `dynamic_entities` yet, so this is a no-op today, but a future spawner can populate
+        // it without `physics_tick` itself changing.
+        for entity in &mut self.dynamic_entities {
+            let entity_is_grounded = is_grounded(get_block_type, entity);
+            entity.acceleration.y = if entity_is_grounded {
+                0.0
+            } else if entity.is_underwater {
+                gravity_y_accel * 0.5
+            } else {
+                gravity_y_accel
+            };
+            entity.velocity += entity.acceleration;
+            entity.velocity.y = entity.velocity.y.clamp(
+                -1000.0,
+                if entity.is_underwater {
+                    MAX_Y_VELOCITY_UNDERWATER
+                } else {
+                    MAX_Y_VELOCITY
+                },
+            );
+            tick_dynamic_entity(get_block_type, entity, entity_is_grounded);
         }
+    }
 
-        // Check for X-axis collisions
-        let x_direction = if self.character_entity.velocity.x > 0.0 {
-            glam::Vec3::X
-        } else {
-            -glam::Vec3::X
-        };
-        if let Some(contact) = check_collision_in_direction(
-            &next_character_pos,
-            &character_collider,
-            x_direction,
-            &blocks_to_check_collision,
-            WALL_CONTACT_TOLERANCE,
-        ) {
-            // Resolve X-axis collision
-            self.character_entity.velocity.x = 0.0;
-            let adjust_vec =
-                glam::Vec3::new(contact.normal1.x, contact.normal1.y, contact.normal1.z)
-                    * contact.dist;
-            // let prev_pos = potential_new_pos.clone();
-            potential_new_pos.x += adjust_vec.x;
-            // println!(
-            //     "X-axis collision, prev_pos: {:?}, new_pos: {:?}, adjust_vec: {:?}, contact: {:?}",
-            //     prev_pos, potential_new_pos, adjust_vec, contact
-            // );
-        }
-
-        // Check for Y-axis collisions, special case for gravity
-        let y_direction = if self.character_entity.velocity.y > 0.0 {
-            glam::Vec3::Y
-        } else {
-            -glam::Vec3::Y
-        };
-        if let Some(contact) = check_collision_in_direction(
-            &next_character_pos,
-            &character_collider,
-            y_direction,
-            &blocks_to_check_collision,
-            FLOOR_CONTACT_TOLERANCE,
-        ) {
-            // Resolve Y-axis collision
-            self.character_entity.velocity.y = 0.0;
-            let adjust_vec =
-                glam::Vec3::new(contact.normal1.x, contact.normal1.y, contact.normal1.z)
-                    * contact.dist;
+    /// The `physics_tick` variant used while `is_flying` -- gravity is off, vertical motion
+    /// is driven directly by the jump (up) / descend (down) keys, XZ speed is boosted, and
+    /// (since there's no ground to stand on up there) collision is skipped entirely, so
+    /// flying doubles as noclip. Mirrors `Gamemode::Survival` vs. the flying
+    /// `PlayerMovement` in stevenarella's player entity.
+    fn flying_physics_tick(&mut self, camera: &Camera) {
+        const FLY_XZ_SPEED: f32 = 0.3;
+        const FLY_Y_SPEED: f32 = 0.3;
+
+        let camera_forward_normal = camera.forward_normal();
+        let camera_forward_xz =
+            glam::Vec3::new(camera_forward_normal.x, 0.0, camera_forward_normal.z).normalize();
+        let camera_right_xz = glam::Vec3::new(-camera_forward_xz.z, 0.0, camera_forward_xz.x);
 
-            // HACK: Keep the character slightly colliding with the floor so we can jump / apply gravity on next frame
-            potential_new_pos.y +=
-                adjust_vec.y + (FLOOR_CONTACT_TOLERANCE / 2.0 * -adjust_vec.y.signum());
+        let mut velocity = glam::Vec3::ZERO;
+        if self.input_state.movement.key_held(MovementKey::Forward) {
+            velocity += camera_forward_xz;
+        }
+        if self.input_state.movement.key_held(MovementKey::Backward) {
+            velocity -= camera_forward_xz;
+        }
+        if self.input_state.movement.key_held(MovementKey::Right) {
+            velocity += camera_right_xz;
+        }
+        if self.input_state.movement.key_held(MovementKey::Left) {
+            velocity -= camera_right_xz;
+        }
+        if velocity.length_squared() > 0.0 {
+            velocity = velocity.normalize() * FLY_XZ_SPEED;
         }
 
-        // Check for Z-axis collisions
-        let z_direction = if self.character_entity.velocity.z > 0.0 {
-            glam::Vec3::Z
-        } else {
-            -glam::Vec3::Z
-        };
-        if let Some(contact) = check_collision_in_direction(
-            &next_character_pos,
-            &character_collider,
-            z_direction,
-            &blocks_to_check_collision,
-            WALL_CONTACT_TOLERANCE,
-        ) {
-            // Resolve Z-axis collision
-            self.character_entity.velocity.z = 0.0;
-            let adjust_vec =
-                glam::Vec3::new(contact.normal1.x, contact.normal1.y, contact.normal1.z)
-                    * contact.dist;
-            // let prev_pos = potential_new_pos.clone();
-            potential_new_pos.z += adjust_vec.z;
-            // println!(
-            //     "Z-axis collision, prev_pos: {:?}, new_pos: {:?}, adjust_vec: {:?}, contact: {:?}",
-            //     prev_pos, potential_new_pos, adjust_vec, contact
-            // );
-        }
-
-        // Apply the final position and velocity to the character
-        self.character_entity.prev_position = self.character_entity.position;
-        self.character_entity.position = potential_new_pos.into();
-
-        // Update if character is underwater
+        if self.input_state.movement.key_held(MovementKey::Jump) {
+            velocity.y += FLY_Y_SPEED;
+        }
+        if self.input_state.movement.key_held(MovementKey::Descend) {
+            velocity.y -= FLY_Y_SPEED;
+        }
+
+        self.character_entity.dynamics.velocity = velocity;
+        self.character_entity.dynamics.prev_position = self.character_entity.dynamics.position;
+        self.character_entity.dynamics.position += velocity;
+
         const WATER_CHECK_Y_ADJUST: f32 = 0.5 + (1.0 - WATER_BLOCK_Y_HEIGHT); // +0.5 for eye level, -0.2 for water-level adjust
-        let prev_underwater = self.character_entity.is_underwater;
-        self.character_entity.is_underwater = self
+        self.character_entity.dynamics.is_underwater = self
             .get_block(
-                self.character_entity.position.x as usize,
-                (self.character_entity.position.y + WATER_CHECK_Y_ADJUST) as usize,
-                self.character_entity.position.z as usize,
+                self.character_entity.dynamics.position.x as usize,
+                (self.character_entity.dynamics.position.y + WATER_CHECK_Y_ADJUST) as usize,
+                self.character_entity.dynamics.position.z as usize,
             )
             .block_type
             == BlockType::Water;
+    }
 
-        if !prev_underwater && self.character_entity.is_underwater {
-            // Water can break a fall
-            self.character_entity.velocity.y /= 4.0;
-        }
+    /// Forces every tracked button/joystick state back to neutral. Call on
+    /// window focus loss -- if the player tabs away or the browser steals
+    /// focus mid-press, the matching release event never arrives, and
+    /// without this the character keeps walking or the jump stays latched
+    /// once focus returns.
+    pub fn clear_stuck_input(&mut self) {
+        self.input_events.swap_buffers();
+        self.input_events.drain().for_each(drop);
+
+        self.input_state.movement.clear();
+        self.input_state.last_joystick_vector = (0.0, 0.0);
+        self.input_state.last_translation_joystick_vector = (0.0, 0.0);
     }
 
+    /// Translates a raw keyboard event into an `InputEvent` and queues it --
+    /// see `apply_queued_input_events` for where it actually takes effect.
     pub fn process_window_event(&mut self, event: &WindowEvent) {
-        match event {
-            WindowEvent::KeyboardInput { input, .. } => {
-                let mut forward_pressed = || {
-                    self.input_state.is_forward_pressed = input.state == ElementState::Pressed;
-                };
-                let mut left_pressed = || {
-                    self.input_state.is_left_pressed = input.state == ElementState::Pressed;
-                };
-                let mut backward_pressed = || {
-                    self.input_state.is_backward_pressed = input.state == ElementState::Pressed;
-                };
-                let mut right_pressed = || {
-                    self.input_state.is_right_pressed = input.state == ElementState::Pressed;
-                };
-                let mut jump_pressed = || {
-                    let pressed = input.state == ElementState::Pressed;
-                    self.input_state.jump_button_state = if pressed {
-                        match self.input_state.jump_button_state {
-                            ButtonState::Pressed => ButtonState::Held,
-                            ButtonState::Held => ButtonState::Held,
-                            _ => ButtonState::Pressed,
+        if let WindowEvent::KeyboardInput { input, .. } = event {
+            if let Some(keycode) = input.virtual_keycode {
+                self.input_events.push(InputEvent::Key {
+                    keycode,
+                    pressed: input.state == ElementState::Pressed,
+                });
+            }
+        }
+    }
+
+    /// Translates a web DOM control event into an `InputEvent` and queues
+    /// it -- see `apply_queued_input_events` for where it actually takes
+    /// effect.
+    pub fn process_web_dom_button_event(&mut self, event: &DomControlsUserEvent) {
+        let translated = match event {
+            DomControlsUserEvent::PitchYawJoystickMoved { vector } => {
+                Some(InputEvent::PitchYawJoystickMoved { vector: *vector })
+            }
+            DomControlsUserEvent::PitchYawJoystickReleased => {
+                Some(InputEvent::PitchYawJoystickReleased)
+            }
+            DomControlsUserEvent::TranslationJoystickMoved { vector } => {
+                Some(InputEvent::TranslationJoystickMoved { vector: *vector })
+            }
+            DomControlsUserEvent::TranslationJoystickReleased => {
+                Some(InputEvent::TranslationJoystickReleased)
+            }
+            DomControlsUserEvent::YButtonPressed => Some(InputEvent::YButtonPressed),
+            DomControlsUserEvent::YButtonReleased => Some(InputEvent::YButtonReleased),
+            DomControlsUserEvent::BlockPreviewPressed => Some(InputEvent::BlockPreviewPressed),
+            _ => None,
+        };
+        if let Some(translated) = translated {
+            self.input_events.push(translated);
+        }
+    }
+
+    /// Applies every `InputEvent` queued by `process_window_event`/
+    /// `process_web_dom_button_event` since the last call, in the order
+    /// they arrived -- the single point where they actually mutate
+    /// `input_state`/`place_block_type`/`is_flying`. Called once per tick
+    /// from `physics_tick`, before anything reads those fields, so native
+    /// keyboard events and web DOM events resolve deterministically instead
+    /// of racing through two separate entry points.
+    fn apply_queued_input_events(&mut self) {
+        const BLOCK_ORDER: [BlockType; 5] = [
+            BlockType::Stone,
+            BlockType::Dirt,
+            BlockType::OakPlank,
+            BlockType::Glass,
+            BlockType::Sand,
+        ];
+
+        self.input_events.swap_buffers();
+        for event in self.input_events.drain() {
+            match event {
+                InputEvent::Key { keycode, pressed } => {
+                    // Toggle creative fly/noclip mode -- kept outside the WASD/IJKL
+                    // remap below so it works no matter which movement scheme is
+                    // currently active.
+                    if pressed && keycode == VirtualKeyCode::F {
+                        self.is_flying = !self.is_flying;
+                    }
+
+                    let movement = &mut self.input_state.movement;
+                    if self.is_flying {
+                        match keycode {
+                            VirtualKeyCode::I => {
+                                movement.set_pressed(MovementKey::Forward, pressed)
+                            }
+                            VirtualKeyCode::J => movement.set_pressed(MovementKey::Left, pressed),
+                            VirtualKeyCode::K => {
+                                movement.set_pressed(MovementKey::Backward, pressed)
+                            }
+                            VirtualKeyCode::L => movement.set_pressed(MovementKey::Right, pressed),
+                            VirtualKeyCode::Z => movement.set_pressed(MovementKey::Jump, pressed),
+                            VirtualKeyCode::X => {
+                                movement.set_pressed(MovementKey::Descend, pressed)
+                            }
+                            _ => (),
                         }
                     } else {
-                        match self.input_state.jump_button_state {
-                            ButtonState::Pressed => ButtonState::Released,
-                            ButtonState::Held => ButtonState::Released,
-                            _ => ButtonState::Idle,
+                        match keycode {
+                            VirtualKeyCode::W => {
+                                movement.set_pressed(MovementKey::Forward, pressed)
+                            }
+                            VirtualKeyCode::A => movement.set_pressed(MovementKey::Left, pressed),
+                            VirtualKeyCode::S => {
+                                movement.set_pressed(MovementKey::Backward, pressed)
+                            }
+                            VirtualKeyCode::D => movement.set_pressed(MovementKey::Right, pressed),
+                            VirtualKeyCode::Space => {
+                                movement.set_pressed(MovementKey::Jump, pressed)
+                            }
+                            _ => (),
                         }
                     }
-                };
 
-                if self.is_flying {
-                    match input.virtual_keycode {
-                        Some(VirtualKeyCode::I) => forward_pressed(),
-                        Some(VirtualKeyCode::J) => left_pressed(),
-                        Some(VirtualKeyCode::K) => backward_pressed(),
-                        Some(VirtualKeyCode::L) => right_pressed(),
-                        Some(VirtualKeyCode::Z) => jump_pressed(),
+                    #[cfg(target_arch = "wasm32")]
+                    let prev_place_block_type = self.place_block_type;
+
+                    match keycode {
+                        VirtualKeyCode::Key1 => self.place_block_type = BlockType::Stone,
+                        VirtualKeyCode::Key2 => self.place_block_type = BlockType::Dirt,
+                        VirtualKeyCode::Key3 => self.place_block_type = BlockType::OakPlank,
+                        VirtualKeyCode::Key4 => self.place_block_type = BlockType::Glass,
+                        VirtualKeyCode::Key5 => self.place_block_type = BlockType::Sand,
                         _ => (),
                     }
-                } else {
-                    match input.virtual_keycode {
-                        Some(VirtualKeyCode::W) => forward_pressed(),
-                        Some(VirtualKeyCode::A) => left_pressed(),
-                        Some(VirtualKeyCode::S) => backward_pressed(),
-                        Some(VirtualKeyCode::D) => right_pressed(),
-                        Some(VirtualKeyCode::Space) => jump_pressed(),
-                        _ => (),
+
+                    #[cfg(target_arch = "wasm32")]
+                    if prev_place_block_type != self.place_block_type {
+                        dom_controls::place_block_type_changed(&self.place_block_type.to_string());
                     }
                 }
-
-                #[cfg(target_arch = "wasm32")]
-                let prev_place_block_type = self.place_block_type;
-
-                match input.virtual_keycode {
-                    Some(VirtualKeyCode::Key1) => self.place_block_type = BlockType::Stone,
-                    Some(VirtualKeyCode::Key2) => self.place_block_type = BlockType::Dirt,
-                    Some(VirtualKeyCode::Key3) => self.place_block_type = BlockType::OakPlank,
-                    Some(VirtualKeyCode::Key4) => self.place_block_type = BlockType::Glass,
-                    Some(VirtualKeyCode::Key5) => self.place_block_type = BlockType::Sand,
-                    _ => (),
+                InputEvent::PitchYawJoystickMoved { vector } => {
+                    const PITCH_YAW_JOYSTICK_SCALE_FACTOR: f64 = 2.5;
+                    self.input_state.last_joystick_vector = (
+                        vector.0 * PITCH_YAW_JOYSTICK_SCALE_FACTOR,
+                        vector.1 * PITCH_YAW_JOYSTICK_SCALE_FACTOR,
+                    );
                 }
-
-                #[cfg(target_arch = "wasm32")]
-                if prev_place_block_type != self.place_block_type {
+                InputEvent::PitchYawJoystickReleased => {
+                    self.input_state.last_joystick_vector = (0.0, 0.0);
+                }
+                InputEvent::TranslationJoystickMoved { vector } => {
+                    self.input_state.last_translation_joystick_vector = vector;
+                }
+                InputEvent::TranslationJoystickReleased => {
+                    self.input_state.last_translation_joystick_vector = (0.0, 0.0);
+                }
+                InputEvent::YButtonPressed => self
+                    .input_state
+                    .movement
+                    .set_pressed(MovementKey::Jump, true),
+                InputEvent::YButtonReleased => self
+                    .input_state
+                    .movement
+                    .set_pressed(MovementKey::Jump, false),
+                InputEvent::BlockPreviewPressed => {
+                    let current_block_type_idx = BLOCK_ORDER
+                        .iter()
+                        .position(|&block_type| block_type == self.place_block_type)
+                        .unwrap();
+                    let next_block_type_idx = (current_block_type_idx + 1) % BLOCK_ORDER.len();
+                    self.place_block_type = BLOCK_ORDER[next_block_type_idx];
+                    #[cfg(target_arch = "wasm32")]
                     dom_controls::place_block_type_changed(&self.place_block_type.to_string());
                 }
             }
-            _ => (),
         }
     }
+}
 
-    pub fn process_web_dom_button_event(&mut self, event: &DomControlsUserEvent) {
-        const BLOCK_ORDER: [BlockType; 5] = [
-            BlockType::Stone,
-            BlockType::Dirt,
-            BlockType::OakPlank,
-            BlockType::Glass,
-            BlockType::Sand,
-        ];
-        match event {
-            DomControlsUserEvent::PitchYawJoystickMoved { vector } => {
-                const PITCH_YAW_JOYSTICK_SCALE_FACTOR: f64 = 2.5;
-                self.input_state.last_joystick_vector = (
-                    vector.0 * PITCH_YAW_JOYSTICK_SCALE_FACTOR,
-                    vector.1 * PITCH_YAW_JOYSTICK_SCALE_FACTOR,
-                );
-            }
-            DomControlsUserEvent::PitchYawJoystickReleased => {
-                self.input_state.last_joystick_vector = (0.0, 0.0);
-            }
-            DomControlsUserEvent::TranslationJoystickMoved { vector } => {
-                self.input_state.last_translation_joystick_vector = *vector;
-            }
-            DomControlsUserEvent::TranslationJoystickReleased => {
-                self.input_state.last_translation_joystick_vector = (0.0, 0.0);
+/// Checks whether `character_collider` at `character_pos` is in contact with any of `blocks`
+/// along `direction` -- used both for the floor-contact check that drives gravity/jump
+/// eligibility (`direction = -Y`) and the step-up probe in `tick_dynamic_entity`
+/// (`direction` = the entity's horizontal travel direction).
+fn check_collision_in_direction(
+    character_pos: &na::Isometry3<f32>,
+    character_collider: &Cylinder,
+    direction: glam::Vec3,
+    blocks: &Vec<[usize; 3]>,
+    contact_tolerance: f32,
+) -> Option<parry3d::query::Contact> {
+    for block_pos in blocks {
+        let block_collider = Cuboid::new(na::vector![0.5, 0.5, 0.5]);
+        let block_pos = na::Isometry3::new(
+            na::vector![
+                block_pos[0] as f32 + 0.5,
+                block_pos[1] as f32 + 0.5,
+                block_pos[2] as f32 + 0.5
+            ],
+            na::zero(),
+        );
+
+        if let Some(contact) = parry3d::query::contact(
+            character_pos,
+            character_collider,
+            &block_pos,
+            &block_collider,
+            0.01, // tolerance
+        )
+        .unwrap()
+        {
+            let contact_normal =
+                glam::Vec3::new(contact.normal1.x, contact.normal1.y, contact.normal1.z);
+
+            // Project the normal onto the plane perpendicular to the direction
+            let normal_on_plane = contact_normal - direction * contact_normal.dot(direction);
+
+            // If true, the normal does not have significant components in directions other than `direction`
+            let is_normal_mostly_parallel_to_direction = normal_on_plane.length() < 0.5;
+
+            if is_normal_mostly_parallel_to_direction && contact.dist.abs() > contact_tolerance {
+                return Some(contact);
             }
-            DomControlsUserEvent::YButtonPressed => {
-                self.input_state.jump_button_state = match self.input_state.jump_button_state {
-                    ButtonState::Pressed => ButtonState::Held,
-                    ButtonState::Held => ButtonState::Held,
-                    _ => ButtonState::Pressed,
+        }
+    }
+    None
+}
+
+/// Whether `entity` is currently resting on solid ground, sampled from the 3x3 column of
+/// blocks under its feet. Drives both `physics_tick`'s player-only gravity/jump state machine
+/// and the gravity `physics_tick` applies to every other entity before `tick_dynamic_entity`.
+fn is_grounded(
+    get_block_type: impl Fn(usize, usize, usize) -> BlockType,
+    entity: &DynamicEntity,
+) -> bool {
+    const FLOOR_CONTACT_TOLERANCE: f32 = 0.001;
+
+    let collider = entity.collider();
+    let pos = na::Isometry3::new(
+        na::vector![entity.position.x, entity.position.y, entity.position.z],
+        na::zero(),
+    );
+
+    // Feet of the entity, a cylinder whose middle sits at `entity.position`.
+    let feet_pos = (
+        entity.position.x,
+        entity.position.y - entity.collider_half_height,
+        entity.position.z,
+    );
+
+    let mut floor_blocks_to_check_collision: Vec<[usize; 3]> = vec![];
+    for (dx, dz) in iproduct!(-1..=1, -1..=1) {
+        let block_pos = [
+            (feet_pos.0 + (dx as f32)).floor() as usize,
+            (feet_pos.1).floor() as usize,
+            (feet_pos.2 + (dz as f32)).floor() as usize,
+        ];
+        if get_block_type(block_pos[0], block_pos[1], block_pos[2]).is_collidable() {
+            floor_blocks_to_check_collision.push(block_pos);
+        }
+    }
+
+    check_collision_in_direction(
+        &pos,
+        &collider,
+        -glam::Vec3::Y,
+        &floor_blocks_to_check_collision,
+        FLOOR_CONTACT_TOLERANCE / 4.0, // lower tolerance
+    )
+    .is_some()
+}
+
+/// The block-collision core every `DynamicEntity` shares once its `velocity` for this tick is
+/// already decided: an optional step-up over a single-block ledge, the swept collision sweep
+/// against nearby blocks, committing the resolved position, and refreshing `is_underwater`.
+/// `physics_tick` runs the input-driven acceleration and jump state machine that's specific to
+/// the player before calling this for `character_entity.dynamics`; it's the same routine a
+/// future mob or dropped-item tick calls after deciding its own `velocity`.
+fn tick_dynamic_entity(
+    get_block_type: impl Fn(usize, usize, usize) -> BlockType,
+    entity: &mut DynamicEntity,
+    is_contacting_floor: bool,
+) {
+    let collider = entity.collider();
+    let curr_pos = na::Isometry3::new(
+        na::vector![entity.position.x, entity.position.y, entity.position.z],
+        na::zero(),
+    );
+    let potential_new_pos = entity.position + entity.velocity;
+
+    // Collect blocks to check for collision, covering both the current and potential-next
+    // position so a fast sweep can't skip over a block it starts or ends inside of.
+    let mut blocks_to_check_collision: Vec<[usize; 3]> = vec![];
+
+    let min_x =
+        (entity.position.x.min(potential_new_pos.x) - entity.collider_half_extent).floor() as isize;
+    let max_x =
+        (entity.position.x.max(potential_new_pos.x) + entity.collider_half_extent).ceil() as isize;
+    let min_y =
+        (entity.position.y.min(potential_new_pos.y) - entity.collider_half_height).floor() as isize;
+    let max_y =
+        (entity.position.y.max(potential_new_pos.y) + entity.collider_half_height).ceil() as isize;
+    let min_z =
+        (entity.position.z.min(potential_new_pos.z) - entity.collider_half_extent).floor() as isize;
+    let max_z =
+        (entity.position.z.max(potential_new_pos.z) + entity.collider_half_extent).ceil() as isize;
+
+    // Iterate over the blocks in the range and collect the ones that are collidable
+    for x in min_x..=max_x {
+        for y in min_y..=max_y {
+            for z in min_z..=max_z {
+                if x < 0 || y < 0 || z < 0 {
+                    // Skip blocks with negative indices, if your world has no blocks at negative coordinates
+                    continue;
+                }
+                let block_pos = [x as usize, y as usize, z as usize];
+                if get_block_type(block_pos[0], block_pos[1], block_pos[2]).is_collidable() {
+                    blocks_to_check_collision.push(block_pos);
                 }
             }
-            DomControlsUserEvent::YButtonReleased => {
-                self.input_state.jump_button_state = match self.input_state.jump_button_state {
-                    ButtonState::Pressed => ButtonState::Released,
-                    ButtonState::Held => ButtonState::Released,
-                    _ => ButtonState::Idle,
+        }
+    }
+
+    // Step-up: walking into a single-block ledge while grounded would otherwise just
+    // zero out horizontal velocity in the sweep below. Probe whether raising the
+    // entity clears the obstruction, and if there's solid, non-liquid ground to land
+    // on up there, start this tick's sweep from the raised position instead -- letting
+    // it climb stairs/ledges without having to jump.
+    const MAX_STEP_HEIGHT: f32 = 1.0;
+    const STEP_HEIGHT_INCREMENT: f32 = 0.1;
+    const STEP_CHECK_TOLERANCE: f32 = 0.01;
+
+    let mut tick_start_pos = curr_pos;
+    let horizontal_velocity = glam::Vec3::new(entity.velocity.x, 0.0, entity.velocity.z);
+    let horizontal_direction = horizontal_velocity.normalize_or_zero();
+    if is_contacting_floor
+        && horizontal_direction != glam::Vec3::ZERO
+        && check_collision_in_direction(
+            &curr_pos,
+            &collider,
+            horizontal_direction,
+            &blocks_to_check_collision,
+            STEP_CHECK_TOLERANCE,
+        )
+        .is_some()
+    {
+        let mut step_height = STEP_HEIGHT_INCREMENT;
+        while step_height <= MAX_STEP_HEIGHT {
+            let mut raised_pos = curr_pos;
+            raised_pos.translation.vector.y += step_height;
+
+            let is_still_blocked = check_collision_in_direction(
+                &raised_pos,
+                &collider,
+                horizontal_direction,
+                &blocks_to_check_collision,
+                STEP_CHECK_TOLERANCE,
+            )
+            .is_some();
+
+            if !is_still_blocked {
+                // There's headroom at this height -- check there's solid, non-liquid
+                // ground just past the ledge before committing to the step (so we don't
+                // step out over a pit or into water; `is_collidable` already excludes
+                // both liquids and non-collidable sprites).
+                let landing_block_type = get_block_type(
+                    (raised_pos.translation.vector.x + horizontal_direction.x).floor() as usize,
+                    (raised_pos.translation.vector.y
+                        - entity.collider_half_height
+                        - STEP_CHECK_TOLERANCE)
+                        .floor() as usize,
+                    (raised_pos.translation.vector.z + horizontal_direction.z).floor() as usize,
+                );
+                if landing_block_type.is_collidable() {
+                    tick_start_pos = raised_pos;
                 }
+                break;
             }
-            DomControlsUserEvent::BlockPreviewPressed => {
-                let current_block_type_idx = BLOCK_ORDER
-                    .iter()
-                    .position(|&block_type| block_type == self.place_block_type)
-                    .unwrap();
-                let next_block_type_idx = (current_block_type_idx + 1) % BLOCK_ORDER.len();
-                let next_block_type = BLOCK_ORDER[next_block_type_idx];
-                self.place_block_type = next_block_type;
-                #[cfg(target_arch = "wasm32")]
-                dom_controls::place_block_type_changed(&self.place_block_type.to_string());
+            step_height += STEP_HEIGHT_INCREMENT;
+        }
+    }
+
+    // Swept collision: cast the entity's full motion for this tick against every candidate
+    // block, stop at the earliest time-of-impact, then slide the leftover motion along the
+    // contact plane and repeat. This replaces the old per-axis discrete resolution, which
+    // could tunnel through thin geometry at high velocity since it only looked at the start
+    // and end positions, never the path between.
+    const MAX_SLIDE_ITERATIONS: usize = 4;
+    let (sweep_pos, remaining_motion) = sweep_and_slide(
+        tick_start_pos,
+        &collider,
+        entity.velocity,
+        &blocks_to_check_collision,
+        MAX_SLIDE_ITERATIONS,
+    );
+
+    // The final slide direction becomes next tick's starting velocity, so the entity keeps
+    // gliding along the surface it hit instead of stopping dead.
+    entity.velocity = remaining_motion;
+
+    // Apply the final position and velocity to the entity
+    entity.prev_position = entity.position;
+    entity.position = glam::Vec3::new(
+        sweep_pos.translation.vector.x,
+        sweep_pos.translation.vector.y,
+        sweep_pos.translation.vector.z,
+    );
+
+    // Update if the entity is underwater
+    const WATER_CHECK_Y_ADJUST: f32 = 0.5 + (1.0 - WATER_BLOCK_Y_HEIGHT); // +0.5 for eye level, +0.2 for the water block's lowered top
+    let prev_underwater = entity.is_underwater;
+    entity.is_underwater = get_block_type(
+        entity.position.x as usize,
+        (entity.position.y + WATER_CHECK_Y_ADJUST) as usize,
+        entity.position.z as usize,
+    ) == BlockType::Water;
+
+    if !prev_underwater && entity.is_underwater {
+        // Water can break a fall
+        entity.velocity.y /= 4.0;
+    }
+}
+
+/// Sweeps `character_collider`'s full `velocity` for one physics tick, starting at `start`,
+/// against the unit-cube colliders implied by `blocks_to_check_collision`. Stops at the
+/// earliest time-of-impact, slides the leftover motion along the contact plane
+/// (`v -= v.dot(n) * n`), and repeats against the same block set until the motion is
+/// exhausted or `max_slide_iterations` is reached. Returns the resolved end isometry and the
+/// (possibly deflected) velocity to carry into the next tick.
+fn sweep_and_slide(
+    start: na::Isometry3<f32>,
+    character_collider: &Cylinder,
+    velocity: glam::Vec3,
+    blocks_to_check_collision: &[[usize; 3]],
+    max_slide_iterations: usize,
+) -> (na::Isometry3<f32>, glam::Vec3) {
+    // Nudge off the contact surface by this much after each slide so the next iteration (or
+    // next tick's sweep) doesn't immediately re-report a zero-distance collision.
+    const SWEEP_SKIN_WIDTH: f32 = 0.001;
+
+    let block_colliders: Vec<(na::Isometry3<f32>, Cuboid)> = blocks_to_check_collision
+        .iter()
+        .map(|block_pos| {
+            (
+                na::Isometry3::new(
+                    na::vector![
+                        block_pos[0] as f32 + 0.5,
+                        block_pos[1] as f32 + 0.5,
+                        block_pos[2] as f32 + 0.5
+                    ],
+                    na::zero(),
+                ),
+                Cuboid::new(na::vector![0.5, 0.5, 0.5]),
+            )
+        })
+        .collect();
+
+    let mut sweep_pos = start;
+    let mut remaining_motion = velocity;
+    for _ in 0..max_slide_iterations {
+        if remaining_motion.length_squared() <= f32::EPSILON {
+            break;
+        }
+
+        let vel_na = na::vector![remaining_motion.x, remaining_motion.y, remaining_motion.z];
+        let closest_toi = block_colliders
+            .iter()
+            .filter_map(|(block_pos, block_collider)| {
+                parry3d::query::time_of_impact(
+                    &sweep_pos,
+                    &vel_na,
+                    character_collider,
+                    block_pos,
+                    &na::Vector3::zeros(),
+                    block_collider,
+                    1.0, // max_toi, as a fraction of `remaining_motion`
+                    true,
+                )
+                .ok()
+                .flatten()
+            })
+            .min_by(|a, b| a.toi.partial_cmp(&b.toi).unwrap());
+
+        match closest_toi {
+            Some(toi) => {
+                let travel = remaining_motion * toi.toi;
+                sweep_pos.translation.vector += na::vector![travel.x, travel.y, travel.z];
+
+                let normal = glam::Vec3::new(toi.normal1.x, toi.normal1.y, toi.normal1.z)
+                    .normalize_or_zero();
+                let leftover = remaining_motion * (1.0 - toi.toi);
+                remaining_motion = leftover - normal * leftover.dot(normal);
+
+                // `normal1` is the character's outward normal, pointing at the block.
+                sweep_pos.translation.vector -= na::vector![
+                    normal.x * SWEEP_SKIN_WIDTH,
+                    normal.y * SWEEP_SKIN_WIDTH,
+                    normal.z * SWEEP_SKIN_WIDTH
+                ];
+            }
+            None => {
+                sweep_pos.translation.vector +=
+                    na::vector![remaining_motion.x, remaining_motion.y, remaining_motion.z];
+                break;
             }
-            _ => (),
         }
     }
+
+    (sweep_pos, remaining_motion)
+}
+
+#[cfg(test)]
+mod sweep_and_slide_tests {
+    use super::*;
+
+    fn character_at(x: f32, y: f32, z: f32) -> na::Isometry3<f32> {
+        na::Isometry3::new(na::vector![x, y, z], na::zero())
+    }
+
+    #[test]
+    fn fast_fall_stops_on_the_floor_instead_of_tunneling_through_it() {
+        // A 2-voxel-tall cylinder falling fast enough to cross an entire block in one tick
+        // would tunnel straight through a single floor block under the old per-axis discrete
+        // resolution, which only ever looked at the start and end positions.
+        let character_collider = Cylinder::new(1.0, 0.5);
+        let start = character_at(0.5, 3.0, 0.5);
+        let velocity = glam::Vec3::new(0.0, -5.0, 0.0);
+        let floor_blocks = vec![[0usize, 0usize, 0usize]];
+
+        let (end_pos, end_velocity) =
+            sweep_and_slide(start, &character_collider, velocity, &floor_blocks, 4);
+
+        // The character's feet (at `y - 1.0`) should rest at or above the floor's top face
+        // (y = 1.0), not have fallen through it.
+        assert!(end_pos.translation.vector.y - 1.0 >= 1.0 - 1e-3);
+        // Downward motion should have been absorbed by the floor contact.
+        assert!(end_velocity.y.abs() < 1e-3);
+    }
+
+    #[test]
+    fn wall_grazing_slides_along_the_surface_instead_of_stopping_dead() {
+        // Moving diagonally into a wall should deflect only the into-wall component of
+        // velocity, letting the character keep sliding along the wall's face.
+        let character_collider = Cylinder::new(1.0, 0.5);
+        let start = character_at(0.5, 1.0, 0.5);
+        let velocity = glam::Vec3::new(2.0, 0.0, 1.0);
+        // A wall filling the x=2 plane, blocking +X motion but not +Z motion.
+        let wall_blocks: Vec<[usize; 3]> = (0..4).map(|z| [2usize, 1usize, z]).collect();
+
+        let (end_pos, end_velocity) =
+            sweep_and_slide(start, &character_collider, velocity, &wall_blocks, 4);
+
+        // Blocked along X: the character shouldn't have crossed into the wall's block column.
+        assert!(end_pos.translation.vector.x < 2.0 - 0.5 + 1e-3);
+        assert!(end_velocity.x.abs() < 1e-3);
+        // Unblocked along Z: the character should have kept moving in that direction.
+        assert!(end_pos.translation.vector.z > start.translation.vector.z);
+        assert!(end_velocity.z > 0.0);
+    }
 }
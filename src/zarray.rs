@@ -92,6 +92,55 @@ SOFTWARE.
 
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
+use std::mem::MaybeUninit;
+
+/// Builds `[T; N]` by cloning `default_val` into every slot.
+///
+/// The naive `[default_val; N]` array-repeat expression requires `T: Copy`,
+/// which is why patches used to reject heap-allocating element types (e.g.
+/// `String`, `Vec<_>`, or any struct containing them). Building the array
+/// via `MaybeUninit` instead only requires `T: Clone`.
+///
+/// Panic safety: if `default_val.clone()` panics partway through filling the
+/// array, `Guard::drop` runs the destructors of just the slots written so
+/// far, so nothing is leaked and nothing uninitialized is ever dropped.
+fn new_cloned_array<T: Clone, const N: usize>(default_val: &T) -> [T; N] {
+	struct Guard<T, const N: usize> {
+		array: [MaybeUninit<T>; N],
+		initialized: usize,
+	}
+
+	impl<T, const N: usize> Drop for Guard<T, N> {
+		fn drop(&mut self) {
+			for slot in &mut self.array[..self.initialized] {
+				unsafe {
+					slot.assume_init_drop();
+				}
+			}
+		}
+	}
+
+	// SAFETY: an array of `MaybeUninit<T>` is itself always in an
+	// initialized state, even though none of its elements are yet -- it's
+	// only unsound to `assume_init()` the *outer* array into `[T; N]` before
+	// every element has actually been written.
+	let mut guard: Guard<T, N> = Guard {
+		array: unsafe { MaybeUninit::uninit().assume_init() },
+		initialized: 0,
+	};
+
+	for slot in guard.array.iter_mut() {
+		slot.write(default_val.clone());
+		guard.initialized += 1;
+	}
+
+	// All N slots are now initialized. Read them out as `[T; N]` and forget
+	// the guard so its `Drop` doesn't immediately drop what we just handed
+	// to the caller.
+	let array = unsafe { std::mem::transmute_copy::<[MaybeUninit<T>; N], [T; N]>(&guard.array) };
+	std::mem::forget(guard);
+	array
+}
 
 /// This struct is an error type that is returned when attempting to get a value that is outside
 /// the range of the data. It implements the Debug and Display traits so that it can be easily
@@ -137,6 +186,51 @@ fn vec_to_string(v: &Vec<usize>) -> String{
 	sb += &String::from(")");
 	return sb;
 }
+
+/// How `neighborhood`/`apply_stencil` should handle a window cell that
+/// falls outside the array's bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundaryPolicy {
+	/// clamp the out-of-bounds coordinate to the nearest edge
+	Clamp,
+	/// wrap the out-of-bounds coordinate around to the opposite edge, same
+	/// as `wrapped_get`
+	Wrap,
+	/// omit the out-of-bounds cell entirely, so the window can yield fewer
+	/// than `(2*radius+1)^2` (or `^3`) items
+	Skip,
+}
+
+/// Error type returned when reading a Z-order array back out of a byte
+/// buffer produced by `as_bytes` (e.g. a memory-mapped file), as opposed to
+/// a fresh `ZArray2D`/`ZArray3D::new`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum FormatError {
+	/// the buffer's first 4 bytes don't match the magic this format writes
+	BadMagic,
+	/// the buffer's version byte isn't one this build of zarray can read
+	UnsupportedVersion(u8),
+	/// `size_of::<T>()` at read time doesn't match the element size recorded when the buffer was written
+	ElementSizeMismatch { expected: usize, actual: usize },
+	/// the buffer is shorter than its own header says it should be
+	Truncated { expected: usize, actual: usize },
+}
+
+impl Display for FormatError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			FormatError::BadMagic => write!(f, "Error: buffer does not start with the expected zarray magic bytes"),
+			FormatError::UnsupportedVersion(v) => write!(f, "Error: unsupported zarray format version {}", v),
+			FormatError::ElementSizeMismatch { expected, actual } =>
+				write!(f, "Error: element size mismatch (buffer was written with {}-byte elements, reading as {}-byte elements)", expected, actual),
+			FormatError::Truncated { expected, actual } =>
+				write!(f, "Error: buffer is truncated (expected at least {} bytes, got {})", expected, actual),
+		}
+	}
+}
+
+impl Error for FormatError {}
+
 /// This module is used for storing 2-dimensional data arrays, and internally uses Z-index arrays
 /// to improve data localization and alignment to the CPU cache-line fetches. In other words, use
 /// this to improve performance for 2D data that is randomly accessed rather than raster scanned
@@ -174,19 +268,62 @@ fn vec_to_string(v: &Vec<usize>) -> String{
 ///   }
 /// }
 /// ```
+/// Builds a `ZArray2D` or `ZArray3D` from a nested array literal, mirroring
+/// how `ndarray::array!` dispatches on bracket depth: one level of nesting
+/// builds a `ZArray2D` row by row, two levels builds a `ZArray3D` from
+/// stacked 2D layers.
+/// # Panics
+/// Panics on a ragged literal (rows, or layers, of unequal length) -- see
+/// `ZArray2D::from_rows` / `ZArray3D::from_slices`.
+/// # Examples
+/// ```
+/// use minecrust::zarray;
+/// let grid = zarray![[1, 2, 3], [4, 5, 6]];
+/// assert_eq!(grid.dimensions(), (3, 2));
+///
+/// let cube = zarray![[[1, 2], [3, 4]], [[5, 6], [7, 8]]];
+/// assert_eq!(cube.dimensions(), (2, 2, 2));
+/// ```
+#[macro_export]
+macro_rules! zarray {
+	( $( [ $( [ $( $elem:expr ),* $(,)? ] ),* $(,)? ] ),+ $(,)? ) => {{
+		$crate::zarray::z3d::ZArray3D::from_slices(&[
+			$( &[ $( &[ $( $elem ),* ][..] ),* ][..] ),+
+		])
+	}};
+	( $( [ $( $elem:expr ),* $(,)? ] ),+ $(,)? ) => {{
+		$crate::zarray::z2d::ZArray2D::from_rows(&[
+			$( &[ $( $elem ),* ][..] ),+
+		])
+	}};
+}
+
 pub mod z2d {
 	// Z-order indexing in 2 dimensions
 
 	use std::marker::PhantomData;
-	use super::LookUpError;
+	use super::{FormatError, LookUpError};
 
 	/// Private struct for holding an 8x8 data patch
-	#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+	#[repr(C)]
+	#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 	#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 	struct Patch<T>{
 		contents: [T;64]
 	}
 
+	// `Patch<T>` dropped its blanket `Copy` derive so non-`Copy` element types
+	// (e.g. `String`) can be stored (see `ZArray2D::new`'s use of
+	// `new_cloned_array`), but it's still `Copy` whenever `T` is, which the
+	// `bytemuck::Pod` impls below require.
+	impl<T: Copy> Copy for Patch<T> {}
+
+	// SAFETY: `Patch<T>` is `#[repr(C)]` with a single `[T; 64]` field, so it
+	// has the same layout as that array and carries no padding/invalid bit
+	// patterns beyond what `T` itself allows.
+	unsafe impl<T: bytemuck::Pod> bytemuck::Zeroable for Patch<T> {}
+	unsafe impl<T: bytemuck::Pod> bytemuck::Pod for Patch<T> {}
+
 	impl<T> Patch<T> {
 		/// data patch getter
 		/// # Parameters
@@ -210,6 +347,10 @@ pub mod z2d {
 			self.contents[i] = new_val;
 			//return old_val;
 		}
+		/// data patch mutable getter, same addressing as `get`
+		fn get_mut(&mut self, x: usize, y:usize) -> &mut T {
+			return &mut self.contents[zorder_4bit_to_8bit(x as u8 & 0x07, y as u8 & 0x07) as usize];
+		}
 	}
 
 	/// function for converting coordinate to index of data patch in the array of patches
@@ -230,13 +371,13 @@ pub mod z2d {
 		_phantomdata: PhantomData<T>,
 	}
 
-	impl<T> ZArray2D<T> where T: Copy {
+	impl<T> ZArray2D<T> where T: Clone {
 		/// Create a Z-index 2D array of values, initially filled with the provided default value
 		/// # Parameters
 		/// * **width** - size of this 2D array in the X dimension
 		/// * **height** - size of this 2D array in the Y dimension
 		/// * **default_val** - initial fill value (if a struct type, then it must implement the
-		/// Copy trait)
+		/// Clone trait)
 		/// # Returns
 		/// Returns an initialized *ZArray2D* struct filled with *default_val*
 		pub fn new(width: usize, height: usize, default_val: T) -> ZArray2D<T>{
@@ -245,11 +386,37 @@ pub mod z2d {
 			let patch_count = pwidth * pheight;
 			let mut p = Vec::with_capacity(patch_count);
 			for _ in 0..patch_count{
-				p.push(Patch{contents: [default_val; 64]});
+				p.push(Patch{contents: super::new_cloned_array(&default_val)});
 			}
 			return ZArray2D {width, height, pwidth, patches: p, _phantomdata: PhantomData};
 		}
 
+		/// Builds a `ZArray2D` from row-major nested slices (`rows[y][x]`), as
+		/// used by the `zarray!` literal macro.
+		/// # Parameters
+		/// * **rows** - one slice per row, top row (y=0) first
+		/// # Panics
+		/// Panics if `rows` is empty, or if any row's length differs from the
+		/// first row's (a ragged literal has no well-defined width)
+		/// # Returns
+		/// Returns a *ZArray2D* with `width = rows[0].len()` and `height = rows.len()`
+		pub fn from_rows(rows: &[&[T]]) -> ZArray2D<T> {
+			let height = rows.len();
+			assert!(height > 0, "zarray!: cannot build a ZArray2D from an empty literal");
+			let width = rows[0].len();
+			for (y, row) in rows.iter().enumerate() {
+				assert_eq!(row.len(), width,
+					"zarray!: ragged row {} (expected length {}, got {})", y, width, row.len());
+			}
+			let mut arr = ZArray2D::new(width, height, rows[0][0].clone());
+			for (y, row) in rows.iter().enumerate() {
+				for (x, val) in row.iter().enumerate() {
+					arr.set(x, y, val.clone()).unwrap();
+				}
+			}
+			arr
+		}
+
 		/// Gets the (x, y) size of this 2D array
 		/// # Returns
 		/// Returns a tuple of (width, height) for this 2D array
@@ -422,7 +589,7 @@ pub mod z2d {
 		pub fn fill(&mut self, x1: usize, y1: usize, x2: usize, y2: usize, new_val: T)
 					-> Result<(), LookUpError> {
 			for y in y1..y2{ for x in x1..x2{
-				self.set(x, y, new_val)?;
+				self.set(x, y, new_val.clone())?;
 			} }
 			Ok(())
 		}
@@ -438,7 +605,7 @@ pub mod z2d {
 		/// (x1, y1) -> (x2, y2) with wrapped axese
 		pub fn wrapped_fill(&mut self, x1: isize, y1: isize, x2: isize, y2: isize, new_val: T) {
 			for y in y1..y2{ for x in x1..x2{
-				self.wrapped_set(x, y, new_val);
+				self.wrapped_set(x, y, new_val.clone());
 			} }
 		}
 
@@ -453,12 +620,363 @@ pub mod z2d {
 		/// (x1, y1) -> (x2, y2)
 		pub fn bounded_fill(&mut self, x1: isize, y1: isize, x2: isize, y2: isize, new_val: T) {
 			for y in y1..y2{ for x in x1..x2{
-				self.bounded_set(x, y, new_val);
+				self.bounded_set(x, y, new_val.clone());
 			} }
 		}
 
 	}
 
+	impl<T> ZArray2D<T> {
+		/// Builds a new array of the same dimensions by applying `f` to every
+		/// cell, including patch padding beyond `width`/`height` (which is
+		/// never observable via `get`). Walks `self`'s patches in their
+		/// existing storage order, so the result stays just as cache-local
+		/// as `self`.
+		pub fn map<U: Copy>(&self, f: impl Fn(&T) -> U) -> ZArray2D<U> {
+			let seed = f(&self.patches[0].contents[0]);
+			let mut out = ZArray2D::new(self.width, self.height, seed);
+			for (patch, out_patch) in self.patches.iter().zip(out.patches.iter_mut()) {
+				for i in 0..64 {
+					out_patch.contents[i] = f(&patch.contents[i]);
+				}
+			}
+			out
+		}
+
+		/// Iterates `((x, y), morton_index, &T)` for every in-bounds cell,
+		/// walking the true patch-local Z-order storage sequence (the same
+		/// index space as `Patch::contents`) rather than a raster sweep, so
+		/// each 8x8 patch is read fully in its own on-disk/on-heap order
+		/// before moving to the next. `morton_index` is the flattened index
+		/// `patch_idx * 64 + local_z_order_index` -- the same offset
+		/// `as_bytes`' patch array is laid out at.
+		pub fn indexed_iter(&self) -> impl Iterator<Item = ((usize, usize), usize, &T)> + '_ {
+			let (width, height, pwidth) = (self.width, self.height, self.pwidth);
+			self.patches.iter().enumerate().flat_map(move |(patch_idx, patch)| {
+				let (px, py) = (patch_idx % pwidth, patch_idx / pwidth);
+				(0..64usize).filter_map(move |cell_idx| {
+					let (lx, ly) = patch_local_decode(cell_idx as u8);
+					let (x, y) = (px * 8 + lx, py * 8 + ly);
+					if x < width && y < height {
+						Some(((x, y), patch_idx * 64 + cell_idx, &patch.contents[cell_idx]))
+					} else {
+						None
+					}
+				})
+			})
+		}
+
+		/// Iterates `&T` for every in-bounds cell; see `indexed_iter` for
+		/// traversal order.
+		pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+			self.indexed_iter().map(|(_, _, v)| v)
+		}
+
+		/// Iterates `&mut T` for every in-bounds cell, same traversal order
+		/// as `indexed_iter`.
+		pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+			IterMut {
+				patches: self.patches.as_mut_ptr(),
+				patch_count: self.patches.len(),
+				pwidth: self.pwidth,
+				width: self.width,
+				height: self.height,
+				patch_idx: 0,
+				cell_idx: 0,
+				_marker: PhantomData,
+			}
+		}
+	}
+
+	/// Deinterleaves a 6-bit patch-local Z-order index into `(x, y)`, each in
+	/// `0..8`. Thin wrapper over `zdecode` restricted to 3-bit coordinates,
+	/// the inverse of the encoding `zorder_4bit_to_8bit` computes over the
+	/// same range (see `Patch::get`/`set`).
+	fn patch_local_decode(idx: u8) -> (usize, usize) {
+		let (x, y) = zdecode(idx as u64, 3);
+		(x as usize, y as usize)
+	}
+
+	/// Mutable, cache-friendly patch-then-Z-order iterator produced by
+	/// `ZArray2D::iter_mut`.
+	pub struct IterMut<'a, T> {
+		patches: *mut Patch<T>,
+		patch_count: usize,
+		pwidth: usize,
+		width: usize,
+		height: usize,
+		patch_idx: usize,
+		cell_idx: usize,
+		_marker: PhantomData<&'a mut T>,
+	}
+
+	impl<'a, T> Iterator for IterMut<'a, T> {
+		type Item = &'a mut T;
+		fn next(&mut self) -> Option<Self::Item> {
+			loop {
+				if self.patch_idx >= self.patch_count {
+					return None;
+				}
+				if self.cell_idx >= 64 {
+					self.patch_idx += 1;
+					self.cell_idx = 0;
+					continue;
+				}
+				let cell_idx = self.cell_idx;
+				self.cell_idx += 1;
+				let (lx, ly) = patch_local_decode(cell_idx as u8);
+				let (px, py) = (self.patch_idx % self.pwidth, self.patch_idx / self.pwidth);
+				let (x, y) = (px * 8 + lx, py * 8 + ly);
+				if x < self.width && y < self.height {
+					// SAFETY: each (patch_idx, cell_idx) pair is visited
+					// exactly once over this iterator's lifetime, so the
+					// mutable reference handed out here never aliases one
+					// handed out by a previous or future call to `next`.
+					unsafe {
+						let patch = &mut *self.patches.add(self.patch_idx);
+						return Some(&mut patch.contents[cell_idx]);
+					}
+				}
+			}
+		}
+	}
+
+	/// Starts a `Zip2D` co-iteration, mirroring `ndarray`'s
+	/// `Zip::from(a).and(b).for_each(...)`.
+	pub struct Zip2D<'a, T> {
+		first: &'a ZArray2D<T>,
+	}
+
+	impl<'a, T> Zip2D<'a, T> {
+		pub fn from(first: &'a ZArray2D<T>) -> Self {
+			Zip2D { first }
+		}
+
+		/// Pairs a second, equally-sized array to co-iterate with the first.
+		/// # Panics
+		/// Panics if `second`'s dimensions differ from the first array's.
+		pub fn and<U>(self, second: &'a ZArray2D<U>) -> ZipWith2D<'a, T, U> {
+			assert_eq!(self.first.dimensions(), second.dimensions(),
+				"Zip2D: arrays must have equal dimensions");
+			ZipWith2D { first: self.first, second }
+		}
+	}
+
+	/// Co-iterates two equally-sized `ZArray2D`s in the same patch-then-Z-order
+	/// traversal as `indexed_iter`, with no per-cell bounds checks against
+	/// each individual array (both are walked by the same patch/cell index
+	/// pair, validated up front by `Zip2D::and`).
+	pub struct ZipWith2D<'a, T, U> {
+		first: &'a ZArray2D<T>,
+		second: &'a ZArray2D<U>,
+	}
+
+	impl<'a, T, U> ZipWith2D<'a, T, U> {
+		pub fn for_each(self, mut f: impl FnMut((usize, usize), &T, &U)) {
+			let (width, height, pwidth) = (self.first.width, self.first.height, self.first.pwidth);
+			for (patch_idx, (pa, pb)) in self.first.patches.iter().zip(self.second.patches.iter()).enumerate() {
+				let (px, py) = (patch_idx % pwidth, patch_idx / pwidth);
+				for cell_idx in 0..64usize {
+					let (lx, ly) = patch_local_decode(cell_idx as u8);
+					let (x, y) = (px * 8 + lx, py * 8 + ly);
+					if x < width && y < height {
+						f((x, y), &pa.contents[cell_idx], &pb.contents[cell_idx]);
+					}
+				}
+			}
+		}
+	}
+
+	impl<T> ZArray2D<T> where T: Clone {
+		/// Returns a heap-free iterator over the `(2*radius+1)^2` cells
+		/// centered on `(x, y)`, handling out-of-bounds window cells
+		/// according to `policy`. Centralizes the `dx`/`dy` double loop that
+		/// blur and cellular-automata code would otherwise hand-roll with
+		/// `bounded_get`/`wrapped_get`.
+		pub fn neighborhood(&self, x: usize, y: usize, radius: usize, policy: BoundaryPolicy) -> Neighborhood<'_, T> {
+			let radius = radius as isize;
+			Neighborhood { map: self, x: x as isize, y: y as isize, radius, policy, dx: -radius, dy: -radius }
+		}
+
+		/// Builds a new array of the same dimensions by calling `f` with the
+		/// `neighborhood(x, y, radius, policy)` window of every cell,
+		/// writing the result into a fresh output array. Turns what would
+		/// otherwise be four nested loops (x, y, dx, dy) into a single call.
+		pub fn apply_stencil<U: Copy>(
+			&self, radius: usize, policy: BoundaryPolicy, f: impl Fn(Neighborhood<'_, T>) -> U,
+		) -> ZArray2D<U> {
+			let seed = f(self.neighborhood(0, 0, radius, policy));
+			let mut out = ZArray2D::new(self.width, self.height, seed);
+			for ((x, y), _, _) in self.indexed_iter() {
+				out.set(x, y, f(self.neighborhood(x, y, radius, policy))).unwrap();
+			}
+			out
+		}
+	}
+
+	/// Heap-free iterator over a `(2*radius+1)^2` window of cells around a
+	/// center coordinate, produced by `ZArray2D::neighborhood`.
+	pub struct Neighborhood<'a, T> {
+		map: &'a ZArray2D<T>,
+		x: isize,
+		y: isize,
+		radius: isize,
+		policy: BoundaryPolicy,
+		dx: isize,
+		dy: isize,
+	}
+
+	impl<'a, T: Clone> Iterator for Neighborhood<'a, T> {
+		type Item = &'a T;
+		fn next(&mut self) -> Option<Self::Item> {
+			loop {
+				if self.dy > self.radius {
+					return None;
+				}
+				let (dx, dy) = (self.dx, self.dy);
+				self.dx += 1;
+				if self.dx > self.radius {
+					self.dx = -self.radius;
+					self.dy += 1;
+				}
+				let (nx, ny) = (self.x + dx, self.y + dy);
+				match self.policy {
+					BoundaryPolicy::Wrap => return Some(self.map.wrapped_get(nx, ny)),
+					BoundaryPolicy::Clamp => {
+						let cx = nx.clamp(0, self.map.width as isize - 1) as usize;
+						let cy = ny.clamp(0, self.map.height as isize - 1) as usize;
+						return Some(self.map.get(cx, cy).unwrap());
+					}
+					BoundaryPolicy::Skip => {
+						if let Some(v) = self.map.bounded_get(nx, ny) {
+							return Some(v);
+						}
+						// out-of-bounds under Skip: loop again for the next (dx, dy)
+					}
+				}
+			}
+		}
+	}
+
+	const MAGIC_2D: [u8; 4] = *b"ZA2D";
+	const FORMAT_VERSION_2D: u8 = 1;
+	/// width/height of a patch, as a power of two (2^3 = 8), stored in the
+	/// header so a future format revision could widen patches without
+	/// breaking readers of the current one
+	const PATCH_DIM_BITS_2D: u8 = 3;
+
+	/// Fixed, `bytemuck::Pod` header written by `ZArray2D::as_bytes` before
+	/// the raw patch bytes, and read back by `ZArray2DView::from_bytes`.
+	#[repr(C)]
+	#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+	struct Header2D {
+		magic: [u8; 4],
+		version: u8,
+		patch_dim_bits: u8,
+		_reserved: [u8; 2],
+		width: u32,
+		height: u32,
+		pwidth: u32,
+		element_size: u32,
+	}
+
+	impl<T: bytemuck::Pod> ZArray2D<T> {
+		/// Serializes this array into a flat buffer: a fixed `Header2D`
+		/// followed immediately by the patch array in its existing Z-order
+		/// layout, byte-for-byte unchanged. Only available for
+		/// `T: bytemuck::Pod` plain-old-data, since the patch bytes are
+		/// written out as-is with no per-element encoding.
+		///
+		/// The resulting buffer can be written straight to disk and later
+		/// read back with `ZArray2DView::from_bytes` without copying any
+		/// patches onto the heap (e.g. after `mmap`'ing the file).
+		pub fn as_bytes(&self) -> Vec<u8> {
+			let header = Header2D {
+				magic: MAGIC_2D,
+				version: FORMAT_VERSION_2D,
+				patch_dim_bits: PATCH_DIM_BITS_2D,
+				_reserved: [0; 2],
+				width: self.width as u32,
+				height: self.height as u32,
+				pwidth: self.pwidth as u32,
+				element_size: std::mem::size_of::<T>() as u32,
+			};
+			let mut out = Vec::with_capacity(
+				std::mem::size_of::<Header2D>() + self.patches.len() * std::mem::size_of::<Patch<T>>());
+			out.extend_from_slice(bytemuck::bytes_of(&header));
+			out.extend_from_slice(bytemuck::cast_slice(&self.patches));
+			out
+		}
+	}
+
+	/// Borrowed, read-only view over a `ZArray2D` serialized by `as_bytes`.
+	/// Reads patches directly out of `bytes` (e.g. a memory-mapped file)
+	/// without copying them onto the heap; only `T: bytemuck::Pod`
+	/// plain-old-data is supported, matching `as_bytes`.
+	pub struct ZArray2DView<'a, T> {
+		width: usize,
+		height: usize,
+		pwidth: usize,
+		patches: &'a [Patch<T>],
+	}
+
+	impl<'a, T: bytemuck::Pod> ZArray2DView<'a, T> {
+		/// Parses the header out of `bytes` and borrows the remaining bytes
+		/// as the patch array, with no copying.
+		/// # Errors
+		/// Returns `FormatError` if `bytes` is too short, doesn't start with
+		/// the expected magic/version, or was written with a different
+		/// element size than `T`'s.
+		pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, FormatError> {
+			let header_size = std::mem::size_of::<Header2D>();
+			if bytes.len() < header_size {
+				return Err(FormatError::Truncated { expected: header_size, actual: bytes.len() });
+			}
+			let header: Header2D = *bytemuck::from_bytes(&bytes[..header_size]);
+			if header.magic != MAGIC_2D {
+				return Err(FormatError::BadMagic);
+			}
+			if header.version != FORMAT_VERSION_2D {
+				return Err(FormatError::UnsupportedVersion(header.version));
+			}
+			let element_size = std::mem::size_of::<T>();
+			if header.element_size as usize != element_size {
+				return Err(FormatError::ElementSizeMismatch {
+					expected: header.element_size as usize, actual: element_size });
+			}
+			let patch_bytes_len = (header.pwidth as usize) * std::mem::size_of::<Patch<T>>()
+				* (((header.height as usize) >> PATCH_DIM_BITS_2D) + 1);
+			let expected_total = header_size + patch_bytes_len;
+			if bytes.len() < expected_total {
+				return Err(FormatError::Truncated { expected: expected_total, actual: bytes.len() });
+			}
+			let patches: &[Patch<T>] = bytemuck::cast_slice(&bytes[header_size..expected_total]);
+			Ok(ZArray2DView {
+				width: header.width as usize,
+				height: header.height as usize,
+				pwidth: header.pwidth as usize,
+				patches,
+			})
+		}
+
+		/// Gets the (x, y) size of this view, same as `ZArray2D::dimensions`
+		pub fn dimensions(&self) -> (usize, usize) {
+			(self.width, self.height)
+		}
+
+		/// Gets a value from the view, or a *LookUpError* if the provided
+		/// coordinate is out of bounds. Uses the exact same `patch_index` +
+		/// Z-order lookup as `ZArray2D::get`, just indexing into the
+		/// borrowed byte buffer instead of an owned `Vec`.
+		pub fn get(&self, x: usize, y: usize) -> Result<&T, LookUpError> {
+			if x < self.width && y < self.height {
+				Ok(self.patches[patch_index(x, y, self.pwidth)].get(x, y))
+			} else {
+				Err(LookUpError { coord: vec![x, y], bounds: vec![self.width, self.height] })
+			}
+		}
+	}
+
 	/// Used for Z-index look-up
 	static ZLUT: [u8; 16] = [
 		0b00000000,
@@ -517,6 +1035,70 @@ pub mod z2d {
 		return ((zorder_8bit_to_16bit((x & 0xFF) as u8, (y & 0xFF) as u8) as u32) << 16) | zorder_8bit_to_16bit((x >> 8) as u8, (y >> 8) as u8) as u32
 	}
 
+	/// Inverse of `zorder_8bit_to_16bit`: deinterleaves a 16-bit Z-index back
+	/// into the `(x, y)` coordinate pair that produced it.
+	/// # Parameters
+	/// * **idx** - Z-curve index, as returned by `zorder_8bit_to_16bit`
+	/// # Returns
+	/// The `(x, y)` coordinate pair, each 8 bits wide.
+	pub fn zdecode_16bit_to_8bit(idx: u16) -> (u8, u8) {
+		let (x, y) = zdecode(idx as u64, 8);
+		(x as u8, y as u8)
+	}
+
+	/// Inverse of `zorder_16bit_to_32bit`: deinterleaves a 32-bit Z-index back
+	/// into the `(x, y)` coordinate pair that produced it.
+	/// # Parameters
+	/// * **idx** - Z-curve index, as returned by `zorder_16bit_to_32bit`
+	/// # Returns
+	/// The `(x, y)` coordinate pair, each 16 bits wide.
+	pub fn zdecode_32bit_to_16bit(idx: u32) -> (u16, u16) {
+		// `zorder_16bit_to_32bit` keeps the low bytes' interleave in the upper
+		// 16 bits and the high bytes' in the lower 16, so decode each half apart.
+		let (low_x, low_y) = zdecode((idx >> 16) as u64, 8);
+		let (high_x, high_y) = zdecode((idx & 0xFFFF) as u64, 8);
+		(((high_x << 8) | low_x) as u16, ((high_y << 8) | low_y) as u16)
+	}
+
+	/// Generic Z-index function over coordinates of up to 32 bits each
+	/// (`u64` is used as a common container so callers can pass `u16`/`u32`
+	/// coordinates without a separate function per width). Unlike
+	/// `zorder_4bit_to_8bit` and friends, which are LUT-based and fixed at
+	/// 4/8/16 bits, this interleaves bit-by-bit and so works for any
+	/// `bits` up to 32, letting arrays larger than 256 per axis still get
+	/// contiguous Z-order rather than only within an 8x8 patch.
+	/// # Parameters
+	/// * **x** - x dimension coordinate
+	/// * **y** - y dimension coordinate
+	/// * **bits** - number of low bits of `x`/`y` to interleave
+	/// # Returns
+	/// Z-curve index built from the lowest `bits` bits of `x` and `y`.
+	pub fn zorder(x: u64, y: u64, bits: u32) -> u64 {
+		let mut idx = 0u64;
+		for i in 0..bits {
+			idx |= ((x >> i) & 1) << (2 * i);
+			idx |= ((y >> i) & 1) << (2 * i + 1);
+		}
+		idx
+	}
+
+	/// Inverse of `zorder`: deinterleaves a Z-index built from `bits`-wide
+	/// coordinates back into the `(x, y)` pair that produced it.
+	/// # Parameters
+	/// * **idx** - Z-curve index, as returned by `zorder`
+	/// * **bits** - number of bits each of `x`/`y` was interleaved with
+	/// # Returns
+	/// The `(x, y)` coordinate pair that `zorder(x, y, bits)` would produce.
+	pub fn zdecode(idx: u64, bits: u32) -> (u64, u64) {
+		let mut x = 0u64;
+		let mut y = 0u64;
+		for i in 0..bits {
+			x |= ((idx >> (2 * i)) & 1) << i;
+			y |= ((idx >> (2 * i + 1)) & 1) << i;
+		}
+		(x, y)
+	}
+
 }
 
 /// This module is used for storing 3-dimensional data arrays, and internally uses Z-index arrays
@@ -568,16 +1150,29 @@ pub mod z3d {
 	// Z-order indexing in 2 dimensions
 
 	use std::marker::PhantomData;
-	use super::LookUpError;
+	use std::io::{Read, Write};
+	use std::cmp::Ordering;
+	use std::collections::{BinaryHeap, HashMap};
+	use super::{FormatError, LookUpError};
+	#[cfg(feature = "rayon")]
+	use rayon::prelude::*;
 
 
 	/// Private struct for holding an 8x8x8 data patch
-	#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+	#[repr(C)]
+	#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 	#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 	struct Patch<T>{
 		contents: [T;512]
 	}
 
+	// See z2d::Patch for why this is conditional rather than a blanket derive.
+	impl<T: Copy> Copy for Patch<T> {}
+
+	// SAFETY: see z2d::Patch's identical impls.
+	unsafe impl<T: bytemuck::Pod> bytemuck::Zeroable for Patch<T> {}
+	unsafe impl<T: bytemuck::Pod> bytemuck::Pod for Patch<T> {}
+
 	impl<T> Patch<T> {
 		/// data patch getter
 		/// # Parameters
@@ -617,6 +1212,30 @@ pub mod z3d {
 		return (x >> 3) + pxsize * ((y >> 3) + (pysize * (z >> 3)));
 	}
 
+	/// Axis-aligned bounding box describing a rectangular sub-volume of a
+	/// `ZArray3D`, as `[min, max)` corners (min inclusive, max exclusive),
+	/// mirroring the `(x1,y1,z1,x2,y2,z2)` convention already used by
+	/// `fill`. Used by `ZArray3D::view`/`view_mut`/`fill_region`/`copy_from`
+	/// to give voxel-editing code a first-class sub-volume instead of
+	/// hand-written triple-nested loops.
+	#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+	pub struct Region3D {
+		pub min: (usize, usize, usize),
+		pub max: (usize, usize, usize),
+	}
+
+	impl Region3D {
+		/// Builds a region from its min (inclusive) and max (exclusive) corners.
+		pub fn new(min: (usize, usize, usize), max: (usize, usize, usize)) -> Region3D {
+			Region3D { min, max }
+		}
+
+		/// Gets the (x, y, z) size of this region
+		pub fn dimensions(&self) -> (usize, usize, usize) {
+			(self.max.0 - self.min.0, self.max.1 - self.min.1, self.max.2 - self.min.2)
+		}
+	}
+
 	/// This is primary struct for z-indexed 3D arrays. Create new instances with
 	/// ZArray3D::new(x_size, y_size, z_size, initial_value)
 	#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
@@ -632,14 +1251,14 @@ pub mod z3d {
 		_phantomdata: PhantomData<T>,
 	}
 
-	impl<T> ZArray3D<T> where T: Copy {
+	impl<T> ZArray3D<T> where T: Clone {
 		/// Create a Z-index 3D array of values, initially filled with the provided default value
 		/// # Parameters
 		/// * **xsize** - size of this 3D array in the X dimension
 		/// * **ysize** - size of this 3D array in the Y dimension
 		/// * **zsize** - size of this 3D array in the Z dimension
 		/// * **default_val** - initial fill value (if a struct type, then it must implement the
-		/// Copy trait)
+		/// Clone trait)
 		/// # Returns
 		/// Returns an initialized *ZArray3D* struct filled with *default_val*
 		pub fn new(xsize: usize, ysize: usize, zsize: usize, default_val: T) -> ZArray3D<T>{
@@ -649,12 +1268,49 @@ pub mod z3d {
 			let patch_count = px * py * pz;
 			let mut p = Vec::with_capacity(patch_count);
 			for _ in 0..patch_count{
-				p.push(Patch{contents: [default_val; 512]});
+				p.push(Patch{contents: super::new_cloned_array(&default_val)});
 			}
 			return ZArray3D { xsize, ysize, zsize, pxsize: px, pysize: py,
 				patches: p, _phantomdata: PhantomData};
 		}
 
+		/// Builds a `ZArray3D` from nested slices (`slices[z][y][x]`), as used
+		/// by the `zarray!` literal macro.
+		/// # Parameters
+		/// * **slices** - one 2D slice per Z layer, z=0 first; each layer is
+		/// one slice per row, y=0 first
+		/// # Panics
+		/// Panics if `slices` (or any layer within it) is empty, or if any
+		/// row/layer's length differs from the first's (a ragged literal has
+		/// no well-defined width/height/depth)
+		/// # Returns
+		/// Returns a *ZArray3D* sized from the shape of `slices`
+		pub fn from_slices(slices: &[&[&[T]]]) -> ZArray3D<T> {
+			let zsize = slices.len();
+			assert!(zsize > 0, "zarray!: cannot build a ZArray3D from an empty literal");
+			let ysize = slices[0].len();
+			assert!(ysize > 0, "zarray!: cannot build a ZArray3D from an empty literal");
+			let xsize = slices[0][0].len();
+			for (z, layer) in slices.iter().enumerate() {
+				assert_eq!(layer.len(), ysize,
+					"zarray!: ragged layer {} (expected height {}, got {})", z, ysize, layer.len());
+				for (y, row) in layer.iter().enumerate() {
+					assert_eq!(row.len(), xsize,
+						"zarray!: ragged row {} in layer {} (expected length {}, got {})",
+						y, z, xsize, row.len());
+				}
+			}
+			let mut arr = ZArray3D::new(xsize, ysize, zsize, slices[0][0][0].clone());
+			for (z, layer) in slices.iter().enumerate() {
+				for (y, row) in layer.iter().enumerate() {
+					for (x, val) in row.iter().enumerate() {
+						arr.set(x, y, z, val.clone()).unwrap();
+					}
+				}
+			}
+			arr
+		}
+
 		/// Gets the (x, y, z) size of this 3D array
 		/// # Returns
 		/// Returns a tuple of (width, height, depth) for this 2D array
@@ -869,7 +1525,7 @@ pub mod z3d {
 					new_val: T)
 					-> Result<(), LookUpError> {
 			for y in y1..y2{ for x in x1..x2{ for z in z1..z2{
-				self.set(x, y, z, new_val)?;
+				self.set(x, y, z, new_val.clone())?;
 			} } }
 			Ok(())
 		}
@@ -888,7 +1544,7 @@ pub mod z3d {
 		pub fn wrapped_fill(&mut self, x1: isize, y1: isize, z1: isize,
 							x2: isize, y2: isize, z2: isize, new_val: T) {
 			for y in y1..y2{ for x in x1..x2{ for z in z1..z2{
-				self.wrapped_set(x, y, z, new_val);
+				self.wrapped_set(x, y, z, new_val.clone());
 			} } }
 		}
 
@@ -906,100 +1562,1295 @@ pub mod z3d {
 		pub fn bounded_fill(&mut self, x1: isize, y1: isize, z1: isize,
 							x2: isize, y2: isize, z2: isize, new_val: T) {
 			for y in y1..y2{ for x in x1..x2{ for z in z1..z2{
-				self.bounded_set(x, y, z, new_val);
+				self.bounded_set(x, y, z, new_val.clone());
 			} } }
 		}
-	}
 
-	/// Used for converting 3D coords to linear Z-index
-	static ZLUT: [u16; 16] = [
-		0b0000000000000000,
-		0b0000000000000001,
-		0b0000000000001000,
-		0b0000000000001001,
-		0b0000000001000000,
-		0b0000000001000001,
-		0b0000000001001000,
-		0b0000000001001001,
-		0b0000001000000000,
-		0b0000001000000001,
-		0b0000001000001000,
-		0b0000001000001001,
-		0b0000001001000000,
-		0b0000001001000001,
-		0b0000001001001000,
-		0b0000001001001001
-	];
+		/// Fills a `Region3D` of this array with a given value, or returns a
+		/// *LookUpError* if the region's `max` corner is out of bounds.
+		/// Equivalent to `fill(region.min.0, region.min.1, region.min.2,
+		/// region.max.0, region.max.1, region.max.2, new_val)`.
+		/// # Parameters
+		/// * **region** - the `[min, max)` sub-volume to fill
+		/// * **new_val** - value to store in every cell of *region*
+		/// # Returns
+		/// Returns a Result type that is either empty or a *LookUpError* signalling that
+		/// *region* is out of bounds
+		pub fn fill_region(&mut self, region: Region3D, new_val: T) -> Result<(), LookUpError> {
+			self.fill(region.min.0, region.min.1, region.min.2,
+				region.max.0, region.max.1, region.max.2, new_val)
+		}
+
+		/// Copies every cell of `other` into this array's *region*, or returns
+		/// a *LookUpError* if `region`'s dimensions don't match `other`'s
+		/// dimensions, or if *region* falls outside this array. Useful for
+		/// pasting a prefabricated structure (itself a `ZArray3D`, e.g. one
+		/// built via `Region3DView::to_owned`) into a larger voxel world.
+		/// # Parameters
+		/// * **region** - the `[min, max)` sub-volume of `self` to overwrite
+		/// * **other** - source array; must have the same dimensions as `region`
+		/// # Returns
+		/// Returns a Result type that is either empty or a *LookUpError* signalling a
+		/// dimension mismatch or out-of-bounds *region*
+		pub fn copy_from(&mut self, region: Region3D, other: &ZArray3D<T>) -> Result<(), LookUpError> {
+			let region_dims = region.dimensions();
+			let other_dims = other.dimensions();
+			if region_dims != other_dims {
+				return Err(LookUpError {
+					coord: vec![other_dims.0, other_dims.1, other_dims.2],
+					bounds: vec![region_dims.0, region_dims.1, region_dims.2] });
+			}
+			for z in 0..other_dims.2 { for y in 0..other_dims.1 { for x in 0..other_dims.0 {
+				self.set(region.min.0 + x, region.min.1 + y, region.min.2 + z,
+					other.get(x, y, z).unwrap().clone())?;
+			} } }
+			Ok(())
+		}
 
-	/// General purpose Z-index function to convert a three-dimensional coordinate into a localized
-	/// one-dimensional coordinate
-	/// # Parameters
-	/// * **x** - x dimension coordinate *(ONLY THE LOWER 4 BITS WILL BE USED!)*
-	/// * **y** - y dimension coordinate *(ONLY THE LOWER 4 BITS WILL BE USED!)*
-	/// * **z** - z dimension coordinate *(ONLY THE LOWER 4 BITS WILL BE USED!)*
-	/// # Returns
-	/// Z-curve index for use as an index in a linear array meant to hold 2D data. In other words,
-	/// given the binary numbers X=0b0000xxxx, Y=0b0000yyyy, and Z=0b0000zzzz, then this method
-	/// will return 0b0000zyxzyxzyxzyx.
-	pub fn zorder_4bit_to_12bit(x: u8, y: u8, z: u8) -> u16 {
-		let x_bits = ZLUT[(x & 0x0F) as usize];
-		let y_bits = ZLUT[(y & 0x0F) as usize] << 1;
-		let z_bits = ZLUT[(z & 0x0F) as usize] << 2;
-		return z_bits | y_bits | x_bits;
-	}
-	/// General purpose Z-index function to convert a three-dimensional coordinate into a localized
-	/// one-dimensional coordinate
-	/// # Parameters
-	/// * **x** - x dimension coordinate (8 bit)
-	/// * **y** - y dimension coordinate (8 bit)
-	/// * **z** - z dimension coordinate (8 bit)
-	/// # Returns
-	/// Z-curve index for use as an index in a linear array meant to hold 2D data. In other words,
-	/// given the binary numbers X=0b0000xxxx, Y=0b0000yyyy, and Z=0b0000zzzz, then this method
-	/// will return 0b0000zyxzyxzyxzyx.
-	pub fn zorder_8bit_to_24bit(x:u8, y:u8, z: u8) -> u32 {
-		return ((zorder_4bit_to_12bit(x >> 4, y >> 4, z >> 4) as u32) << 12)
-			| zorder_4bit_to_12bit(x, y, z) as u32
+		/// Borrows the rectangular sub-volume of this array described by
+		/// *region* as a read-only `Region3DView`, or returns a *LookUpError*
+		/// if `region.max` is out of bounds.
+		/// # Parameters
+		/// * **region** - the `[min, max)` sub-volume to view
+		/// # Returns
+		/// Returns a Result type that holds either a `Region3DView` borrowing
+		/// this array, or a *LookUpError* signalling that *region* is out of bounds
+		pub fn view(&self, region: Region3D) -> Result<Region3DView<'_, T>, LookUpError> {
+			if region.max.0 > self.xsize || region.max.1 > self.ysize || region.max.2 > self.zsize {
+				return Err(LookUpError {
+					coord: vec![region.max.0, region.max.1, region.max.2],
+					bounds: vec![self.xsize, self.ysize, self.zsize] });
+			}
+			Ok(Region3DView { map: self, region })
+		}
+
+		/// Borrows the rectangular sub-volume of this array described by
+		/// *region* as a mutable `Region3DViewMut`, or returns a *LookUpError*
+		/// if `region.max` is out of bounds.
+		/// # Parameters
+		/// * **region** - the `[min, max)` sub-volume to view
+		/// # Returns
+		/// Returns a Result type that holds either a `Region3DViewMut` mutably
+		/// borrowing this array, or a *LookUpError* signalling that *region* is out of bounds
+		pub fn view_mut(&mut self, region: Region3D) -> Result<Region3DViewMut<'_, T>, LookUpError> {
+			if region.max.0 > self.xsize || region.max.1 > self.ysize || region.max.2 > self.zsize {
+				return Err(LookUpError {
+					coord: vec![region.max.0, region.max.1, region.max.2],
+					bounds: vec![self.xsize, self.ysize, self.zsize] });
+			}
+			Ok(Region3DViewMut { map: self, region })
+		}
 	}
 
-}
+	/// Read-only borrow of a rectangular sub-volume of a `ZArray3D`, produced
+	/// by `ZArray3D::view`. Coordinates passed to `get` are relative to the
+	/// region, i.e. `(0,0,0)` is `region.min` itself.
+	pub struct Region3DView<'a, T> {
+		map: &'a ZArray3D<T>,
+		region: Region3D,
+	}
 
+	impl<'a, T: Clone> Region3DView<'a, T> {
+		/// Gets the (x, y, z) size of this view, same as `region.dimensions()`
+		pub fn dimensions(&self) -> (usize, usize, usize) {
+			self.region.dimensions()
+		}
 
-#[cfg(test)]
-mod tests {
-	use super::z2d::ZArray2D;
-	use super::z3d::ZArray3D;
-	use rand::{rngs::StdRng, Rng, SeedableRng};
+		/// Gets a value at a region-relative coordinate, or a *LookUpError* if
+		/// it falls outside the view's dimensions.
+		pub fn get(&self, x: usize, y: usize, z: usize) -> Result<&T, LookUpError> {
+			let (w, h, d) = self.dimensions();
+			if x < w && y < h && z < d {
+				Ok(self.map.get_unchecked(
+					self.region.min.0 + x, self.region.min.1 + y, self.region.min.2 + z))
+			} else {
+				Err(LookUpError { coord: vec![x, y, z], bounds: vec![w, h, d] })
+			}
+		}
 
+		/// Extracts this view into a new, standalone `ZArray3D` owning a copy
+		/// of the region's cells, decoupled from the array it was borrowed from.
+		pub fn to_owned(&self) -> ZArray3D<T> {
+			let (w, h, d) = self.dimensions();
+			let mut out = ZArray3D::new(w, h, d, self.get(0, 0, 0).unwrap().clone());
+			for z in 0..d { for y in 0..h { for x in 0..w {
+				out.set(x, y, z, self.get(x, y, z).unwrap().clone()).unwrap();
+			} } }
+			out
+		}
+	}
 
-	fn seed_arrays_u8(w: usize, h: usize) -> (Vec<Vec<u8>>, ZArray2D<u8>){
-		let ref_map: Vec<Vec<u8>> = vec![vec![0u8;w];h];
-		let map = ZArray2D::new(w, h, 0u8);
-		return (ref_map, map);
+	/// Mutable borrow of a rectangular sub-volume of a `ZArray3D`, produced
+	/// by `ZArray3D::view_mut`. Coordinates passed to `get`/`set` are
+	/// relative to the region, i.e. `(0,0,0)` is `region.min` itself.
+	pub struct Region3DViewMut<'a, T> {
+		map: &'a mut ZArray3D<T>,
+		region: Region3D,
 	}
 
-	#[test]
-	fn test_zarray2dmap_get_set(){
-		let h: usize = 601;
-		let w: usize = 809;
-		let (mut ref_map, mut map) = seed_arrays_u8(w, h);
-		let mut prng = StdRng::seed_from_u64(20220331u64);
-		// assert get sizes
-		assert_eq!(map.dimensions().0, w);
-		assert_eq!(map.dimensions().1, h);
-		assert_eq!(map.xsize(), w);
-		assert_eq!(map.width(), w);
-		assert_eq!(map.ysize(), h);
-		assert_eq!(map.height(), h);
-		// set values
-		for y in 0..h {
-			for x in 0..w {
-				let v: u8 = prng.gen();
-				ref_map[y][x] = v;
-				map.set(x, y, v).unwrap();
-			}
+	impl<'a, T: Clone> Region3DViewMut<'a, T> {
+		/// Gets the (x, y, z) size of this view, same as `region.dimensions()`
+		pub fn dimensions(&self) -> (usize, usize, usize) {
+			self.region.dimensions()
 		}
-		// get values
+
+		/// Gets a value at a region-relative coordinate, or a *LookUpError* if
+		/// it falls outside the view's dimensions.
+		pub fn get(&self, x: usize, y: usize, z: usize) -> Result<&T, LookUpError> {
+			let (w, h, d) = self.dimensions();
+			if x < w && y < h && z < d {
+				Ok(self.map.get_unchecked(
+					self.region.min.0 + x, self.region.min.1 + y, self.region.min.2 + z))
+			} else {
+				Err(LookUpError { coord: vec![x, y, z], bounds: vec![w, h, d] })
+			}
+		}
+
+		/// Sets a value at a region-relative coordinate, or returns a
+		/// *LookUpError* if it falls outside the view's dimensions.
+		pub fn set(&mut self, x: usize, y: usize, z: usize, new_val: T) -> Result<(), LookUpError> {
+			let (w, h, d) = self.dimensions();
+			if x < w && y < h && z < d {
+				self.map.set_unchecked(
+					self.region.min.0 + x, self.region.min.1 + y, self.region.min.2 + z, new_val);
+				Ok(())
+			} else {
+				Err(LookUpError { coord: vec![x, y, z], bounds: vec![w, h, d] })
+			}
+		}
+
+		/// Extracts this view into a new, standalone `ZArray3D` owning a copy
+		/// of the region's cells, decoupled from the array it was borrowed from.
+		pub fn to_owned(&self) -> ZArray3D<T> {
+			let (w, h, d) = self.dimensions();
+			let mut out = ZArray3D::new(w, h, d, self.get(0, 0, 0).unwrap().clone());
+			for z in 0..d { for y in 0..h { for x in 0..w {
+				out.set(x, y, z, self.get(x, y, z).unwrap().clone()).unwrap();
+			} } }
+			out
+		}
+	}
+
+	impl<T> ZArray3D<T> {
+		/// Builds a new array of the same dimensions by applying `f` to every
+		/// cell, including patch padding beyond `xsize`/`ysize`/`zsize`
+		/// (which is never observable via `get`). Walks `self`'s patches in
+		/// their existing storage order, so the result stays just as
+		/// cache-local as `self`.
+		pub fn map<U: Copy>(&self, f: impl Fn(&T) -> U) -> ZArray3D<U> {
+			let seed = f(&self.patches[0].contents[0]);
+			let mut out = ZArray3D::new(self.xsize, self.ysize, self.zsize, seed);
+			for (patch, out_patch) in self.patches.iter().zip(out.patches.iter_mut()) {
+				for i in 0..512 {
+					out_patch.contents[i] = f(&patch.contents[i]);
+				}
+			}
+			out
+		}
+
+		/// Iterates `((x, y, z), morton_index, &T)` for every in-bounds cell,
+		/// walking the true patch-local Z-order storage sequence (the same
+		/// index space as `Patch::contents`) rather than a raster sweep, so
+		/// each 8x8x8 patch is read fully in its own on-disk/on-heap order
+		/// before moving to the next. `morton_index` is the flattened index
+		/// `patch_idx * 512 + local_z_order_index` -- the same offset
+		/// `as_bytes`' patch array is laid out at.
+		pub fn indexed_iter(&self) -> impl Iterator<Item = ((usize, usize, usize), usize, &T)> + '_ {
+			let (xsize, ysize, zsize) = (self.xsize, self.ysize, self.zsize);
+			let (pxsize, pysize) = (self.pxsize, self.pysize);
+			self.patches.iter().enumerate().flat_map(move |(patch_idx, patch)| {
+				let px = patch_idx % pxsize;
+				let py = (patch_idx / pxsize) % pysize;
+				let pz = patch_idx / (pxsize * pysize);
+				(0..512usize).filter_map(move |cell_idx| {
+					let (lx, ly, lz) = patch_local_decode(cell_idx as u16);
+					let (x, y, z) = (px * 8 + lx, py * 8 + ly, pz * 8 + lz);
+					if x < xsize && y < ysize && z < zsize {
+						Some(((x, y, z), patch_idx * 512 + cell_idx, &patch.contents[cell_idx]))
+					} else {
+						None
+					}
+				})
+			})
+		}
+
+		/// Iterates `&T` for every in-bounds cell; see `indexed_iter` for
+		/// traversal order.
+		pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+			self.indexed_iter().map(|(_, _, v)| v)
+		}
+
+		/// Iterates `&mut T` for every in-bounds cell, same traversal order
+		/// as `indexed_iter`.
+		pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+			IterMut {
+				patches: self.patches.as_mut_ptr(),
+				patch_count: self.patches.len(),
+				pxsize: self.pxsize,
+				pysize: self.pysize,
+				xsize: self.xsize,
+				ysize: self.ysize,
+				zsize: self.zsize,
+				patch_idx: 0,
+				cell_idx: 0,
+				_marker: PhantomData,
+			}
+		}
+
+		/// Iterates `((x, y, z), &T)` for every in-bounds cell in naive
+		/// raster order (x innermost, then y, then z), the opposite of
+		/// `indexed_iter`'s cache-coherent Z-order tile traversal. Useful
+		/// when a consumer needs raster order for its own sake (writing
+		/// rows out to an image, a text dump, etc.) but doesn't care about
+		/// read locality -- for cache-friendly folds/scans, prefer
+		/// `indexed_iter`/`iter`, which walk memory in the order it's
+		/// actually laid out.
+		pub fn cells(&self) -> impl Iterator<Item = ((usize, usize, usize), &T)> + '_ {
+			let (xsize, ysize, zsize) = (self.xsize, self.ysize, self.zsize);
+			(0..zsize).flat_map(move |z| (0..ysize).flat_map(move |y| (0..xsize).map(move |x| {
+				((x, y, z), self.get_unchecked(x, y, z))
+			})))
+		}
+
+		/// Returns the up-to-six face-adjacent (±x, ±y, ±z) neighbors of
+		/// `coord` that are in-bounds, skipping any that fall outside the
+		/// array -- so a corner cell yields 3 neighbors, an edge cell 4 or
+		/// 5, and an interior cell all 6.
+		pub fn neighbors6(&self, coord: (usize, usize, usize)) -> Vec<&T> {
+			let (x, y, z) = (coord.0 as isize, coord.1 as isize, coord.2 as isize);
+			[(x-1, y, z), (x+1, y, z), (x, y-1, z), (x, y+1, z), (x, y, z-1), (x, y, z+1)]
+				.into_iter()
+				.filter_map(|(nx, ny, nz)| self.bounded_get(nx, ny, nz))
+				.collect()
+		}
+
+		/// Finds the least-cost 6-connected path from `start` to `goal`,
+		/// treating each cell's value as a movement cost via `cost_fn`.
+		/// Equivalent to `astar` with a heuristic of zero (i.e. plain
+		/// Dijkstra); see `astar` for the full contract.
+		pub fn dijkstra(
+			&self, start: (usize, usize, usize), goal: (usize, usize, usize),
+			cost_fn: impl Fn((usize, usize, usize), &T) -> Option<f64>,
+		) -> Option<(Vec<(usize, usize, usize)>, f64)> {
+			self.astar(start, goal, cost_fn, |_| 0.0)
+		}
+
+		/// Finds the least-cost 6-connected path from `start` to `goal`
+		/// with an A* frontier guided by `heuristic`, treating each cell's
+		/// value as a movement cost via `cost_fn`. Moves are gathered via
+		/// `bounded_get`, so a move off the edge of the array is simply
+		/// pruned from the frontier rather than treated as an error.
+		/// # Parameters
+		/// * **start** / **goal** - coordinates to route between
+		/// * **cost_fn** - maps `(coord, &T)` to the cost of entering that
+		/// cell, or `None` if the cell is impassable (e.g. rock above a
+		/// hardness threshold)
+		/// * **heuristic** - estimated remaining cost from a coordinate to
+		/// `goal`; must be admissible (never overestimate the true
+		/// remaining cost, e.g. Manhattan distance to `goal` times the
+		/// minimum possible `cost_fn` value) for the result to be a true
+		/// least-cost path rather than merely *a* path
+		/// # Returns
+		/// `Some((path, total_cost))` with `path` running from `start` to
+		/// `goal` inclusive, or `None` if no path exists -- including when
+		/// `start` or `goal` is out of bounds or impassable. `start == goal`
+		/// returns `Some((vec![start], 0.0))`.
+		pub fn astar(
+			&self, start: (usize, usize, usize), goal: (usize, usize, usize),
+			cost_fn: impl Fn((usize, usize, usize), &T) -> Option<f64>,
+			heuristic: impl Fn((usize, usize, usize)) -> f64,
+		) -> Option<(Vec<(usize, usize, usize)>, f64)> {
+			let start_val = self.get(start.0, start.1, start.2).ok()?;
+			if cost_fn(start, start_val).is_none() {
+				return None;
+			}
+			let goal_val = self.get(goal.0, goal.1, goal.2).ok()?;
+			if cost_fn(goal, goal_val).is_none() {
+				return None;
+			}
+			if start == goal {
+				return Some((vec![start], 0.0));
+			}
+
+			let mut best_cost: HashMap<(usize, usize, usize), f64> = HashMap::new();
+			let mut came_from: HashMap<(usize, usize, usize), (usize, usize, usize)> = HashMap::new();
+			let mut frontier: BinaryHeap<FrontierNode> = BinaryHeap::new();
+
+			best_cost.insert(start, 0.0);
+			frontier.push(FrontierNode { f_score: heuristic(start), coord: start });
+
+			while let Some(FrontierNode { coord, .. }) = frontier.pop() {
+				if coord == goal {
+					let mut path = vec![coord];
+					let mut cur = coord;
+					while let Some(&prev) = came_from.get(&cur) {
+						path.push(prev);
+						cur = prev;
+					}
+					path.reverse();
+					return Some((path, best_cost[&goal]));
+				}
+
+				let g = best_cost[&coord];
+				let (x, y, z) = (coord.0 as isize, coord.1 as isize, coord.2 as isize);
+				for (nx, ny, nz) in [
+					(x - 1, y, z), (x + 1, y, z),
+					(x, y - 1, z), (x, y + 1, z),
+					(x, y, z - 1), (x, y, z + 1),
+				] {
+					if nx < 0 || ny < 0 || nz < 0 {
+						continue;
+					}
+					let ncoord = (nx as usize, ny as usize, nz as usize);
+					let nval = match self.bounded_get(nx, ny, nz) {
+						Some(v) => v,
+						None => continue,
+					};
+					let step_cost = match cost_fn(ncoord, nval) {
+						Some(c) => c,
+						None => continue,
+					};
+					let tentative = g + step_cost;
+					if best_cost.get(&ncoord).map_or(true, |&c| tentative < c) {
+						best_cost.insert(ncoord, tentative);
+						came_from.insert(ncoord, coord);
+						frontier.push(FrontierNode { f_score: tentative + heuristic(ncoord), coord: ncoord });
+					}
+				}
+			}
+			None
+		}
+	}
+
+	/// Frontier entry for `ZArray3D::dijkstra`/`astar`'s binary-heap
+	/// search, ordered by ascending `f_score` so the lowest-cost node is
+	/// always popped next; ties are broken on `coord` so the search (and
+	/// thus the returned path) is reproducible across runs regardless of
+	/// insertion order.
+	#[derive(Copy, Clone, Debug, PartialEq)]
+	struct FrontierNode {
+		f_score: f64,
+		coord: (usize, usize, usize),
+	}
+
+	impl Eq for FrontierNode {}
+
+	impl Ord for FrontierNode {
+		fn cmp(&self, other: &Self) -> Ordering {
+			// BinaryHeap is a max-heap, so reverse the natural f_score
+			// order (lowest cost should pop first) and likewise reverse
+			// the coordinate tie-break for determinism.
+			other.f_score.partial_cmp(&self.f_score).unwrap_or(Ordering::Equal)
+				.then_with(|| other.coord.cmp(&self.coord))
+		}
+	}
+
+	impl PartialOrd for FrontierNode {
+		fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+			Some(self.cmp(other))
+		}
+	}
+
+	/// Drives N iterations of a user-supplied update rule over a
+	/// `ZArray3D<T>`, generalizing the drip-erosion loop that used to be
+	/// hand-rolled inside a test. Supports two rule styles:
+	/// - **in-place**, via `run_in_place`: the rule mutates the map
+	/// directly and sees its own writes within the same pass (erosion-style,
+	/// where a falling drip's effect on one cell is visible to the next
+	/// cell it drips into)
+	/// - **synchronous**, via `run_synchronous`: every cell's next value is
+	/// computed by reading the *previous* pass's snapshot, and nothing is
+	/// written until the whole pass has been computed (double-buffered
+	/// under the hood). Mandatory for rules -- Conway-style automata,
+	/// diffusion -- where a cell's neighbors must all be read from the same
+	/// generation.
+	pub struct Simulation<T> {
+		map: ZArray3D<T>,
+	}
+
+	impl<T> Simulation<T> {
+		/// Wraps `map` so `run_in_place`/`run_synchronous` can drive
+		/// iterations over it.
+		pub fn new(map: ZArray3D<T>) -> Simulation<T> {
+			Simulation { map }
+		}
+
+		/// Unwraps back into the underlying `ZArray3D`.
+		pub fn into_inner(self) -> ZArray3D<T> {
+			self.map
+		}
+
+		/// Borrows the underlying array, e.g. to snapshot it from an
+		/// `on_iteration` callback.
+		pub fn map(&self) -> &ZArray3D<T> {
+			&self.map
+		}
+
+		/// Runs up to `iterations` passes of `rule` against the map in
+		/// place: `rule` is free to call `bounded_get`/`bounded_set` (or
+		/// any other mutator) on the map and see its own writes from
+		/// earlier in the same pass, the way a falling drip of water sees
+		/// the soil it already eroded on its way down. After each pass,
+		/// `on_iteration(iteration, &map)` runs; returning `false` stops
+		/// the run early.
+		pub fn run_in_place(
+			&mut self, iterations: usize,
+			mut rule: impl FnMut(&mut ZArray3D<T>),
+			mut on_iteration: impl FnMut(usize, &ZArray3D<T>) -> bool,
+		) {
+			for i in 0..iterations {
+				rule(&mut self.map);
+				if !on_iteration(i, &self.map) {
+					break;
+				}
+			}
+		}
+	}
+
+	impl<T: Clone + PartialEq> Simulation<T> {
+		/// Runs up to `iterations` passes of `rule` synchronously: `rule`
+		/// is given `(coord, &prev)` where `prev` is the *previous* pass's
+		/// snapshot, untouched by this pass's writes, and returns the
+		/// cell's next value. Every cell's next value is written into a
+		/// second buffer and the two are swapped only once the whole pass
+		/// has been computed, so a rule reading a neighbor never sees a
+		/// value this same pass already wrote.
+		///
+		/// After each pass, `on_iteration(iteration, &map, changed)` runs,
+		/// where `changed` is whether any cell's value differed from the
+		/// previous pass -- letting a caller early-stop once the field has
+		/// stabilized (e.g. a Conway board gone static). Returning `false`
+		/// from `on_iteration` stops the run early.
+		pub fn run_synchronous(
+			&mut self, iterations: usize,
+			rule: impl Fn((usize, usize, usize), &ZArray3D<T>) -> T,
+			mut on_iteration: impl FnMut(usize, &ZArray3D<T>, bool) -> bool,
+		) {
+			let (xsize, ysize, zsize) = self.map.dimensions();
+			let seed = self.map.get_unchecked(0, 0, 0).clone();
+			let mut next = ZArray3D::new(xsize, ysize, zsize, seed);
+			for i in 0..iterations {
+				let mut changed = false;
+				for ((x, y, z), _, v) in self.map.indexed_iter() {
+					let new_val = rule((x, y, z), &self.map);
+					if new_val != *v {
+						changed = true;
+					}
+					next.set_unchecked(x, y, z, new_val);
+				}
+				std::mem::swap(&mut self.map, &mut next);
+				if !on_iteration(i, &self.map, changed) {
+					break;
+				}
+			}
+		}
+	}
+
+	/// Built-in `run_in_place` rule: drips `drip_power` of erosive force
+	/// down each `(x, y)` column (z ascending). Each cell no harder than the
+	/// remaining drip is worn to 0 and its hardness subtracted from the
+	/// drip; the first harder cell absorbs what is left and stops it --
+	/// the same rule `test_erosion_sim` used to
+	/// hand-roll. Out-of-bounds z (below the array) reads as hardness 100,
+	/// an effective floor the drip can't erode past.
+	pub fn gravity_erosion(map: &mut ZArray3D<f32>, drip_power: f32) {
+		let (xsize, ysize, _) = map.dimensions();
+		for x in 0..xsize { for y in 0..ysize {
+			let mut drip = drip_power;
+			let mut z = 0isize;
+			while drip > 0.0 {
+				let h = *map.bounded_get(x as isize, y as isize, z).unwrap_or(&100.0);
+				if h > drip {
+					map.bounded_set(x as isize, y as isize, z, h - drip);
+					drip = 0.0;
+				} else {
+					map.bounded_set(x as isize, y as isize, z, 0.0);
+					drip -= h;
+				}
+				z += 1;
+			}
+		} }
+	}
+
+	/// Built-in `run_synchronous` rule: a simple falling-sand/flood settle
+	/// step. A cell holding `empty_val` becomes `fill_val` if the cell
+	/// above it (lower z) holds `fill_val`; a cell holding `fill_val`
+	/// becomes `empty_val` if the cell below it (higher z) is in bounds
+	/// and holds `empty_val`. Any other cell keeps its value. Driving this
+	/// with `run_synchronous` settles a field of `fill_val` particles
+	/// straight down, one cell per pass, the way loose sand or water falls
+	/// through open space.
+	pub fn flood_settle<T: Clone + PartialEq>(
+		empty_val: T, fill_val: T,
+	) -> impl Fn((usize, usize, usize), &ZArray3D<T>) -> T {
+		move |(x, y, z), prev| {
+			let current = prev.get_unchecked(x, y, z).clone();
+			if current == empty_val {
+				if z > 0 && *prev.get_unchecked(x, y, z - 1) == fill_val {
+					return fill_val.clone();
+				}
+			} else if current == fill_val {
+				if let Some(below) = prev.bounded_get(x as isize, y as isize, z as isize + 1) {
+					if *below == empty_val {
+						return empty_val.clone();
+					}
+				}
+			}
+			current
+		}
+	}
+
+	/// Parallel patch-level operations, gated behind the optional `rayon`
+	/// feature. Each `Patch` is an independent, contiguous `[T; 512]` and
+	/// `patch_index` partitions space disjointly, so splitting work across
+	/// `patches` with `par_iter`/`par_iter_mut` is embarrassingly parallel
+	/// while still preserving the cache-friendly Z-order layout within each
+	/// patch.
+	#[cfg(feature = "rayon")]
+	impl<T: Send + Sync> ZArray3D<T> {
+		/// Parallel version of `iter_mut`/`indexed_iter`: applies `f` to
+		/// every in-bounds cell's `((x, y, z), &mut T)`, splitting the
+		/// `patches` vector across rayon's thread pool with `par_iter_mut`
+		/// and reconstructing global coordinates from each patch's index and
+		/// its local Z-order offset, same as `indexed_iter`.
+		pub fn par_for_each_mut(&mut self, f: impl Fn((usize, usize, usize), &mut T) + Sync + Send) {
+			let (xsize, ysize, zsize) = (self.xsize, self.ysize, self.zsize);
+			let (pxsize, pysize) = (self.pxsize, self.pysize);
+			self.patches.par_iter_mut().enumerate().for_each(|(patch_idx, patch)| {
+				let px = patch_idx % pxsize;
+				let py = (patch_idx / pxsize) % pysize;
+				let pz = patch_idx / (pxsize * pysize);
+				for cell_idx in 0..512usize {
+					let (lx, ly, lz) = patch_local_decode(cell_idx as u16);
+					let (x, y, z) = (px * 8 + lx, py * 8 + ly, pz * 8 + lz);
+					if x < xsize && y < ysize && z < zsize {
+						f((x, y, z), &mut patch.contents[cell_idx]);
+					}
+				}
+			});
+		}
+
+		/// Parallel version of `map`: builds a new array of the same
+		/// dimensions by applying `f` to every cell (including patch padding,
+		/// same as `map`), splitting `self`'s and the output's `patches`
+		/// vectors across rayon's thread pool in lock-step with `par_iter`/
+		/// `par_iter_mut`.
+		pub fn par_map<U: Copy + Send>(&self, f: impl Fn(&T) -> U + Sync + Send) -> ZArray3D<U> {
+			let seed = f(&self.patches[0].contents[0]);
+			let mut out = ZArray3D::new(self.xsize, self.ysize, self.zsize, seed);
+			self.patches.par_iter().zip(out.patches.par_iter_mut()).for_each(|(patch, out_patch)| {
+				for i in 0..512 {
+					out_patch.contents[i] = f(&patch.contents[i]);
+				}
+			});
+			out
+		}
+
+		/// Parallel version of `fill_region`: fills `region` with `new_val`,
+		/// via `par_for_each_mut` so each patch touching `region` is written
+		/// on its own rayon thread.
+		pub fn par_fill_region(&mut self, region: Region3D, new_val: T) where T: Clone {
+			self.par_for_each_mut(|(x, y, z), v| {
+				if x >= region.min.0 && x < region.max.0
+					&& y >= region.min.1 && y < region.max.1
+					&& z >= region.min.2 && z < region.max.2 {
+					*v = new_val.clone();
+				}
+			});
+		}
+	}
+
+	/// Deinterleaves a 9-bit patch-local Z-order index into `(x, y, z)`, each
+	/// in `0..8`. Thin wrapper over `zdecode` restricted to 3-bit
+	/// coordinates, the inverse of the encoding `zorder_4bit_to_12bit`
+	/// computes over the same range (see `Patch::get`/`set`).
+	fn patch_local_decode(idx: u16) -> (usize, usize, usize) {
+		let (x, y, z) = zdecode(idx as u64, 3);
+		(x as usize, y as usize, z as usize)
+	}
+
+	/// Mutable, cache-friendly patch-then-Z-order iterator produced by
+	/// `ZArray3D::iter_mut`.
+	pub struct IterMut<'a, T> {
+		patches: *mut Patch<T>,
+		patch_count: usize,
+		pxsize: usize,
+		pysize: usize,
+		xsize: usize,
+		ysize: usize,
+		zsize: usize,
+		patch_idx: usize,
+		cell_idx: usize,
+		_marker: PhantomData<&'a mut T>,
+	}
+
+	impl<'a, T> Iterator for IterMut<'a, T> {
+		type Item = &'a mut T;
+		fn next(&mut self) -> Option<Self::Item> {
+			loop {
+				if self.patch_idx >= self.patch_count {
+					return None;
+				}
+				if self.cell_idx >= 512 {
+					self.patch_idx += 1;
+					self.cell_idx = 0;
+					continue;
+				}
+				let cell_idx = self.cell_idx;
+				self.cell_idx += 1;
+				let (lx, ly, lz) = patch_local_decode(cell_idx as u16);
+				let px = self.patch_idx % self.pxsize;
+				let py = (self.patch_idx / self.pxsize) % self.pysize;
+				let pz = self.patch_idx / (self.pxsize * self.pysize);
+				let (x, y, z) = (px * 8 + lx, py * 8 + ly, pz * 8 + lz);
+				if x < self.xsize && y < self.ysize && z < self.zsize {
+					// SAFETY: each (patch_idx, cell_idx) pair is visited
+					// exactly once over this iterator's lifetime, so the
+					// mutable reference handed out here never aliases one
+					// handed out by a previous or future call to `next`.
+					unsafe {
+						let patch = &mut *self.patches.add(self.patch_idx);
+						return Some(&mut patch.contents[cell_idx]);
+					}
+				}
+			}
+		}
+	}
+
+	/// Starts a `Zip3D` co-iteration, mirroring `ndarray`'s
+	/// `Zip::from(a).and(b).for_each(...)`.
+	pub struct Zip3D<'a, T> {
+		first: &'a ZArray3D<T>,
+	}
+
+	impl<'a, T> Zip3D<'a, T> {
+		pub fn from(first: &'a ZArray3D<T>) -> Self {
+			Zip3D { first }
+		}
+
+		/// Pairs a second, equally-sized array to co-iterate with the first.
+		/// # Panics
+		/// Panics if `second`'s dimensions differ from the first array's.
+		pub fn and<U>(self, second: &'a ZArray3D<U>) -> ZipWith3D<'a, T, U> {
+			assert_eq!(self.first.dimensions(), second.dimensions(),
+				"Zip3D: arrays must have equal dimensions");
+			ZipWith3D { first: self.first, second }
+		}
+	}
+
+	/// Co-iterates two equally-sized `ZArray3D`s in the same patch-then-Z-order
+	/// traversal as `indexed_iter`, with no per-cell bounds checks against
+	/// each individual array (both are walked by the same patch/cell index
+	/// pair, validated up front by `Zip3D::and`).
+	pub struct ZipWith3D<'a, T, U> {
+		first: &'a ZArray3D<T>,
+		second: &'a ZArray3D<U>,
+	}
+
+	impl<'a, T, U> ZipWith3D<'a, T, U> {
+		pub fn for_each(self, mut f: impl FnMut((usize, usize, usize), &T, &U)) {
+			let (xsize, ysize, zsize) = (self.first.xsize, self.first.ysize, self.first.zsize);
+			let (pxsize, pysize) = (self.first.pxsize, self.first.pysize);
+			for (patch_idx, (pa, pb)) in self.first.patches.iter().zip(self.second.patches.iter()).enumerate() {
+				let px = patch_idx % pxsize;
+				let py = (patch_idx / pxsize) % pysize;
+				let pz = patch_idx / (pxsize * pysize);
+				for cell_idx in 0..512usize {
+					let (lx, ly, lz) = patch_local_decode(cell_idx as u16);
+					let (x, y, z) = (px * 8 + lx, py * 8 + ly, pz * 8 + lz);
+					if x < xsize && y < ysize && z < zsize {
+						f((x, y, z), &pa.contents[cell_idx], &pb.contents[cell_idx]);
+					}
+				}
+			}
+		}
+	}
+
+	impl<T> ZArray3D<T> where T: Clone {
+		/// Returns a heap-free iterator over the `(2*radius+1)^3` cells
+		/// centered on `(x, y, z)`, handling out-of-bounds window cells
+		/// according to `policy`. Centralizes the `dx`/`dy`/`dz` triple loop
+		/// that blur and cellular-automata code would otherwise hand-roll
+		/// with `bounded_get`/`wrapped_get`.
+		pub fn neighborhood(&self, x: usize, y: usize, z: usize, radius: usize, policy: BoundaryPolicy) -> Neighborhood<'_, T> {
+			let radius = radius as isize;
+			Neighborhood {
+				map: self, x: x as isize, y: y as isize, z: z as isize, radius, policy,
+				dx: -radius, dy: -radius, dz: -radius,
+			}
+		}
+
+		/// Builds a new array of the same dimensions by calling `f` with the
+		/// `neighborhood(x, y, z, radius, policy)` window of every cell,
+		/// writing the result into a fresh output array. Turns what would
+		/// otherwise be six nested loops (x, y, z, dx, dy, dz) into a single
+		/// call.
+		pub fn apply_stencil<U: Copy>(
+			&self, radius: usize, policy: BoundaryPolicy, f: impl Fn(Neighborhood<'_, T>) -> U,
+		) -> ZArray3D<U> {
+			let seed = f(self.neighborhood(0, 0, 0, radius, policy));
+			let mut out = ZArray3D::new(self.xsize, self.ysize, self.zsize, seed);
+			for ((x, y, z), _, _) in self.indexed_iter() {
+				out.set(x, y, z, f(self.neighborhood(x, y, z, radius, policy))).unwrap();
+			}
+			out
+		}
+
+		/// In-place variant of `apply_stencil` for time-stepping loops that
+		/// ping-pong two fields (erosion, smoothing, diffusion): instead of
+		/// allocating a fresh `ZArray3D<U>` on every call, `advance` writes
+		/// into the caller-supplied `dst` (which must share `self`'s
+		/// dimensions), so the caller can swap `self`/`dst` each iteration
+		/// and never allocate a new array. `f` is given the neighborhood as a
+		/// flat `&[T]` slice (in `neighborhood`'s `dx`/`dy`/`dz` scan order)
+		/// rather than an iterator, matching the shape most kernel/weight
+		/// closures expect.
+		/// # Parameters
+		/// * **dst** - destination array; must have the same dimensions as `self`
+		/// * **radius** - neighborhood radius, same meaning as `neighborhood`/`apply_stencil`
+		/// * **policy** - how to handle a window cell outside `self`'s bounds
+		/// * **work** - reusable scratch buffer for the neighborhood window;
+		/// pass `None` to allocate one for this call, or a buffer from a
+		/// previous call to avoid allocating at all
+		/// * **f** - kernel applied to each cell's neighborhood window
+		/// # Panics
+		/// Panics if `dst.dimensions() != self.dimensions()`
+		pub fn advance<U: Copy>(
+			&self, dst: &mut ZArray3D<U>, radius: usize, policy: BoundaryPolicy,
+			work: Option<&mut WorkBuffers<T>>, f: impl Fn(&[T]) -> U,
+		) {
+			assert_eq!(self.dimensions(), dst.dimensions(),
+				"advance: dst must have the same dimensions as self");
+			let mut owned_work;
+			let work = match work {
+				Some(w) => w,
+				None => { owned_work = WorkBuffers::new(); &mut owned_work }
+			};
+			for ((x, y, z), _, _) in self.indexed_iter() {
+				work.window.clear();
+				work.window.extend(self.neighborhood(x, y, z, radius, policy).cloned());
+				dst.set_unchecked(x, y, z, f(&work.window));
+			}
+		}
+
+		/// Iterates `((x, y, z), window)` over every *interior* cell -- one
+		/// whose full `(2*radius+1)^3` cubic neighborhood is in-bounds -- in
+		/// the same cache-coherent Z-order tile traversal as `indexed_iter`.
+		/// `window` holds the neighborhood in `dz`, `dy`, `dx` scan order
+		/// (same order `neighborhood` walks). Cells along the array's edge
+		/// are skipped entirely rather than clamped or wrapped, since their
+		/// window would need out-of-bounds data; use `neighborhood` with a
+		/// `BoundaryPolicy` if you need those too.
+		///
+		/// Because Z-order patches aren't contiguous across patch
+		/// boundaries, the window can't be handed out as a true zero-copy
+		/// slice the way a raster array's row could be -- it's materialized
+		/// into a fresh `Vec` per cell, the same tradeoff `advance`'s
+		/// `WorkBuffers` scratch buffer makes.
+		pub fn windows(&self, radius: usize) -> impl Iterator<Item = ((usize, usize, usize), Vec<T>)> + '_ {
+			let r = radius as isize;
+			self.indexed_iter().filter_map(move |((x, y, z), _, _)| {
+				let (xi, yi, zi) = (x as isize, y as isize, z as isize);
+				if xi - r < 0 || yi - r < 0 || zi - r < 0
+					|| xi + r >= self.xsize as isize
+					|| yi + r >= self.ysize as isize
+					|| zi + r >= self.zsize as isize {
+					return None;
+				}
+				let mut window = Vec::with_capacity((2 * radius + 1).pow(3));
+				for dz in -r..=r { for dy in -r..=r { for dx in -r..=r {
+					window.push(self.get_unchecked(
+						(xi + dx) as usize, (yi + dy) as usize, (zi + dz) as usize).clone());
+				} } }
+				Some(((x, y, z), window))
+			})
+		}
+	}
+
+	/// Reusable scratch space for `ZArray3D::advance`, so repeated calls
+	/// across a time-stepping loop allocate nothing after the first. Holds
+	/// a single buffer sized for one neighborhood window, cleared and
+	/// refilled on every call.
+	pub struct WorkBuffers<T> {
+		window: Vec<T>,
+	}
+
+	impl<T> WorkBuffers<T> {
+		/// Builds an empty scratch buffer; its backing `Vec` grows to fit
+		/// the neighborhood window on the first `advance` call and is
+		/// reused (never reallocated smaller) on every call after that.
+		pub fn new() -> WorkBuffers<T> {
+			WorkBuffers { window: Vec::new() }
+		}
+	}
+
+	impl<T> Default for WorkBuffers<T> {
+		fn default() -> Self {
+			WorkBuffers::new()
+		}
+	}
+
+	/// Heap-free iterator over a `(2*radius+1)^3` window of cells around a
+	/// center coordinate, produced by `ZArray3D::neighborhood`.
+	pub struct Neighborhood<'a, T> {
+		map: &'a ZArray3D<T>,
+		x: isize,
+		y: isize,
+		z: isize,
+		radius: isize,
+		policy: BoundaryPolicy,
+		dx: isize,
+		dy: isize,
+		dz: isize,
+	}
+
+	impl<'a, T: Clone> Iterator for Neighborhood<'a, T> {
+		type Item = &'a T;
+		fn next(&mut self) -> Option<Self::Item> {
+			loop {
+				if self.dz > self.radius {
+					return None;
+				}
+				let (dx, dy, dz) = (self.dx, self.dy, self.dz);
+				self.dx += 1;
+				if self.dx > self.radius {
+					self.dx = -self.radius;
+					self.dy += 1;
+				}
+				if self.dy > self.radius {
+					self.dy = -self.radius;
+					self.dz += 1;
+				}
+				let (nx, ny, nz) = (self.x + dx, self.y + dy, self.z + dz);
+				match self.policy {
+					BoundaryPolicy::Wrap => return Some(self.map.wrapped_get(nx, ny, nz)),
+					BoundaryPolicy::Clamp => {
+						let cx = nx.clamp(0, self.map.xsize as isize - 1) as usize;
+						let cy = ny.clamp(0, self.map.ysize as isize - 1) as usize;
+						let cz = nz.clamp(0, self.map.zsize as isize - 1) as usize;
+						return Some(self.map.get(cx, cy, cz).unwrap());
+					}
+					BoundaryPolicy::Skip => {
+						if let Some(v) = self.map.bounded_get(nx, ny, nz) {
+							return Some(v);
+						}
+						// out-of-bounds under Skip: loop again for the next (dx, dy, dz)
+					}
+				}
+			}
+		}
+	}
+
+	const MAGIC_3D: [u8; 4] = *b"ZA3D";
+	const FORMAT_VERSION_3D: u8 = 1;
+	/// width/height/depth of a patch, as a power of two (2^3 = 8), stored in
+	/// the header so a future format revision could widen patches without
+	/// breaking readers of the current one
+	const PATCH_DIM_BITS_3D: u8 = 3;
+
+	/// Fixed, `bytemuck::Pod` header written by `ZArray3D::as_bytes` before
+	/// the raw patch bytes, and read back by `ZArray3DView::from_bytes`.
+	#[repr(C)]
+	#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+	struct Header3D {
+		magic: [u8; 4],
+		version: u8,
+		patch_dim_bits: u8,
+		_reserved: [u8; 2],
+		xsize: u32,
+		ysize: u32,
+		zsize: u32,
+		pxsize: u32,
+		pysize: u32,
+		pzsize: u32,
+		element_size: u32,
+	}
+
+	impl<T: bytemuck::Pod> ZArray3D<T> {
+		/// Serializes this array into a flat buffer: a fixed `Header3D`
+		/// followed immediately by the patch array in its existing Z-order
+		/// layout, byte-for-byte unchanged. Only available for
+		/// `T: bytemuck::Pod` plain-old-data, since the patch bytes are
+		/// written out as-is with no per-element encoding.
+		///
+		/// The resulting buffer can be written straight to disk and later
+		/// read back with `ZArray3DView::from_bytes` without copying any
+		/// patches onto the heap (e.g. after `mmap`'ing the file).
+		pub fn as_bytes(&self) -> Vec<u8> {
+			let header = Header3D {
+				magic: MAGIC_3D,
+				version: FORMAT_VERSION_3D,
+				patch_dim_bits: PATCH_DIM_BITS_3D,
+				_reserved: [0; 2],
+				xsize: self.xsize as u32,
+				ysize: self.ysize as u32,
+				zsize: self.zsize as u32,
+				pxsize: self.pxsize as u32,
+				pysize: self.pysize as u32,
+				pzsize: (self.patches.len() / (self.pxsize * self.pysize)) as u32,
+				element_size: std::mem::size_of::<T>() as u32,
+			};
+			let mut out = Vec::with_capacity(
+				std::mem::size_of::<Header3D>() + self.patches.len() * std::mem::size_of::<Patch<T>>());
+			out.extend_from_slice(bytemuck::bytes_of(&header));
+			out.extend_from_slice(bytemuck::cast_slice(&self.patches));
+			out
+		}
+	}
+
+	/// Byte order used by `ZArray3D::write_to`/`read_from`'s header fields.
+	/// Unlike `as_bytes`/`from_bytes` (which round-trip through raw,
+	/// platform-native bytes for zero-copy mmap reads), this codec records
+	/// an explicit endianness for its header fields. The patch contents
+	/// after the header are not converted and stay in the writing machine's
+	/// native byte order.
+	#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+	pub enum Endianness {
+		Little,
+		Big,
+	}
+
+	/// Error returned by `ZArray3D::read_from`: either an `io::Error` while
+	/// reading from the stream, or a `FormatError` once a full header was
+	/// read but didn't validate.
+	#[derive(Debug)]
+	pub enum CodecError {
+		Io(std::io::Error),
+		Format(FormatError),
+	}
+
+	impl std::fmt::Display for CodecError {
+		fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+			match self {
+				CodecError::Io(e) => write!(f, "Error: {}", e),
+				CodecError::Format(e) => write!(f, "{}", e),
+			}
+		}
+	}
+
+	impl std::error::Error for CodecError {}
+
+	impl From<std::io::Error> for CodecError {
+		fn from(e: std::io::Error) -> Self {
+			CodecError::Io(e)
+		}
+	}
+
+	fn write_u32<W: Write>(writer: &mut W, v: u32, endian: Endianness) -> std::io::Result<()> {
+		match endian {
+			Endianness::Little => writer.write_all(&v.to_le_bytes()),
+			Endianness::Big => writer.write_all(&v.to_be_bytes()),
+		}
+	}
+
+	fn read_u32<R: Read>(reader: &mut R, endian: Endianness) -> std::io::Result<u32> {
+		let mut buf = [0u8; 4];
+		reader.read_exact(&mut buf)?;
+		Ok(match endian {
+			Endianness::Little => u32::from_le_bytes(buf),
+			Endianness::Big => u32::from_be_bytes(buf),
+		})
+	}
+
+	const MAGIC_3D_CODEC: [u8; 4] = *b"ZA3C";
+	const FORMAT_VERSION_3D_CODEC: u8 = 1;
+
+	impl<T: bytemuck::Pod> ZArray3D<T> {
+		/// Writes this array to `writer` as a compact, endian-explicit binary
+		/// format for saving voxel worlds to disk: a small header (magic
+		/// bytes, version, endianness flag, x/y/z sizes, element byte-width)
+		/// followed by the raw patch contents in Z-order. Unlike `as_bytes`,
+		/// this writes the header's size fields in the chosen `endian`, so
+		/// they read back correctly on any machine. The patch contents are
+		/// copied as-is in native byte order, so for elements wider than one
+		/// byte the file reads back correctly only on a machine with the
+		/// writer's byte order.
+		/// # Errors
+		/// Propagates any `io::Error` from writing to `writer`.
+		pub fn write_to<W: Write>(&self, writer: &mut W, endian: Endianness) -> std::io::Result<()> {
+			writer.write_all(&MAGIC_3D_CODEC)?;
+			writer.write_all(&[FORMAT_VERSION_3D_CODEC, endian as u8])?;
+			write_u32(writer, self.xsize as u32, endian)?;
+			write_u32(writer, self.ysize as u32, endian)?;
+			write_u32(writer, self.zsize as u32, endian)?;
+			write_u32(writer, std::mem::size_of::<T>() as u32, endian)?;
+			writer.write_all(bytemuck::cast_slice(&self.patches))?;
+			Ok(())
+		}
+
+		/// Reads a `ZArray3D` back from `reader`, as previously written by
+		/// `write_to`. Validates the header (magic, version, element size)
+		/// before allocating the patch `Vec`, returning a `CodecError` on
+		/// truncated or malformed input rather than panicking.
+		/// # Errors
+		/// Returns `CodecError::Format` if the header is malformed, or
+		/// `CodecError::Io` if `reader` runs out of bytes partway through
+		/// (e.g. a truncated file).
+		pub fn read_from<R: Read>(reader: &mut R) -> Result<ZArray3D<T>, CodecError> {
+			let mut magic = [0u8; 4];
+			reader.read_exact(&mut magic)?;
+			if magic != MAGIC_3D_CODEC {
+				return Err(CodecError::Format(FormatError::BadMagic));
+			}
+			let mut version_and_endian = [0u8; 2];
+			reader.read_exact(&mut version_and_endian)?;
+			if version_and_endian[0] != FORMAT_VERSION_3D_CODEC {
+				return Err(CodecError::Format(FormatError::UnsupportedVersion(version_and_endian[0])));
+			}
+			let endian = match version_and_endian[1] {
+				0 => Endianness::Little,
+				1 => Endianness::Big,
+				other => return Err(CodecError::Format(FormatError::UnsupportedVersion(other))),
+			};
+			let xsize = read_u32(reader, endian)? as usize;
+			let ysize = read_u32(reader, endian)? as usize;
+			let zsize = read_u32(reader, endian)? as usize;
+			let element_size = read_u32(reader, endian)? as usize;
+			if element_size != std::mem::size_of::<T>() {
+				return Err(CodecError::Format(FormatError::ElementSizeMismatch {
+					expected: element_size, actual: std::mem::size_of::<T>() }));
+			}
+			let pxsize = (xsize >> 3) + 1;
+			let pysize = (ysize >> 3) + 1;
+			let pzsize = (zsize >> 3) + 1;
+			let patch_count = pxsize * pysize * pzsize;
+			let mut patch_bytes = vec![0u8; patch_count * std::mem::size_of::<Patch<T>>()];
+			reader.read_exact(&mut patch_bytes)?;
+			let patches: Vec<Patch<T>> = bytemuck::cast_slice(&patch_bytes).to_vec();
+			Ok(ZArray3D { xsize, ysize, zsize, pxsize, pysize, patches, _phantomdata: PhantomData })
+		}
+	}
+
+	/// Borrowed, read-only view over a `ZArray3D` serialized by `as_bytes`.
+	/// Reads patches directly out of `bytes` (e.g. a memory-mapped file)
+	/// without copying them onto the heap; only `T: bytemuck::Pod`
+	/// plain-old-data is supported, matching `as_bytes`.
+	pub struct ZArray3DView<'a, T> {
+		xsize: usize,
+		ysize: usize,
+		zsize: usize,
+		pxsize: usize,
+		pysize: usize,
+		patches: &'a [Patch<T>],
+	}
+
+	impl<'a, T: bytemuck::Pod> ZArray3DView<'a, T> {
+		/// Parses the header out of `bytes` and borrows the remaining bytes
+		/// as the patch array, with no copying.
+		/// # Errors
+		/// Returns `FormatError` if `bytes` is too short, doesn't start with
+		/// the expected magic/version, or was written with a different
+		/// element size than `T`'s.
+		pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, FormatError> {
+			let header_size = std::mem::size_of::<Header3D>();
+			if bytes.len() < header_size {
+				return Err(FormatError::Truncated { expected: header_size, actual: bytes.len() });
+			}
+			let header: Header3D = *bytemuck::from_bytes(&bytes[..header_size]);
+			if header.magic != MAGIC_3D {
+				return Err(FormatError::BadMagic);
+			}
+			if header.version != FORMAT_VERSION_3D {
+				return Err(FormatError::UnsupportedVersion(header.version));
+			}
+			let element_size = std::mem::size_of::<T>();
+			if header.element_size as usize != element_size {
+				return Err(FormatError::ElementSizeMismatch {
+					expected: header.element_size as usize, actual: element_size });
+			}
+			let patch_count = (header.pxsize as usize) * (header.pysize as usize) * (header.pzsize as usize);
+			let patch_bytes_len = patch_count * std::mem::size_of::<Patch<T>>();
+			let expected_total = header_size + patch_bytes_len;
+			if bytes.len() < expected_total {
+				return Err(FormatError::Truncated { expected: expected_total, actual: bytes.len() });
+			}
+			let patches: &[Patch<T>] = bytemuck::cast_slice(&bytes[header_size..expected_total]);
+			Ok(ZArray3DView {
+				xsize: header.xsize as usize,
+				ysize: header.ysize as usize,
+				zsize: header.zsize as usize,
+				pxsize: header.pxsize as usize,
+				pysize: header.pysize as usize,
+				patches,
+			})
+		}
+
+		/// Gets the (x, y, z) size of this view, same as `ZArray3D::dimensions`
+		pub fn dimensions(&self) -> (usize, usize, usize) {
+			(self.xsize, self.ysize, self.zsize)
+		}
+
+		/// Gets a value from the view, or a *LookUpError* if the provided
+		/// coordinate is out of bounds. Uses the exact same `patch_index` +
+		/// Z-order lookup as `ZArray3D::get`, just indexing into the
+		/// borrowed byte buffer instead of an owned `Vec`.
+		pub fn get(&self, x: usize, y: usize, z: usize) -> Result<&T, LookUpError> {
+			if x < self.xsize && y < self.ysize && z < self.zsize {
+				Ok(self.patches[patch_index(x, y, z, self.pxsize, self.pysize)].get(x, y, z))
+			} else {
+				Err(LookUpError {
+					coord: vec![x, y, z], bounds: vec![self.xsize, self.ysize, self.zsize] })
+			}
+		}
+	}
+
+	/// Used for converting 3D coords to linear Z-index
+	static ZLUT: [u16; 16] = [
+		0b0000000000000000,
+		0b0000000000000001,
+		0b0000000000001000,
+		0b0000000000001001,
+		0b0000000001000000,
+		0b0000000001000001,
+		0b0000000001001000,
+		0b0000000001001001,
+		0b0000001000000000,
+		0b0000001000000001,
+		0b0000001000001000,
+		0b0000001000001001,
+		0b0000001001000000,
+		0b0000001001000001,
+		0b0000001001001000,
+		0b0000001001001001
+	];
+
+	/// General purpose Z-index function to convert a three-dimensional coordinate into a localized
+	/// one-dimensional coordinate
+	/// # Parameters
+	/// * **x** - x dimension coordinate *(ONLY THE LOWER 4 BITS WILL BE USED!)*
+	/// * **y** - y dimension coordinate *(ONLY THE LOWER 4 BITS WILL BE USED!)*
+	/// * **z** - z dimension coordinate *(ONLY THE LOWER 4 BITS WILL BE USED!)*
+	/// # Returns
+	/// Z-curve index for use as an index in a linear array meant to hold 2D data. In other words,
+	/// given the binary numbers X=0b0000xxxx, Y=0b0000yyyy, and Z=0b0000zzzz, then this method
+	/// will return 0b0000zyxzyxzyxzyx.
+	pub fn zorder_4bit_to_12bit(x: u8, y: u8, z: u8) -> u16 {
+		let x_bits = ZLUT[(x & 0x0F) as usize];
+		let y_bits = ZLUT[(y & 0x0F) as usize] << 1;
+		let z_bits = ZLUT[(z & 0x0F) as usize] << 2;
+		return z_bits | y_bits | x_bits;
+	}
+	/// General purpose Z-index function to convert a three-dimensional coordinate into a localized
+	/// one-dimensional coordinate
+	/// # Parameters
+	/// * **x** - x dimension coordinate (8 bit)
+	/// * **y** - y dimension coordinate (8 bit)
+	/// * **z** - z dimension coordinate (8 bit)
+	/// # Returns
+	/// Z-curve index for use as an index in a linear array meant to hold 2D data. In other words,
+	/// given the binary numbers X=0b0000xxxx, Y=0b0000yyyy, and Z=0b0000zzzz, then this method
+	/// will return 0b0000zyxzyxzyxzyx.
+	pub fn zorder_8bit_to_24bit(x:u8, y:u8, z: u8) -> u32 {
+		return ((zorder_4bit_to_12bit(x >> 4, y >> 4, z >> 4) as u32) << 12)
+			| zorder_4bit_to_12bit(x, y, z) as u32
+	}
+
+	/// Inverse of `zorder_4bit_to_12bit`: deinterleaves a 12-bit Z-index back
+	/// into the `(x, y, z)` coordinate triple that produced it.
+	/// # Parameters
+	/// * **idx** - Z-curve index, as returned by `zorder_4bit_to_12bit`
+	/// # Returns
+	/// The `(x, y, z)` coordinate triple, each 4 bits wide.
+	pub fn zdecode_12bit_to_4bit(idx: u16) -> (u8, u8, u8) {
+		let (x, y, z) = zdecode(idx as u64, 4);
+		(x as u8, y as u8, z as u8)
+	}
+
+	/// Inverse of `zorder_8bit_to_24bit`: deinterleaves a 24-bit Z-index back
+	/// into the `(x, y, z)` coordinate triple that produced it.
+	/// # Parameters
+	/// * **idx** - Z-curve index, as returned by `zorder_8bit_to_24bit`
+	/// # Returns
+	/// The `(x, y, z)` coordinate triple, each 8 bits wide.
+	pub fn zdecode_24bit(idx: u32) -> (u8, u8, u8) {
+		let (low_x, low_y, low_z) = zdecode_12bit_to_4bit((idx & 0xFFF) as u16);
+		let (high_x, high_y, high_z) = zdecode_12bit_to_4bit((idx >> 12) as u16);
+		((high_x << 4) | low_x, (high_y << 4) | low_y, (high_z << 4) | low_z)
+	}
+
+	/// Generic Z-index function over coordinates of up to 21 bits each
+	/// (`u64` is used as a common container so callers can pass `u16`/`u32`
+	/// coordinates without a separate function per width). Unlike
+	/// `zorder_4bit_to_12bit` and friends, which are LUT-based and fixed at
+	/// 4/8-bit inputs, this interleaves bit-by-bit and so works for any
+	/// `bits` up to 21 (the widest that still fits a `u64` index), letting
+	/// arrays larger than 256 per axis still get contiguous Z-order instead
+	/// of only within an 8x8x8 patch.
+	/// # Parameters
+	/// * **x** - x dimension coordinate
+	/// * **y** - y dimension coordinate
+	/// * **z** - z dimension coordinate
+	/// * **bits** - number of low bits of `x`/`y`/`z` to interleave
+	/// # Returns
+	/// Z-curve index built from the lowest `bits` bits of `x`, `y`, and `z`.
+	pub fn zorder(x: u64, y: u64, z: u64, bits: u32) -> u64 {
+		let mut idx = 0u64;
+		for i in 0..bits {
+			idx |= ((x >> i) & 1) << (3 * i);
+			idx |= ((y >> i) & 1) << (3 * i + 1);
+			idx |= ((z >> i) & 1) << (3 * i + 2);
+		}
+		idx
+	}
+
+	/// Inverse of `zorder`: deinterleaves a Z-index built from `bits`-wide
+	/// coordinates back into the `(x, y, z)` triple that produced it.
+	/// # Parameters
+	/// * **idx** - Z-curve index, as returned by `zorder`
+	/// * **bits** - number of bits each of `x`/`y`/`z` was interleaved with
+	/// # Returns
+	/// The `(x, y, z)` triple that `zorder(x, y, z, bits)` would produce.
+	pub fn zdecode(idx: u64, bits: u32) -> (u64, u64, u64) {
+		let mut x = 0u64;
+		let mut y = 0u64;
+		let mut z = 0u64;
+		for i in 0..bits {
+			x |= ((idx >> (3 * i)) & 1) << i;
+			y |= ((idx >> (3 * i + 1)) & 1) << i;
+			z |= ((idx >> (3 * i + 2)) & 1) << i;
+		}
+		(x, y, z)
+	}
+
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::z2d::ZArray2D;
+	use super::z3d::ZArray3D;
+	use super::BoundaryPolicy;
+	use rand::{rngs::StdRng, Rng, SeedableRng};
+
+
+	fn seed_arrays_u8(w: usize, h: usize) -> (Vec<Vec<u8>>, ZArray2D<u8>){
+		let ref_map: Vec<Vec<u8>> = vec![vec![0u8;w];h];
+		let map = ZArray2D::new(w, h, 0u8);
+		return (ref_map, map);
+	}
+
+	#[test]
+	fn test_zarray2dmap_get_set(){
+		let h: usize = 601;
+		let w: usize = 809;
+		let (mut ref_map, mut map) = seed_arrays_u8(w, h);
+		let mut prng = StdRng::seed_from_u64(20220331u64);
+		// assert get sizes
+		assert_eq!(map.dimensions().0, w);
+		assert_eq!(map.dimensions().1, h);
+		assert_eq!(map.xsize(), w);
+		assert_eq!(map.width(), w);
+		assert_eq!(map.ysize(), h);
+		assert_eq!(map.height(), h);
+		// set values
+		for y in 0..h {
+			for x in 0..w {
+				let v: u8 = prng.gen();
+				ref_map[y][x] = v;
+				map.set(x, y, v).unwrap();
+			}
+		}
+		// get values
 		for y in 0..h {
 			for x in 0..w {
 				assert_eq!(ref_map[y][x], *map.get(x, y).unwrap());
@@ -1512,4 +3363,663 @@ mod tests {
 			}}
 		}
 	}
+
+	#[test]
+	fn test_simulation_run_in_place_gravity_erosion_matches_hand_rolled_loop(){
+		use super::z3d::{gravity_erosion, Simulation};
+
+		let (width, length, depth) = (20, 20, 10);
+		let soil_hardness = 1f32;
+		let drip_power = 1.5f32;
+		let iterations = 6;
+
+		let mut hand_rolled = ZArray3D::new(width, length, depth, 0f32);
+		hand_rolled.fill(0, 0, 3, width, length, depth, soil_hardness).unwrap();
+		for _ in 0..iterations {
+			for x in 0..width { for y in 0..length {
+				let mut drip = drip_power;
+				let mut z = 0;
+				while drip > 0f32 {
+					let h = *hand_rolled.bounded_get(x as isize, y as isize, z).unwrap_or(&100f32);
+					if h > drip {
+						hand_rolled.bounded_set(x as isize, y as isize, z, h - drip);
+						drip = 0.;
+					} else {
+						hand_rolled.bounded_set(x as isize, y as isize, z, 0.);
+						drip -= h;
+					}
+					z += 1;
+				}
+			} }
+		}
+
+		let mut via_driver = ZArray3D::new(width, length, depth, 0f32);
+		via_driver.fill(0, 0, 3, width, length, depth, soil_hardness).unwrap();
+		let mut sim = Simulation::new(via_driver);
+		sim.run_in_place(iterations, |map| gravity_erosion(map, drip_power), |_, _| true);
+
+		assert_eq!(
+			hand_rolled.iter().collect::<Vec<_>>(),
+			sim.into_inner().iter().collect::<Vec<_>>());
+	}
+
+	#[test]
+	fn test_simulation_run_in_place_early_stop(){
+		use super::z3d::Simulation;
+
+		let mut sim = Simulation::new(ZArray3D::new(2, 2, 2, 0u32));
+		let mut passes = 0;
+		sim.run_in_place(100, |map| {
+			map.iter_mut().for_each(|v| *v += 1);
+		}, |i, _| {
+			passes = i + 1;
+			i < 2 // stop after 3 passes
+		});
+
+		assert_eq!(passes, 3);
+		assert!(sim.map().iter().all(|v| *v == 3));
+	}
+
+	#[test]
+	fn test_simulation_run_synchronous_flood_settle_falls_one_cell_per_pass(){
+		use super::z3d::{flood_settle, Simulation};
+
+		let mut map = ZArray3D::new(1, 1, 5, 0u8);
+		map.set(0, 0, 0, 1).unwrap(); // single grain of "sand" at the top
+		let mut sim = Simulation::new(map);
+
+		let rule = flood_settle(0u8, 1u8);
+		for expected_z in 1..5 {
+			sim.run_synchronous(1, &rule, |_, _, _| true);
+			assert_eq!(*sim.map().get(0, 0, expected_z).unwrap(), 1,
+				"grain should have fallen to z={}", expected_z);
+			assert_eq!(sim.map().iter().filter(|&&v| v == 1).count(), 1,
+				"exactly one grain should exist in the field");
+		}
+	}
+
+	#[test]
+	fn test_simulation_run_synchronous_reports_changed_and_stabilizes(){
+		use super::z3d::{flood_settle, Simulation};
+
+		let mut map = ZArray3D::new(1, 1, 3, 0u8);
+		map.set(0, 0, 0, 1).unwrap();
+		let mut sim = Simulation::new(map);
+
+		let mut changes_seen = Vec::new();
+		sim.run_synchronous(10, flood_settle(0u8, 1u8), |i, _, changed| {
+			changes_seen.push(changed);
+			changed // stop once a pass changes nothing
+		});
+
+		assert_eq!(changes_seen, vec![true, true, false]);
+		assert_eq!(*sim.map().get(0, 0, 2).unwrap(), 1, "grain settles at the bottom of the column");
+	}
+
+	#[test]
+	fn test_zarray2dmap_non_copy_element(){
+		// Regression test: String is Clone but not Copy, so this only
+		// compiles if ZArray2D's bound was relaxed from Copy to Clone.
+		let w = 12;
+		let h = 9;
+		let mut map = ZArray2D::new(w, h, String::from("air"));
+		map.set(3, 4, String::from("stone")).unwrap();
+		map.fill(0, 0, w, 3, String::from("bedrock")).unwrap();
+
+		assert_eq!(map.get(3, 4).unwrap(), "stone");
+		assert_eq!(map.get(0, 0).unwrap(), "bedrock");
+		assert_eq!(map.get(0, 8).unwrap(), "air");
+	}
+
+	#[test]
+	fn test_zarray3dmap_non_copy_element(){
+		let mut map = ZArray3D::new(10, 10, 10, Vec::<u8>::new());
+		map.set(1, 2, 3, vec![1, 2, 3]).unwrap();
+
+		assert_eq!(map.get(1, 2, 3).unwrap(), &vec![1u8, 2, 3]);
+		assert_eq!(map.get(0, 0, 0).unwrap(), &Vec::<u8>::new());
+	}
+
+	#[test]
+	fn test_zarray_macro_2d(){
+		let map = crate::zarray![[1, 2, 3], [4, 5, 6]];
+		assert_eq!(map.dimensions(), (3, 2));
+		assert_eq!(*map.get(0, 0).unwrap(), 1);
+		assert_eq!(*map.get(2, 0).unwrap(), 3);
+		assert_eq!(*map.get(0, 1).unwrap(), 4);
+		assert_eq!(*map.get(2, 1).unwrap(), 6);
+	}
+
+	#[test]
+	fn test_zarray_macro_3d(){
+		let map = crate::zarray![[[1, 2], [3, 4]], [[5, 6], [7, 8]]];
+		assert_eq!(map.dimensions(), (2, 2, 2));
+		assert_eq!(*map.get(0, 0, 0).unwrap(), 1);
+		assert_eq!(*map.get(1, 1, 1).unwrap(), 8);
+	}
+
+	#[test]
+	#[should_panic(expected = "ragged row")]
+	fn test_zarray_macro_ragged_row_panics(){
+		let _ = crate::zarray![[1, 2, 3], [4, 5]];
+	}
+
+	#[test]
+	fn test_zarray2d_bytes_roundtrip(){
+		use super::z2d::ZArray2DView;
+		let mut map = ZArray2D::new(20, 17, 0i32);
+		map.set(3, 4, 42).unwrap();
+		map.set(19, 16, -7).unwrap();
+
+		let bytes = map.as_bytes();
+		let view = ZArray2DView::<i32>::from_bytes(&bytes).unwrap();
+
+		assert_eq!(view.dimensions(), map.dimensions());
+		assert_eq!(*view.get(3, 4).unwrap(), 42);
+		assert_eq!(*view.get(19, 16).unwrap(), -7);
+		assert_eq!(*view.get(0, 0).unwrap(), 0);
+	}
+
+	#[test]
+	fn test_zarray3d_bytes_roundtrip(){
+		use super::z3d::ZArray3DView;
+		let mut map = ZArray3D::new(9, 11, 13, 0u8);
+		map.set(8, 10, 12, 255).unwrap();
+
+		let bytes = map.as_bytes();
+		let view = ZArray3DView::<u8>::from_bytes(&bytes).unwrap();
+
+		assert_eq!(view.dimensions(), map.dimensions());
+		assert_eq!(*view.get(8, 10, 12).unwrap(), 255);
+		assert_eq!(*view.get(0, 0, 0).unwrap(), 0);
+	}
+
+	#[test]
+	fn test_zarray3d_write_to_read_from_roundtrip_little_endian(){
+		use super::z3d::Endianness;
+		let mut map = ZArray3D::new(9, 11, 13, 0u8);
+		map.set(8, 10, 12, 255).unwrap();
+
+		let mut bytes = Vec::new();
+		map.write_to(&mut bytes, Endianness::Little).unwrap();
+		let read_back = ZArray3D::<u8>::read_from(&mut bytes.as_slice()).unwrap();
+
+		assert_eq!(read_back.dimensions(), map.dimensions());
+		assert_eq!(*read_back.get(8, 10, 12).unwrap(), 255);
+		assert_eq!(*read_back.get(0, 0, 0).unwrap(), 0);
+	}
+
+	#[test]
+	fn test_zarray3d_write_to_read_from_roundtrip_big_endian(){
+		use super::z3d::Endianness;
+		let mut map = ZArray3D::new(9, 11, 13, 0u32);
+		map.set(8, 10, 12, 0xdead_beef).unwrap();
+
+		let mut bytes = Vec::new();
+		map.write_to(&mut bytes, Endianness::Big).unwrap();
+		let read_back = ZArray3D::<u32>::read_from(&mut bytes.as_slice()).unwrap();
+
+		assert_eq!(read_back.dimensions(), map.dimensions());
+		assert_eq!(*read_back.get(8, 10, 12).unwrap(), 0xdead_beef);
+	}
+
+	#[test]
+	fn test_zarray3d_read_from_rejects_bad_magic(){
+		use super::z3d::CodecError;
+		use super::FormatError;
+		let map = ZArray3D::new(4, 4, 4, 0i32);
+		let mut bytes = Vec::new();
+		map.write_to(&mut bytes, super::z3d::Endianness::Little).unwrap();
+		bytes[0] = b'X';
+
+		match ZArray3D::<i32>::read_from(&mut bytes.as_slice()).unwrap_err() {
+			CodecError::Format(FormatError::BadMagic) => {},
+			other => panic!("expected BadMagic, got {:?}", other),
+		}
+	}
+
+	#[test]
+	fn test_zarray3d_read_from_rejects_truncated_input(){
+		use super::z3d::CodecError;
+		let map = ZArray3D::new(4, 4, 4, 0i32);
+		let mut bytes = Vec::new();
+		map.write_to(&mut bytes, super::z3d::Endianness::Little).unwrap();
+		bytes.truncate(bytes.len() - 1);
+
+		assert!(matches!(
+			ZArray3D::<i32>::read_from(&mut bytes.as_slice()).unwrap_err(), CodecError::Io(_)));
+	}
+
+	#[test]
+	fn test_zarray2d_bytes_rejects_bad_magic(){
+		use super::z2d::ZArray2DView;
+		use super::FormatError;
+		let mut garbage = ZArray2D::new(4, 4, 0i32).as_bytes();
+		garbage[0] = b'X';
+
+		assert_eq!(ZArray2DView::<i32>::from_bytes(&garbage).unwrap_err(), FormatError::BadMagic);
+	}
+
+	#[test]
+	fn test_zarray2d_iter_visits_every_cell_once(){
+		let w = 13;
+		let h = 10;
+		let rows: Vec<Vec<i32>> = (0..h).map(|_| (0..w).collect()).collect();
+		let row_refs: Vec<&[i32]> = rows.iter().map(|r| r.as_slice()).collect();
+		let map = ZArray2D::from_rows(&row_refs);
+
+		assert_eq!(map.iter().count(), w as usize * h as usize);
+		let mut seen_indices: Vec<usize> = map.indexed_iter().map(|(_, idx, _)| idx).collect();
+		seen_indices.sort_unstable();
+		seen_indices.dedup();
+		assert_eq!(seen_indices.len(), w as usize * h as usize, "every linear index must be visited exactly once");
+		for ((x, y), _, val) in map.indexed_iter() {
+			assert_eq!(*val, *map.get(x, y).unwrap());
+		}
+	}
+
+	#[test]
+	fn test_zarray2d_iter_mut_and_map(){
+		let w = 12;
+		let h = 9;
+		let mut map = ZArray2D::new(w, h, 1i32);
+		for val in map.iter_mut() {
+			*val += 1;
+		}
+		assert_eq!(*map.get(0, 0).unwrap(), 2);
+
+		let doubled = map.map(|v| v * 2);
+		assert_eq!(doubled.dimensions(), map.dimensions());
+		assert_eq!(*doubled.get(0, 0).unwrap(), 4);
+	}
+
+	#[test]
+	fn test_zarray3d_iter_mut_and_map(){
+		let (w, h, d) = (9, 6, 5);
+		let mut map = ZArray3D::new(w, h, d, 1u8);
+		for val in map.iter_mut() {
+			*val += 1;
+		}
+		assert_eq!(map.iter().count(), w * h * d);
+		assert_eq!(*map.get(0, 0, 0).unwrap(), 2);
+
+		let widened = map.map(|v| *v as u32 * 10);
+		assert_eq!(widened.dimensions(), map.dimensions());
+		assert_eq!(*widened.get(0, 0, 0).unwrap(), 20);
+	}
+
+	#[test]
+	fn test_zarray2d_neighborhood_clamp(){
+		let map = ZArray2D::from_rows(&[&[1, 2, 3][..], &[4, 5, 6][..], &[7, 8, 9][..]]);
+
+		// corner (0,0) with radius 1 under Clamp sees its own row/col repeated
+		let sum: i32 = map.neighborhood(0, 0, 1, BoundaryPolicy::Clamp).sum();
+		assert_eq!(sum, 1*4 + 2*2 + 4*2 + 5); // four 1's, two 2's, two 4's, one 5
+
+		assert_eq!(map.neighborhood(1, 1, 1, BoundaryPolicy::Clamp).count(), 9);
+	}
+
+	#[test]
+	fn test_zarray2d_neighborhood_skip_omits_out_of_bounds(){
+		let map = ZArray2D::new(5, 5, 1i32);
+		let count = map.neighborhood(0, 0, 1, BoundaryPolicy::Skip).count();
+		assert_eq!(count, 4); // (0,0), (1,0), (0,1), (1,1) only
+	}
+
+	#[test]
+	fn test_zarray2d_apply_stencil_sum_blur(){
+		let map = ZArray2D::new(10, 10, 1i32);
+		let blurred = map.apply_stencil(1, BoundaryPolicy::Wrap, |window| window.sum::<i32>());
+		assert_eq!(*blurred.get(5, 5).unwrap(), 9);
+	}
+
+	#[test]
+	fn test_zarray3d_apply_stencil_sum_blur(){
+		let map = ZArray3D::new(6, 6, 6, 1u32);
+		let blurred = map.apply_stencil(1, BoundaryPolicy::Wrap, |window| window.sum::<u32>());
+		assert_eq!(*blurred.get(3, 3, 3).unwrap(), 27);
+	}
+
+	#[test]
+	fn test_zarray3d_advance_matches_apply_stencil(){
+		let map = ZArray3D::new(6, 6, 6, 1u32);
+		let expected = map.apply_stencil(1, BoundaryPolicy::Wrap, |window| window.sum::<u32>());
+
+		let mut dst = ZArray3D::new(6, 6, 6, 0u32);
+		map.advance(&mut dst, 1, BoundaryPolicy::Wrap, None, |window| window.iter().sum::<u32>());
+
+		assert_eq!(dst.iter().collect::<Vec<_>>(), expected.iter().collect::<Vec<_>>());
+	}
+
+	#[test]
+	fn test_zarray3d_advance_reuses_work_buffers_across_calls(){
+		use super::z3d::WorkBuffers;
+		let mut cur = ZArray3D::new(5, 5, 5, 1u32);
+		let mut next = ZArray3D::new(5, 5, 5, 0u32);
+		let mut work = WorkBuffers::new();
+
+		for _ in 0..3 {
+			cur.advance(&mut next, 1, BoundaryPolicy::Clamp, Some(&mut work),
+				|window| window.iter().sum::<u32>() / window.len() as u32);
+			std::mem::swap(&mut cur, &mut next);
+		}
+		// after ping-ponging a uniform field through an averaging kernel, every cell is still 1
+		assert!(cur.iter().all(|v| *v == 1));
+	}
+
+	#[test]
+	#[should_panic(expected = "advance: dst must have the same dimensions as self")]
+	fn test_zarray3d_advance_panics_on_dimension_mismatch(){
+		let map = ZArray3D::new(6, 6, 6, 1u32);
+		let mut dst = ZArray3D::new(5, 6, 6, 0u32);
+		map.advance(&mut dst, 1, BoundaryPolicy::Wrap, None, |window| window.iter().sum::<u32>());
+	}
+
+	#[test]
+	fn test_zarray3d_dijkstra_straight_line_through_open_field(){
+		let map = ZArray3D::new(5, 1, 1, 1.0f64);
+		let (path, cost) = map.dijkstra((0, 0, 0), (4, 0, 0), |_, &hardness| Some(hardness)).unwrap();
+		assert_eq!(path, vec![(0,0,0), (1,0,0), (2,0,0), (3,0,0), (4,0,0)]);
+		assert_eq!(cost, 4.0);
+	}
+
+	#[test]
+	fn test_zarray3d_dijkstra_start_equals_goal(){
+		let map = ZArray3D::new(3, 3, 3, 1.0f64);
+		let (path, cost) = map.dijkstra((1, 1, 1), (1, 1, 1), |_, &h| Some(h)).unwrap();
+		assert_eq!(path, vec![(1, 1, 1)]);
+		assert_eq!(cost, 0.0);
+	}
+
+	#[test]
+	fn test_zarray3d_dijkstra_routes_around_impassable_rock(){
+		// a wall of impassable "rock" at x=1 except for a gap at y=2
+		let mut map = ZArray3D::new(3, 5, 1, 1.0f64);
+		map.fill(1, 0, 0, 2, 5, 1, 100.0).unwrap();
+		map.set(1, 2, 0, 1.0).unwrap();
+
+		let passable = |_, &hardness: &f64| if hardness >= 100.0 { None } else { Some(hardness) };
+		let (path, _cost) = map.dijkstra((0, 0, 0), (2, 0, 0), passable).unwrap();
+		assert!(path.contains(&(1, 2, 0)), "path must detour through the gap at (1,2,0)");
+		assert!(!path.iter().any(|&(x, y, _)| x == 1 && y != 2), "path must never cross the rock wall");
+	}
+
+	#[test]
+	fn test_zarray3d_dijkstra_returns_none_when_goal_unreachable(){
+		let mut map = ZArray3D::new(3, 3, 1, 1.0f64);
+		map.fill(1, 0, 0, 2, 3, 1, 100.0).unwrap(); // solid wall, no gap
+		let passable = |_, &hardness: &f64| if hardness >= 100.0 { None } else { Some(hardness) };
+		assert!(map.dijkstra((0, 0, 0), (2, 0, 0), passable).is_none());
+	}
+
+	#[test]
+	fn test_zarray3d_dijkstra_returns_none_for_impassable_start_or_goal(){
+		let map = ZArray3D::new(3, 3, 3, 1.0f64);
+		let always_impassable = |_, _: &f64| None;
+		assert!(map.dijkstra((0, 0, 0), (2, 2, 2), always_impassable).is_none());
+	}
+
+	#[test]
+	fn test_zarray3d_astar_matches_dijkstra_cost_with_admissible_heuristic(){
+		let map = ZArray3D::new(6, 6, 6, 1.0f64);
+		let cost_fn = |_, &h: &f64| Some(h);
+		let manhattan = |goal: (usize, usize, usize)| move |coord: (usize, usize, usize)| {
+			(coord.0 as f64 - goal.0 as f64).abs()
+				+ (coord.1 as f64 - goal.1 as f64).abs()
+				+ (coord.2 as f64 - goal.2 as f64).abs()
+		};
+		let start = (0, 0, 0);
+		let goal = (5, 4, 3);
+		let (dijkstra_path, dijkstra_cost) = map.dijkstra(start, goal, cost_fn).unwrap();
+		let (astar_path, astar_cost) = map.astar(start, goal, cost_fn, manhattan(goal)).unwrap();
+
+		assert_eq!(dijkstra_cost, astar_cost);
+		assert_eq!(dijkstra_path.len(), astar_path.len());
+	}
+
+	#[test]
+	fn test_zarray3d_iter_visits_every_cell_once(){
+		let (w, h, d) = (9, 6, 5);
+		let map = ZArray3D::new(w, h, d, 0i32);
+
+		assert_eq!(map.iter().count(), w * h * d);
+		let mut seen_indices: Vec<usize> = map.indexed_iter().map(|(_, idx, _)| idx).collect();
+		seen_indices.sort_unstable();
+		seen_indices.dedup();
+		assert_eq!(seen_indices.len(), w * h * d, "every linear index must be visited exactly once");
+	}
+
+	#[test]
+	fn test_zarray3d_cells_visits_every_cell_in_raster_order(){
+		let (w, h, d) = (4, 3, 2);
+		let mut map = ZArray3D::new(w, h, d, 0i32);
+		for (i, v) in map.iter_mut().enumerate() {
+			*v = i as i32;
+		}
+
+		let raster: Vec<(usize, usize, usize)> = map.cells().map(|(coord, _)| coord).collect();
+		assert_eq!(raster.len(), w * h * d);
+		assert_eq!(raster[0], (0, 0, 0));
+		assert_eq!(raster[1], (1, 0, 0));
+		assert_eq!(raster[w], (0, 1, 0));
+		assert_eq!(raster[w * h], (0, 0, 1));
+
+		let mut seen: Vec<(usize, usize, usize)> = raster.clone();
+		seen.sort_unstable();
+		seen.dedup();
+		assert_eq!(seen.len(), w * h * d);
+	}
+
+	#[test]
+	fn test_zarray3d_neighbors6_counts_by_position(){
+		let map = ZArray3D::new(3, 3, 3, 1i32);
+		assert_eq!(map.neighbors6((1, 1, 1)).len(), 6, "interior cell has all 6 neighbors");
+		assert_eq!(map.neighbors6((0, 0, 0)).len(), 3, "corner cell has only 3 neighbors");
+		assert_eq!(map.neighbors6((1, 0, 0)).len(), 4, "edge cell has 4 neighbors");
+	}
+
+	#[test]
+	fn test_zarray3d_windows_skips_boundary_and_sums_interior(){
+		let map = ZArray3D::new(4, 4, 4, 1u32);
+		let windows: Vec<_> = map.windows(1).collect();
+		// only the 8 interior cells (1,1,1)..=(2,2,2) have a full radius-1 window in a 4x4x4 array
+		assert_eq!(windows.len(), 8);
+		for (_, window) in &windows {
+			assert_eq!(window.len(), 27);
+			assert_eq!(window.iter().sum::<u32>(), 27);
+		}
+	}
+
+	#[test]
+	fn test_zarray3d_view_reads_sub_region(){
+		use super::z3d::Region3D;
+		let mut map = ZArray3D::new(4, 4, 4, 0i32);
+		map.fill(1, 1, 1, 3, 3, 3, 9).unwrap();
+
+		let region = Region3D::new((1, 1, 1), (3, 3, 3));
+		let view = map.view(region).unwrap();
+		assert_eq!(view.dimensions(), (2, 2, 2));
+		for z in 0..2 { for y in 0..2 { for x in 0..2 {
+			assert_eq!(*view.get(x, y, z).unwrap(), 9);
+		} } }
+		assert!(view.get(2, 0, 0).is_err());
+	}
+
+	#[test]
+	fn test_zarray3d_view_mut_writes_sub_region(){
+		use super::z3d::Region3D;
+		let mut map = ZArray3D::new(4, 4, 4, 0i32);
+
+		let region = Region3D::new((1, 1, 1), (3, 3, 3));
+		{
+			let mut view = map.view_mut(region).unwrap();
+			for z in 0..2 { for y in 0..2 { for x in 0..2 {
+				view.set(x, y, z, 5).unwrap();
+			} } }
+		}
+		assert_eq!(*map.get(1, 1, 1).unwrap(), 5);
+		assert_eq!(*map.get(2, 2, 2).unwrap(), 5);
+		assert_eq!(*map.get(0, 0, 0).unwrap(), 0);
+	}
+
+	#[test]
+	fn test_zarray3d_view_to_owned(){
+		use super::z3d::Region3D;
+		let mut map = ZArray3D::new(4, 4, 4, 0i32);
+		map.fill(1, 1, 1, 3, 3, 3, 7).unwrap();
+
+		let owned = map.view(Region3D::new((1, 1, 1), (3, 3, 3))).unwrap().to_owned();
+		assert_eq!(owned.dimensions(), (2, 2, 2));
+		assert_eq!(owned.iter().all(|v| *v == 7), true);
+	}
+
+	#[test]
+	fn test_zarray3d_fill_region_matches_fill(){
+		use super::z3d::Region3D;
+		let mut a = ZArray3D::new(4, 4, 4, 0i32);
+		let mut b = ZArray3D::new(4, 4, 4, 0i32);
+		a.fill(1, 1, 1, 3, 3, 3, 6).unwrap();
+		b.fill_region(Region3D::new((1, 1, 1), (3, 3, 3)), 6).unwrap();
+		assert_eq!(a.iter().collect::<Vec<_>>(), b.iter().collect::<Vec<_>>());
+	}
+
+	#[test]
+	fn test_zarray3d_copy_from_pastes_block(){
+		use super::z3d::Region3D;
+		let mut dest = ZArray3D::new(4, 4, 4, 0i32);
+		let src = ZArray3D::new(2, 2, 2, 42i32);
+
+		dest.copy_from(Region3D::new((1, 1, 1), (3, 3, 3)), &src).unwrap();
+		assert_eq!(*dest.get(1, 1, 1).unwrap(), 42);
+		assert_eq!(*dest.get(2, 2, 2).unwrap(), 42);
+		assert_eq!(*dest.get(0, 0, 0).unwrap(), 0);
+	}
+
+	#[test]
+	fn test_zarray3d_copy_from_dimension_mismatch_errs(){
+		use super::z3d::Region3D;
+		let mut dest = ZArray3D::new(4, 4, 4, 0i32);
+		let src = ZArray3D::new(3, 2, 2, 1i32);
+		assert!(dest.copy_from(Region3D::new((0, 0, 0), (2, 2, 2)), &src).is_err());
+	}
+
+	#[test]
+	#[cfg(feature = "rayon")]
+	fn test_zarray3d_par_for_each_mut_visits_every_cell(){
+		let (w, h, d) = (9, 6, 5);
+		let mut map = ZArray3D::new(w, h, d, 1u8);
+		map.par_for_each_mut(|_, v| *v += 1);
+		assert_eq!(map.iter().count(), w * h * d);
+		assert_eq!(*map.get(0, 0, 0).unwrap(), 2);
+	}
+
+	#[test]
+	#[cfg(feature = "rayon")]
+	fn test_zarray3d_par_map_matches_map(){
+		let (w, h, d) = (9, 6, 5);
+		let map = ZArray3D::new(w, h, d, 3u8);
+		let sequential = map.map(|v| *v as u32 * 10);
+		let parallel = map.par_map(|v| *v as u32 * 10);
+		assert_eq!(sequential.iter().collect::<Vec<_>>(), parallel.iter().collect::<Vec<_>>());
+	}
+
+	#[test]
+	#[cfg(feature = "rayon")]
+	fn test_zarray3d_par_fill_region_matches_fill_region(){
+		use super::z3d::Region3D;
+		let mut sequential = ZArray3D::new(4, 4, 4, 0i32);
+		let mut parallel = ZArray3D::new(4, 4, 4, 0i32);
+		let region = Region3D::new((1, 1, 1), (3, 3, 3));
+		sequential.fill_region(region, 6).unwrap();
+		parallel.par_fill_region(region, 6);
+		assert_eq!(sequential.iter().collect::<Vec<_>>(), parallel.iter().collect::<Vec<_>>());
+	}
+
+	#[test]
+	fn test_zip2d_for_each_pairs_matching_coords(){
+		use super::z2d::Zip2D;
+		let a = ZArray2D::from_rows(&[&[1, 2][..], &[3, 4][..]]);
+		let b = ZArray2D::from_rows(&[&[10, 20][..], &[30, 40][..]]);
+
+		let mut sums = Vec::new();
+		Zip2D::from(&a).and(&b).for_each(|(x, y), &av, &bv| {
+			sums.push((x, y, av + bv));
+		});
+
+		sums.sort_unstable();
+		assert_eq!(sums, vec![(0, 0, 11), (0, 1, 33), (1, 0, 22), (1, 1, 44)]);
+	}
+
+	#[test]
+	#[should_panic(expected = "Zip2D: arrays must have equal dimensions")]
+	fn test_zip2d_mismatched_dimensions_panics(){
+		use super::z2d::Zip2D;
+		let a = ZArray2D::new(2, 2, 0i32);
+		let b = ZArray2D::new(3, 3, 0i32);
+		Zip2D::from(&a).and(&b).for_each(|_, _, _| {});
+	}
+
+	#[test]
+	fn test_zip3d_for_each_pairs_matching_coords(){
+		use super::z3d::Zip3D;
+		let a = ZArray3D::new(2, 2, 2, 1i32);
+		let b = ZArray3D::new(2, 2, 2, 2i32);
+
+		let mut total = 0;
+		Zip3D::from(&a).and(&b).for_each(|_, &av, &bv| {
+			total += av + bv;
+		});
+
+		assert_eq!(total, 3 * 8);
+	}
+
+	#[test]
+	fn test_zdecode_12bit_to_4bit_inverts_zorder_4bit_to_12bit(){
+		use super::z3d::{zdecode_12bit_to_4bit, zorder_4bit_to_12bit};
+		for x in 0..16u8 {
+			for y in 0..16u8 {
+				for z in 0..16u8 {
+					let idx = zorder_4bit_to_12bit(x, y, z);
+					assert_eq!(zdecode_12bit_to_4bit(idx), (x, y, z));
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn test_zdecode_24bit_inverts_zorder_8bit_to_24bit(){
+		use super::z3d::{zdecode_24bit, zorder_8bit_to_24bit};
+		let mut prng = StdRng::seed_from_u64(20220401u64);
+		for _ in 0..1000 {
+			let (x, y, z): (u8, u8, u8) = (prng.gen(), prng.gen(), prng.gen());
+			let idx = zorder_8bit_to_24bit(x, y, z);
+			assert_eq!(zdecode_24bit(idx), (x, y, z));
+		}
+	}
+
+	#[test]
+	fn test_generic_zorder_zdecode_3d_round_trip(){
+		use super::z3d::{zdecode, zorder};
+		let mut prng = StdRng::seed_from_u64(20220402u64);
+		for _ in 0..1000 {
+			let (x, y, z): (u64, u64, u64) = (
+				prng.gen::<u32>() as u64 & 0x1FFFFF,
+				prng.gen::<u32>() as u64 & 0x1FFFFF,
+				prng.gen::<u32>() as u64 & 0x1FFFFF,
+			);
+			let idx = zorder(x, y, z, 21);
+			assert_eq!(zdecode(idx, 21), (x, y, z));
+		}
+	}
+
+	#[test]
+	fn test_generic_zorder_zdecode_2d_round_trip(){
+		use super::z2d::{zdecode, zorder};
+		let mut prng = StdRng::seed_from_u64(20220403u64);
+		for _ in 0..1000 {
+			let (x, y): (u64, u64) = (prng.gen::<u32>() as u64, prng.gen::<u32>() as u64);
+			let idx = zorder(x, y, 32);
+			assert_eq!(zdecode(idx, 32), (x, y));
+		}
+	}
 }